@@ -0,0 +1,178 @@
+//! Who may generate passwords with `serve`, and how often.
+//!
+//! A bearer token, read from a file or the environment rather than argv where
+//! other users could see it, keeps the API to the clients given it. Each
+//! client address gets a bucket of requests refilled at the rate allowed per
+//! minute, so that nobody can hammer the API, be it for passwords or to guess
+//! the token: the limit is checked before the token.
+
+use sha2::{Digest, Sha256};
+use std::{collections::HashMap, fs, io, net::IpAddr, path::Path, time::Instant};
+
+/// Clients remembered before forgetting those whose bucket is full again.
+const MAX_CLIENTS: usize = 10_000;
+
+/// Why a request is turned away.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Denied {
+    /// The token is missing or wrong.
+    Unauthorized,
+    /// The client made too many requests, and may make another in this many seconds.
+    TooManyRequests(u64),
+}
+
+/// The token and the rate limit of the API.
+#[derive(Debug, Default)]
+pub struct Access {
+    /// The SHA-256 of the token, compared with that of the token given so
+    /// that the time taken tells nothing about the token.
+    token: Option<[u8; 32]>,
+    limit: Option<RateLimit>,
+}
+
+impl Access {
+    /// Access with `token`, if any, and at most `per_minute` requests a
+    /// minute from each client, if set.
+    pub fn new(token: Option<&str>, per_minute: Option<u32>) -> Self {
+        Self {
+            token: token.map(hash),
+            limit: per_minute.map(RateLimit::new),
+        }
+    }
+
+    /// Whether clients need a token.
+    pub fn has_token(&self) -> bool {
+        self.token.is_some()
+    }
+
+    /// Read the token from the first line of the file at `path`.
+    pub fn read_token(path: &Path) -> io::Result<String> {
+        let text = fs::read_to_string(path)?;
+        let token = text.lines().next().unwrap_or("").trim();
+        if token.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "the token file is empty",
+            ));
+        }
+        Ok(token.to_string())
+    }
+
+    /// Whether a request from `client` with the `authorization` header may
+    /// go through at `now`.
+    pub fn check(
+        &mut self,
+        client: Option<IpAddr>,
+        authorization: Option<&str>,
+        now: Instant,
+    ) -> Result<(), Denied> {
+        if let Some(limit) = &mut self.limit {
+            limit.take(client, now)?;
+        }
+        match self.token {
+            Some(token) => {
+                let given = authorization
+                    .and_then(|value| value.strip_prefix("Bearer "))
+                    .map(|given| hash(given.trim()));
+                if given == Some(token) {
+                    Ok(())
+                } else {
+                    Err(Denied::Unauthorized)
+                }
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+/// Buckets of requests, one for each client address.
+#[derive(Debug)]
+struct RateLimit {
+    per_minute: u32,
+    /// Requests left to each client, and when they were counted.
+    buckets: HashMap<Option<IpAddr>, (f64, Instant)>,
+}
+
+impl RateLimit {
+    fn new(per_minute: u32) -> Self {
+        Self {
+            per_minute,
+            buckets: HashMap::new(),
+        }
+    }
+
+    /// Requests left to a client with `left` at `then`, by `now`.
+    fn refill(&self, left: f64, then: Instant, now: Instant) -> f64 {
+        let rate = f64::from(self.per_minute) / 60.0;
+        let elapsed = now.saturating_duration_since(then).as_secs_f64();
+        (left + elapsed * rate).min(f64::from(self.per_minute))
+    }
+
+    /// Take a request out of the bucket of `client`.
+    fn take(&mut self, client: Option<IpAddr>, now: Instant) -> Result<(), Denied> {
+        if self.buckets.len() >= MAX_CLIENTS {
+            let full = f64::from(self.per_minute);
+            let buckets = std::mem::take(&mut self.buckets);
+            self.buckets = buckets
+                .into_iter()
+                .filter(|&(_, (left, then))| self.refill(left, then, now) < full)
+                .collect();
+        }
+
+        let full = (f64::from(self.per_minute), now);
+        let (left, then) = self.buckets.get(&client).copied().unwrap_or(full);
+        let left = self.refill(left, then, now);
+        if left >= 1.0 {
+            self.buckets.insert(client, (left - 1.0, now));
+            Ok(())
+        } else {
+            self.buckets.insert(client, (left, now));
+            let wait = (1.0 - left) * 60.0 / f64::from(self.per_minute);
+            Err(Denied::TooManyRequests(wait.ceil() as u64))
+        }
+    }
+}
+
+fn hash(token: &str) -> [u8; 32] {
+    Sha256::digest(token.as_bytes()).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn wants_the_token() {
+        let mut access = Access::new(Some("s3cret"), None);
+        let now = Instant::now();
+        assert_eq!(access.check(None, Some("Bearer s3cret"), now), Ok(()));
+        for wrong in [
+            None,
+            Some("Bearer s3cre"),
+            Some("s3cret"),
+            Some("Basic s3cret"),
+        ] {
+            assert_eq!(access.check(None, wrong, now), Err(Denied::Unauthorized));
+        }
+        assert_eq!(Access::default().check(None, None, now), Ok(()));
+    }
+
+    #[test]
+    fn limits_each_client() {
+        let mut access = Access::new(None, Some(2));
+        let (alice, bob) = (Some([10, 0, 0, 1].into()), Some([10, 0, 0, 2].into()));
+        let now = Instant::now();
+        assert_eq!(access.check(alice, None, now), Ok(()));
+        assert_eq!(access.check(alice, None, now), Ok(()));
+        assert_eq!(
+            access.check(alice, None, now),
+            Err(Denied::TooManyRequests(30))
+        );
+        assert_eq!(access.check(bob, None, now), Ok(()));
+        // one request back every 30 seconds
+        let later = now + Duration::from_secs(30);
+        assert_eq!(access.check(alice, None, later), Ok(()));
+        assert!(access.check(alice, None, later).is_err());
+    }
+}
@@ -0,0 +1,162 @@
+//! Laying a password out for screen readers.
+//!
+//! Screen readers read a passphrase as one long word, say nothing of case and
+//! skip most punctuation at their usual verbosity. Each word and each
+//! separator gets a line of its own instead, with the capitals announced and
+//! every character other than a letter named.
+
+use crate::phonetic;
+use xkpass::Password;
+
+/// `password` with a line for each word and each separator.
+pub fn describe(password: &Password) -> String {
+    let mut lines = Vec::new();
+    for (i, piece) in pieces(password).iter().enumerate() {
+        if i % 2 == 0 {
+            lines.push(word(i / 2 + 1, piece));
+        } else {
+            lines.push(separator(piece));
+        }
+    }
+    lines.join("\n")
+}
+
+/// The words and separators of `password` in turn, with the characters
+/// inserted inside them.
+fn pieces(password: &Password) -> Vec<String> {
+    let mut separators = password.separators().iter();
+    let mut insertions = password.insertions().iter().peekable();
+    let mut position = 0;
+    let mut pieces = Vec::new();
+    for word in password.words() {
+        for piece in [Some(word), separators.next()].into_iter().flatten() {
+            let mut text = String::new();
+            for c in piece.chars() {
+                while let Some(insertion) = insertions.next_if(|i| i.position == position) {
+                    text += &insertion.text;
+                    position += insertion.text.len();
+                }
+                text.push(c);
+                position += c.len_utf8();
+            }
+            pieces.push(text);
+        }
+    }
+    if let Some(last) = pieces.last_mut() {
+        last.extend(insertions.map(|insertion| insertion.text.as_str()));
+    }
+    pieces
+}
+
+/// The line of the `number`th word, with its capitals and other characters.
+fn word(number: usize, word: &str) -> String {
+    let letters = word.chars().filter(|c| c.is_alphabetic());
+    let mut notes = Vec::new();
+    if letters.clone().count() > 1 && letters.clone().all(char::is_uppercase) {
+        notes.push(String::from("all capitals"));
+    } else {
+        notes.extend(
+            letters
+                .filter(|c| c.is_uppercase())
+                .map(|c| format!("capital {}", c)),
+        );
+    }
+    notes.extend(
+        word.chars()
+            .filter(|c| !c.is_alphabetic())
+            .map(|c| format!("with {}", phonetic::name(c))),
+    );
+
+    let mut line = format!("word {}: {}", number, word);
+    for note in notes {
+        line += &format!(", {}", note);
+    }
+    line
+}
+
+/// The line of a separator, naming each of its characters but letters,
+/// which are left in words such as the numbers of `--digits-as-words`.
+fn separator(separator: &str) -> String {
+    if separator.is_empty() {
+        return String::from("no separator");
+    }
+    let mut names = Vec::new();
+    let mut letters = String::new();
+    for c in separator.chars() {
+        if c.is_alphabetic() {
+            letters.push(c);
+            continue;
+        }
+        if !letters.is_empty() {
+            names.push(std::mem::take(&mut letters));
+        }
+        names.push(phonetic::name(c));
+    }
+    if !letters.is_empty() {
+        names.push(letters);
+    }
+    format!("separator: {}", names.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use xkpass::{Case, Config, List};
+
+    #[test]
+    fn announces_case_and_symbols() {
+        assert_eq!(word(1, "battery"), "word 1: battery");
+        assert_eq!(word(2, "Horse"), "word 2: Horse, capital H");
+        assert_eq!(word(3, "STAPLE"), "word 3: STAPLE, all capitals");
+        assert_eq!(
+            word(4, "batt!ery"),
+            "word 4: batt!ery, with exclamation mark"
+        );
+        assert_eq!(separator(" "), "separator: space");
+        assert_eq!(separator("42"), "separator: digit four, digit two");
+        assert_eq!(separator(""), "no separator");
+        assert_eq!(
+            separator(" forty-two "),
+            "separator: space, forty, dash, two, space"
+        );
+    }
+
+    #[test]
+    fn puts_each_piece_on_its_line() {
+        let config = Config {
+            number: 3,
+            separator: String::from("-"),
+            list: List::Short1,
+            case: Case::Capitalized,
+        };
+        let password = xkpass::generate_password(config)
+            .unwrap()
+            .embed_symbol(&['#'], &mut rand::thread_rng());
+        let description = describe(&password);
+        let lines: Vec<&str> = description.lines().collect();
+
+        assert_eq!(lines.len(), 5);
+        assert!(lines[1] == "separator: dash" && lines[3] == "separator: dash");
+        assert_eq!(
+            lines
+                .iter()
+                .filter(|line| line.contains("with hash"))
+                .count(),
+            1
+        );
+        let words: String = [0, 2, 4]
+            .iter()
+            .map(|&i| {
+                lines[i]
+                    .split(": ")
+                    .nth(1)
+                    .unwrap()
+                    .split(',')
+                    .next()
+                    .unwrap()
+            })
+            .collect::<Vec<_>>()
+            .join("-");
+        assert_eq!(words, password.expose());
+    }
+}
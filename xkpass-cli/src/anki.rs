@@ -0,0 +1,96 @@
+//! Exporting a password as an Anki deck, to learn it by spaced repetition.
+//!
+//! The deck is a CSV file of cloze notes, with the headers Anki reads to pick
+//! the separator and note type on import. One note has a card per word, the
+//! word hidden behind its first letter, and another hides every word at once.
+
+use std::{
+    fs::File,
+    io::{self, Write},
+    path::Path,
+};
+use xkpass::Password;
+
+/// Write the deck for `password` to `path`, on Unix readable by the current
+/// user only.
+pub fn write(path: &Path, password: &Password) -> io::Result<()> {
+    let mut file = File::create(path)?;
+    #[cfg(unix)]
+    {
+        use std::{fs, os::unix::fs::PermissionsExt};
+        fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+    }
+
+    file.write_all(deck(password).as_bytes())
+}
+
+/// The deck for `password`, as the contents of a CSV file.
+fn deck(password: &Password) -> String {
+    let words = password.words();
+    let each = cloze(password, |i| i + 1);
+    let all = cloze(password, |_| 1);
+
+    let mut deck = String::from("#separator:Comma\n#html:false\n#notetype:Cloze\n#tags:xkpass\n");
+    deck += &format!(
+        "{},{}\n",
+        quote(&each),
+        quote(&format!("one word of the {}", words.len()))
+    );
+    deck += &format!(
+        "{},{}\n",
+        quote(&all),
+        quote(&format!("all {} words", words.len()))
+    );
+    deck
+}
+
+/// `password` with each word hidden in the cloze numbered by `number`, its
+/// first letter as the hint.
+fn cloze(password: &Password, number: impl Fn(usize) -> usize) -> String {
+    let mut text = String::new();
+    for (i, word) in password.words().iter().enumerate() {
+        if i > 0 {
+            text += &password.separators()[i - 1];
+        }
+        let hint: String = word.chars().take(1).collect();
+        text += &format!("{{{{c{}::{}::{}…}}}}", number(i), word, hint);
+    }
+    text
+}
+
+/// Quote a CSV field.
+fn quote(field: &str) -> String {
+    format!("\"{}\"", field.replace('"', "\"\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use xkpass::{Config, List};
+
+    #[test]
+    fn hides_words_behind_their_first_letter() {
+        let words = ["oak", "elm"];
+        let config = Config {
+            number: 2,
+            separator: "\"".to_string(),
+            list: List::Long,
+            ..Config::default()
+        };
+        let password = xkpass::generate_password_from(&words, config).unwrap();
+        let (first, second) = match password.words()[0].as_str() {
+            "oak" => ("oak", "elm"),
+            _ => ("elm", "oak"),
+        };
+        let (a, b) = (&first[..1], &second[..1]);
+
+        assert_eq!(
+            deck(&password),
+            format!(
+                "#separator:Comma\n#html:false\n#notetype:Cloze\n#tags:xkpass\n\
+                 \"{{{{c1::{first}::{a}…}}}}\"\"{{{{c2::{second}::{b}…}}}}\",\"one word of the 2\"\n\
+                 \"{{{{c1::{first}::{a}…}}}}\"\"{{{{c1::{second}::{b}…}}}}\",\"all 2 words\"\n"
+            )
+        );
+    }
+}
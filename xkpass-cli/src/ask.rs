@@ -0,0 +1,67 @@
+//! Handing passwords to `systemd-ask-password` instead of printing them.
+//!
+//! The password is added to the user's kernel keyring under a key name, the
+//! same cache systemd uses itself, so that
+//! `systemd-ask-password --accept-cached --keyname=<name>` and units unlocking
+//! disks with it pick the password up without it ever being written to stdout.
+
+use std::{ffi::CString, io};
+
+/// `KEY_SPEC_USER_KEYRING` from `linux/keyctl.h`.
+const KEY_SPEC_USER_KEYRING: libc::c_long = -4;
+/// `KEYCTL_SET_TIMEOUT` from `linux/keyctl.h`.
+const KEYCTL_SET_TIMEOUT: libc::c_long = 15;
+/// Seconds until the kernel discards the key, the same as systemd's own cache.
+const TIMEOUT: libc::c_long = 150;
+
+/// Store `password` in the user keyring as the cached answer for `keyname`.
+pub fn store(keyname: &str, password: &str) -> io::Result<()> {
+    let description = description(keyname)?;
+
+    // SAFETY: the description is NUL-terminated and the payload is valid for its length
+    let key = unsafe {
+        libc::syscall(
+            libc::SYS_add_key,
+            c"user".as_ptr(),
+            description.as_ptr(),
+            password.as_ptr(),
+            password.len(),
+            KEY_SPEC_USER_KEYRING,
+        )
+    };
+    if key == -1 {
+        return Err(io::Error::last_os_error());
+    }
+
+    // SAFETY: `key` is the serial number returned by `add_key`
+    if unsafe { libc::syscall(libc::SYS_keyctl, KEYCTL_SET_TIMEOUT, key, TIMEOUT) } == -1 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(())
+}
+
+/// Check the key name, which the kernel wants as a C string.
+fn description(keyname: &str) -> io::Result<CString> {
+    if keyname.is_empty() || keyname.contains(char::is_control) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("invalid key name {:?}", keyname),
+        ));
+    }
+
+    CString::new(keyname).map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checks_key_names() {
+        assert!(description("cryptsetup").is_ok());
+        assert!(description("").is_err());
+        assert!(description("crypt\nsetup").is_err());
+        assert!(description("crypt\0setup").is_err());
+    }
+}
@@ -0,0 +1,142 @@
+//! Sheets of backup codes made of words, to print and keep in a safe, rather
+//! than the usual rows of hex digits.
+//!
+//! Every code is a few words of the first short list, the easiest to type,
+//! followed by a check character: the first 5 bits of the SHA-256 of the
+//! words, in the Crockford alphabet. Typing a code back wrong is then caught
+//! 31 times out of 32 before a service counts it as an attempt.
+
+use crate::render::{Format, Renderer};
+use rand::Rng;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::io::{self, BufRead};
+use xkpass::List;
+
+/// Crockford's base32 alphabet, without the letters mistaken for digits.
+const CHECK_ALPHABET: &[u8; 32] = b"0123456789abcdefghjkmnpqrstvwxyz";
+
+/// A backup code, the record of `backup-codes`.
+#[derive(Serialize, Debug)]
+struct Code {
+    number: usize,
+    code: String,
+}
+
+/// Print a sheet of `sets` codes of `words` words each, in `format`.
+pub fn run(sets: usize, words: usize, format: Format) -> Result<(), String> {
+    let codes = codes(sets, words, &mut rand::thread_rng())?;
+    let bits = words as f64 * xkpass::word_entropy(List::Short1.len(), 1);
+
+    let mut out = Renderer::new(format, io::stdout().lock());
+    let width = sets.to_string().len();
+    out.plain(format_args!(
+        "BACKUP CODES\n\n\
+         Each code signs you in once, when you can't use your password or your second factor.\n\
+         Cross it out once used. Keep this sheet somewhere safe.\n\n"
+    ))
+    .and_then(|()| {
+        for (i, code) in codes.into_iter().enumerate() {
+            let line = format!("  {:>width$}.  {}", i + 1, code, width = width);
+            out.record(
+                &Code {
+                    number: i + 1,
+                    code,
+                },
+                line,
+            )?;
+        }
+        out.plain(format_args!(
+            "\n{} bits of entropy per code; the last character is a check.\n",
+            bits.floor()
+        ))
+    })
+    .and_then(|()| out.finish())
+    .map_err(|err| err.to_string())
+}
+
+/// Read a code on stdin and fail unless its check character matches its words.
+pub fn check_stdin() -> Result<(), String> {
+    let mut line = String::new();
+    io::stdin()
+        .lock()
+        .read_line(&mut line)
+        .map_err(|err| format!("stdin: {}", err))?;
+    if check(&line) {
+        eprintln!("The check character matches.");
+        Ok(())
+    } else {
+        Err(String::from(
+            "the check character doesn't match, a word of the code is mistyped",
+        ))
+    }
+}
+
+/// `sets` distinct codes of `words` words each.
+fn codes(sets: usize, words: usize, rng: &mut impl Rng) -> Result<Vec<String>, String> {
+    let mut codes: Vec<String> = Vec::with_capacity(sets);
+    while codes.len() < sets {
+        let picked = xkpass::sample_words_with(List::Short1.as_slice(), words, rng)
+            .map_err(|err| err.to_string())?;
+        let code = format!("{} {}", picked.join(" "), check_character(&picked));
+        // two codes alike are unlikely, but would leave one code fewer
+        if !codes.contains(&code) {
+            codes.push(code);
+        }
+    }
+    Ok(codes)
+}
+
+/// The check character of `words`, whatever their case.
+fn check_character(words: &[&str]) -> char {
+    let mut hasher = Sha256::new();
+    for (i, word) in words.iter().enumerate() {
+        if i > 0 {
+            hasher.update(b" ");
+        }
+        hasher.update(word.to_lowercase().as_bytes());
+    }
+    char::from(CHECK_ALPHABET[usize::from(hasher.finalize()[0] >> 3)])
+}
+
+/// Whether the last character of `code` is the check character of its words,
+/// with any spacing and case.
+fn check(code: &str) -> bool {
+    let mut words: Vec<&str> = code.split_whitespace().collect();
+    match words.pop() {
+        Some(last) if !words.is_empty() => last
+            .parse::<char>()
+            .is_ok_and(|c| c.to_ascii_lowercase() == check_character(&words)),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn catches_mistyped_codes() {
+        let codes = codes(10, 3, &mut rand::thread_rng()).unwrap();
+        assert_eq!(codes.len(), 10);
+        for code in &codes {
+            assert!(check(code), "{}", code);
+            assert!(check(&format!("  {}\n", code.to_uppercase())));
+            let words: Vec<&str> = code.split(' ').collect();
+            assert_eq!(words.len(), 4);
+            assert!(words[..3].iter().all(|word| List::Short1.contains(word)));
+        }
+        assert!(!check("acid"));
+        assert!(!check(""));
+
+        // most codes with a word swapped for another no longer check
+        let caught = List::Short1.as_slice()[..100]
+            .iter()
+            .filter(|word| {
+                let check = check_character(&["acid", "acorn", "acre"]);
+                check_character(&["acid", "acorn", word]) != check
+            })
+            .count();
+        assert!(caught > 80, "{}", caught);
+    }
+}
@@ -0,0 +1,239 @@
+//! Generating passwords with different settings in one run, for provisioning
+//! scripts that would otherwise start `xkpass` once per password.
+//!
+//! Every line of stdin holds the settings of one password, either as
+//! `key=value` pairs or as a JSON object with the fields of the HTTP API:
+//!
+//! ```text
+//! number=4 list=short1 case=capitalized
+//! {"number": 5, "separator": " "}
+//! ```
+//!
+//! Blank lines and lines starting with `#` are skipped. An invalid line stops
+//! the batch after the passwords of the lines before it, with its own exit
+//! status.
+//!
+//! With `--format ndjson`, for orchestration systems, every line is a JSON
+//! request which may also have an `id` to find its result by, a `count` of
+//! passwords and a `min_entropy` they must have, and is answered by a line of
+//! JSON with the passwords and their entropy, or the error:
+//!
+//! ```text
+//! {"id": "db", "list": "short1", "number": 8, "count": 2, "min_entropy": 80}
+//! {"id":"db","passwords":["...","..."],"entropy":82.7}
+//! ```
+//!
+//! An invalid request doesn't stop the batch then, but the exit status still
+//! tells that one was.
+
+use crate::{
+    render::Format,
+    request::GenerateRequest,
+    status::{Failure, Status},
+};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::io::{self, BufRead, Write};
+use xkpass::{Config, PasswordGenerator};
+
+/// Largest number of passwords a single request of NDJSON may ask for.
+const MAX_COUNT: usize = 1000;
+
+/// The answer to a request of NDJSON.
+#[derive(Serialize, Debug, Default)]
+struct Answer {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    passwords: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    entropy: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Answer every line of stdin, as NDJSON requests with `format` ndjson and
+/// as settings of one password otherwise.
+pub fn run(defaults: Config, format: Format) -> Result<(), Failure> {
+    if format == Format::Ndjson {
+        return run_ndjson(&defaults);
+    }
+    let mut out = io::BufWriter::new(io::stdout().lock());
+    for (i, line) in io::stdin().lock().lines().enumerate() {
+        let line = line?;
+        let password = match generate(&line, &defaults) {
+            Some(Ok(password)) => password,
+            Some(Err(err)) => {
+                out.flush()?;
+                return Err(Failure::new(
+                    Status::PartialBatch,
+                    format!("line {}: {}", i + 1, err),
+                ));
+            }
+            None => continue,
+        };
+        writeln!(out, "{}", password)?;
+    }
+
+    Ok(out.flush()?)
+}
+
+/// Answer one JSON request per line of stdin with a line of JSON, going on
+/// after invalid requests.
+fn run_ndjson(defaults: &Config) -> Result<(), Failure> {
+    let mut out = io::BufWriter::new(io::stdout().lock());
+    let mut invalid = 0;
+    for (i, line) in io::stdin().lock().lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let answer = answer(&line, defaults);
+        if let Some(err) = &answer.error {
+            invalid += 1;
+            eprintln!("xkpass: line {}: {}", i + 1, err);
+        }
+        serde_json::to_writer(&mut out, &answer).map_err(io::Error::from)?;
+        writeln!(out)?;
+        // orchestrators wait for each answer before sending the next request
+        out.flush()?;
+    }
+
+    if invalid > 0 {
+        return Err(Failure::new(
+            Status::PartialBatch,
+            format!("some requests were invalid, {} in all", invalid),
+        ));
+    }
+    Ok(())
+}
+
+/// The answer to the JSON request `line`.
+fn answer(line: &str, defaults: &Config) -> Answer {
+    let mut fields = match serde_json::from_str(line) {
+        Ok(Value::Object(fields)) => fields,
+        Ok(_) => return failed(None, String::from("requests are JSON objects")),
+        Err(err) => return failed(None, format!("invalid request: {}", err)),
+    };
+    let id = fields.remove("id");
+    match generate_many(&mut fields, defaults) {
+        Ok((passwords, entropy)) => Answer {
+            id,
+            passwords: Some(passwords),
+            entropy: Some(entropy),
+            error: None,
+        },
+        Err(err) => failed(id, err),
+    }
+}
+
+fn failed(id: Option<Value>, error: String) -> Answer {
+    Answer {
+        id,
+        error: Some(error),
+        ..Answer::default()
+    }
+}
+
+/// The passwords and their entropy of a request, its `count` and `min_entropy`
+/// taken out of `fields` and the rest being settings.
+fn generate_many(
+    fields: &mut Map<String, Value>,
+    defaults: &Config,
+) -> Result<(Vec<String>, f64), String> {
+    let count = match fields.remove("count") {
+        None => 1,
+        Some(count) => count
+            .as_u64()
+            .and_then(|count| usize::try_from(count).ok())
+            .filter(|count| (1..=MAX_COUNT).contains(count))
+            .ok_or_else(|| format!("count must be between 1 and {}", MAX_COUNT))?,
+    };
+    let min_entropy = match fields.remove("min_entropy") {
+        None => None,
+        Some(min) => Some(min.as_f64().ok_or("min_entropy must be a number")?),
+    };
+
+    let request: GenerateRequest = serde_json::from_value(Value::Object(std::mem::take(fields)))
+        .map_err(|err| format!("invalid request: {}", err))?;
+    let generator = PasswordGenerator::builder()
+        .config(request.into_config(defaults)?)
+        .build()
+        .map_err(|err| err.to_string())?;
+    let entropy = generator.entropy();
+    if let Some(min) = min_entropy.filter(|&min| entropy < min) {
+        return Err(format!(
+            "the passwords would have {:.1} bits of entropy, less than the {} of min_entropy",
+            entropy, min
+        ));
+    }
+    let passwords = generator
+        .generate_n(count)
+        .into_iter()
+        .map(|password| password.into_string())
+        .collect();
+    Ok((passwords, entropy))
+}
+
+/// Generate the password a line asks for, or nothing for blank lines and comments.
+fn generate(line: &str, defaults: &Config) -> Option<Result<String, String>> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+
+    let request = if line.starts_with('{') {
+        GenerateRequest::from_json(line.as_bytes())
+    } else {
+        GenerateRequest::from_pairs(line)
+    };
+
+    Some(request.and_then(|request| request.generate(defaults)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_both_formats() {
+        let defaults = Config {
+            separator: String::from("+"),
+            ..Config::default()
+        };
+
+        let password = generate("number=3 case=upper", &defaults).unwrap().unwrap();
+        assert_eq!(password.split('+').count(), 3);
+        assert_eq!(password, password.to_uppercase());
+
+        let password = generate(r#"{"number": 2, "separator": "."}"#, &defaults)
+            .unwrap()
+            .unwrap();
+        assert_eq!(password.split('.').count(), 2);
+
+        assert!(generate("  # comment", &defaults).is_none());
+        assert!(generate("number=0", &defaults).unwrap().is_err());
+    }
+
+    #[test]
+    fn answers_ndjson_requests() {
+        let defaults = Config::default();
+        let answer = |line| serde_json::to_value(super::answer(line, &defaults)).unwrap();
+
+        let ok =
+            answer(r#"{"id": "db", "list": "short1", "number": 8, "count": 2, "min_entropy": 80}"#);
+        assert_eq!(ok["id"], "db");
+        assert_eq!(ok["passwords"].as_array().unwrap().len(), 2);
+        assert!(ok["entropy"].as_f64().unwrap() > 80.0);
+        assert!(ok.get("error").is_none());
+        assert!(answer("{}")["passwords"][0].is_string());
+
+        let weak = answer(r#"{"id": 7, "number": 3, "min_entropy": 80}"#);
+        assert_eq!(weak["id"], 7);
+        assert!(weak["error"].as_str().unwrap().contains("min_entropy"));
+        assert!(weak.get("passwords").is_none());
+        for invalid in [r#"{"count": 0}"#, r#"{"colour": "red"}"#, "[]", "number=4"] {
+            assert!(answer(invalid)["error"].is_string(), "{}", invalid);
+        }
+    }
+}
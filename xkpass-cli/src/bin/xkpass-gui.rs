@@ -0,0 +1,150 @@
+//! A small window for generating passwords without opening a terminal.
+
+use eframe::egui;
+use xkpass::{Case, Config, List, Password, VariantNames};
+
+fn main() -> eframe::Result {
+    let options = eframe::NativeOptions {
+        viewport: egui::ViewportBuilder::default()
+            .with_inner_size([520.0, 260.0])
+            .with_min_inner_size([360.0, 220.0]),
+        ..Default::default()
+    };
+
+    eframe::run_native("xkpass", options, Box::new(|_| Ok(Box::new(App::new()))))
+}
+
+/// Settings chosen in the window and the password they produced.
+struct App {
+    config: Config,
+    password: Result<Password, String>,
+}
+
+impl App {
+    fn new() -> Self {
+        let config = Config::default();
+        Self {
+            password: generate(&config),
+            config,
+        }
+    }
+}
+
+impl eframe::App for App {
+    fn ui(&mut self, ui: &mut egui::Ui, _frame: &mut eframe::Frame) {
+        egui::CentralPanel::default().show(ui, |ui| {
+            let mut changed = false;
+
+            egui::Grid::new("settings")
+                .num_columns(2)
+                .spacing([16.0, 8.0])
+                .show(ui, |ui| {
+                    ui.label("Words");
+                    changed |= ui
+                        .add(egui::Slider::new(&mut self.config.number, 1..=12))
+                        .changed();
+                    ui.end_row();
+
+                    ui.label("List");
+                    changed |= variant_picker(ui, "list", &mut self.config.list, List::name);
+                    ui.end_row();
+
+                    ui.label("Case");
+                    changed |= variant_picker(ui, "case", &mut self.config.case, Case::name);
+                    ui.end_row();
+
+                    ui.label("Separator");
+                    changed |= ui
+                        .add(
+                            egui::TextEdit::singleline(&mut self.config.separator)
+                                .desired_width(60.0),
+                        )
+                        .changed();
+                    ui.end_row();
+                });
+
+            ui.separator();
+
+            match &self.password {
+                Ok(password) => {
+                    ui.add(
+                        egui::Label::new(
+                            egui::RichText::new(password.expose())
+                                .monospace()
+                                .size(18.0),
+                        )
+                        .wrap(),
+                    );
+                    ui.label(format!("{:.1} bits of entropy", password.entropy()));
+                }
+                Err(err) => {
+                    ui.colored_label(ui.visuals().error_fg_color, err);
+                }
+            }
+
+            ui.horizontal(|ui| {
+                changed |= ui.button("Regenerate").clicked();
+                if let Ok(password) = &self.password {
+                    if ui.button("Copy").clicked() {
+                        ui.ctx().copy_text(password.expose().to_string());
+                    }
+                }
+            });
+
+            if changed {
+                self.password = generate(&self.config);
+            }
+        });
+    }
+}
+
+/// Show a drop-down with every variant of `T`, returning whether the selection changed.
+fn variant_picker<T>(
+    ui: &mut egui::Ui,
+    id: &str,
+    value: &mut T,
+    name: fn(&T) -> &'static str,
+) -> bool
+where
+    T: VariantNames + std::str::FromStr,
+{
+    let mut selected = name(value);
+    egui::ComboBox::from_id_salt(id)
+        .selected_text(selected)
+        .show_ui(ui, |ui| {
+            for variant in T::VARIANTS {
+                ui.selectable_value(&mut selected, *variant, *variant);
+            }
+        });
+
+    match selected.parse() {
+        Ok(parsed) if selected != name(value) => {
+            *value = parsed;
+            true
+        }
+        _ => false,
+    }
+}
+
+fn generate(config: &Config) -> Result<Password, String> {
+    xkpass::generate_password(config.clone()).map_err(|err| err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn regenerates_with_settings() {
+        let mut config = Config {
+            number: 3,
+            separator: String::from("+"),
+            ..Config::default()
+        };
+        let password = generate(&config).unwrap();
+        assert_eq!(password.expose().split('+').count(), 3);
+
+        config.separator = String::from("\t");
+        assert!(generate(&config).is_err());
+    }
+}
@@ -0,0 +1,131 @@
+//! Generating many passwords at once, spread over every core.
+//!
+//! Each worker thread draws from its own generator, seeded from the operating
+//! system, and the passwords are printed in the order of their index whatever
+//! the thread that generated them.
+
+use crate::{
+    render::{Format, Generated, Renderer},
+    separators,
+};
+use rayon::prelude::*;
+use std::io::{self, Write};
+use xkpass::{Config, Password};
+
+/// Number of passwords generated in parallel before they are printed, which
+/// bounds memory use for very large counts.
+const CHUNK: usize = 1 << 16;
+
+/// The word at each index of a list of words, shared by the worker threads.
+pub type Lookup<'a, 'w> = &'a (dyn Fn(usize) -> &'w str + Sync);
+
+/// Print `count` passwords out of the `pool` words looked up with `word`, one
+/// per line in plain text, each with `recipe` in the other formats.
+pub fn print(
+    pool: usize,
+    word: Lookup,
+    config: &Config,
+    count: usize,
+    format: Format,
+    recipe: &str,
+) -> io::Result<()> {
+    let mut out = Renderer::new(format, io::BufWriter::new(io::stdout().lock()));
+    match write(&mut out, pool, word, config, count, recipe).and_then(|()| out.finish()) {
+        // stop quietly when piped to a program such as `head`
+        Err(err) if err.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        result => result,
+    }
+}
+
+fn write<W: Write>(
+    out: &mut Renderer<W>,
+    pool: usize,
+    word: Lookup,
+    config: &Config,
+    count: usize,
+    recipe: &str,
+) -> io::Result<()> {
+    let mut left = count;
+    while left > 0 {
+        let chunk = left.min(CHUNK);
+        let passwords = generate(pool, word, config, chunk)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+        for password in &passwords {
+            let password = password.expose();
+            let record = Generated {
+                password,
+                recipe,
+                key: None,
+                salt: None,
+                kdf: None,
+            };
+            out.record(&record, password)?;
+        }
+        left -= chunk;
+    }
+
+    Ok(())
+}
+
+/// Generate `n` passwords in parallel, in the order of their index.
+fn generate(
+    pool: usize,
+    word: Lookup,
+    config: &Config,
+    n: usize,
+) -> Result<Vec<Password>, xkpass::Error> {
+    // the thread-local generator of each worker is seeded on its own from the OS
+    (0..n)
+        .into_par_iter()
+        .map(|_| {
+            let rng = &mut rand::thread_rng();
+            let password = xkpass::generate_password_indexed(pool, word, config, rng)?;
+            Ok(separators::apply(password, &config.separator, false, rng))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_every_password() {
+        let config = Config {
+            number: 2,
+            separator: String::from("+"),
+            ..Config::default()
+        };
+        let mut out = Vec::new();
+        let mut renderer = Renderer::new(Format::Plain, &mut out);
+        let list = xkpass::List::Short1.as_slice();
+        write(
+            &mut renderer,
+            list.len(),
+            &|i| list[i],
+            &config,
+            CHUNK + 3,
+            "",
+        )
+        .unwrap();
+        renderer.finish().unwrap();
+
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(out.lines().count(), CHUNK + 3);
+        assert!(out.lines().all(|line| line.split('+').count() == 2));
+    }
+
+    #[test]
+    fn fails_before_printing() {
+        let config = Config {
+            number: 0,
+            ..Config::default()
+        };
+        let mut out = Vec::new();
+        let mut renderer = Renderer::new(Format::Plain, &mut out);
+        let list = xkpass::List::Short1.as_slice();
+        assert!(write(&mut renderer, list.len(), &|i| list[i], &config, 10, "").is_err());
+        renderer.finish().unwrap();
+        assert!(out.is_empty());
+    }
+}
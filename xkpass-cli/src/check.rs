@@ -0,0 +1,212 @@
+//! Estimating the strength of an existing passphrase made of words from a known list.
+//!
+//! The passphrase is read on stdin rather than taken as an argument, so it
+//! doesn't end up in the shell history or in the process list.
+//!
+//! Words are looked up in the index of the embedded lists built at compile
+//! time, and in a hash set of the words of each of your own lists.
+
+use crate::{
+    memorability::{self, Memorability},
+    render::{Format, Renderer},
+    separators,
+    word_lists::{ListName, Source},
+};
+use serde::{Serialize, Serializer};
+use std::{
+    collections::HashSet,
+    fmt::{self, Display},
+    io::{self, BufRead},
+};
+use xkpass::List;
+
+/// The words of a list, ready to be looked up.
+enum Words<'a> {
+    Embedded(List),
+    Custom(HashSet<&'a str>),
+}
+
+impl Words<'_> {
+    fn contains(&self, word: &str) -> bool {
+        match self {
+            Words::Embedded(list) => list.contains(word),
+            Words::Custom(words) => words.contains(word),
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            Words::Embedded(list) => list.len(),
+            Words::Custom(words) => words.len(),
+        }
+    }
+}
+
+/// How strong a passphrase is, or why that can't be told.
+#[derive(Serialize, Debug)]
+struct Report {
+    words: usize,
+    list: Option<String>,
+    bits: Option<f64>,
+    #[serde(serialize_with = "score")]
+    memorability: Option<Memorability>,
+    problem: Option<&'static str>,
+}
+
+impl Report {
+    fn problem(words: usize, problem: &'static str) -> Self {
+        Self {
+            words,
+            list: None,
+            bits: None,
+            memorability: None,
+            problem: Some(problem),
+        }
+    }
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.list, self.bits, &self.memorability) {
+            (Some(list), Some(bits), Some(memorability)) => write!(
+                f,
+                "{} words from the {} list: {:.1} bits of entropy, {}",
+                self.words, list, bits, memorability
+            ),
+            _ => f.write_str(self.problem.unwrap_or_default()),
+        }
+    }
+}
+
+/// Only the score of the memorability goes in the records, the rest is for reading.
+fn score<S: Serializer>(memorability: &Option<Memorability>, s: S) -> Result<S::Ok, S::Error> {
+    memorability.as_ref().map(|m| m.score).serialize(s)
+}
+
+/// Read a passphrase on stdin and report how strong it is, assuming it was
+/// generated like xkpass does out of the smallest known list containing its words.
+pub fn run(separator: &str, format: Format) -> io::Result<()> {
+    let mut passphrase = String::new();
+    io::stdin().lock().read_line(&mut passphrase)?;
+    let passphrase = passphrase.trim_end_matches(&['\r', '\n'][..]);
+
+    let names = ListName::all();
+    let sources = names
+        .iter()
+        .map(ListName::open)
+        .collect::<io::Result<Vec<_>>>()?;
+    let lists = sources
+        .iter()
+        .zip(names)
+        .map(|(source, name)| {
+            let words = match source {
+                Source::Embedded(list) => Words::Embedded(list.clone()),
+                _ => Words::Custom(source.iter().collect()),
+            };
+            Ok((words, name))
+        })
+        .collect::<io::Result<Vec<_>>>()?;
+
+    let report = check(passphrase, separator, &lists);
+    let mut out = Renderer::new(format, io::stdout().lock());
+    out.record(&report, &report)?;
+    out.finish()
+}
+
+/// Describe the strength of `passphrase`, split into words by `separator`,
+/// or by digits for random digit separators.
+fn check(passphrase: &str, separator: &str, lists: &[(Words, ListName)]) -> Report {
+    let parts: Vec<&str> = match separators::digits(separator) {
+        Some(_) => passphrase.split(|c: char| c.is_ascii_digit()).collect(),
+        None => passphrase.split(separator).collect(),
+    };
+    let words: Vec<String> = parts
+        .into_iter()
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    if words.is_empty() {
+        return Report::problem(0, "the passphrase is empty");
+    }
+
+    let distinct: HashSet<&String> = words.iter().collect();
+    if distinct.len() != words.len() {
+        return Report::problem(
+            words.len(),
+            "the passphrase repeats words, xkpass never does",
+        );
+    }
+
+    let best = lists
+        .iter()
+        .filter(|(list, _)| words.iter().all(|word| list.contains(word)))
+        .min_by_key(|(list, _)| list.len());
+
+    match best {
+        Some((list, name)) => Report {
+            words: words.len(),
+            list: Some(name.to_string()),
+            bits: Some(xkpass::word_entropy(list.len(), words.len())),
+            memorability: Some(memorability::score(words.iter().map(String::as_str))),
+            problem: None,
+        },
+        None => Report::problem(words.len(), "the words don't all come from one known list"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lists() -> Vec<(Words<'static>, ListName)> {
+        [List::Long, List::Short1]
+            .into_iter()
+            .map(|list| (Words::Embedded(list.clone()), ListName::Embedded(list)))
+            .collect()
+    }
+
+    #[test]
+    fn finds_smallest_list() {
+        // "acid" is in both lists, short1 is the smaller one
+        assert_eq!(
+            check("Acid Acorn", " ", &lists()).to_string(),
+            "2 words from the short1 list: 20.7 bits of entropy, memorability 88/100, \
+             with 2.0 syllables per word and 2 of 2 words on EFF's short list"
+        );
+        assert_eq!(
+            check("abacus-abdomen", "-", &lists()).to_string(),
+            "2 words from the long list: 25.8 bits of entropy, memorability 52/100, \
+             with 3.0 syllables per word and 0 of 2 words on EFF's short list"
+        );
+        assert_eq!(
+            check("abacus7abdomen", "digits", &lists()).to_string(),
+            "2 words from the long list: 25.8 bits of entropy, memorability 52/100, \
+             with 3.0 syllables per word and 0 of 2 words on EFF's short list"
+        );
+    }
+
+    #[test]
+    fn reports_unknown_words() {
+        assert_eq!(
+            check("acid acid", " ", &lists()).to_string(),
+            "the passphrase repeats words, xkpass never does"
+        );
+        assert_eq!(
+            check("acid xyzzy", " ", &lists()).to_string(),
+            "the words don't all come from one known list"
+        );
+    }
+
+    #[test]
+    fn looks_up_custom_lists() {
+        let custom = (
+            Words::Custom(["xyzzy", "plugh"].into_iter().collect()),
+            ListName::Custom(String::from("adventure")),
+        );
+        assert_eq!(
+            check("plugh Xyzzy", " ", &[custom]).to_string(),
+            "2 words from the adventure list: 1.0 bits of entropy, memorability 74/100, \
+             with 1.5 syllables per word and 0 of 2 words on EFF's short list"
+        );
+    }
+}
@@ -0,0 +1,55 @@
+//! Laying a password out in blocks, for copying it by hand onto paper.
+//!
+//! Like a hex dump, each row starts with the position of its first character
+//! and a ruler on top gives the offset of each block in the row, so a
+//! character can be found again by position. Spaces are shown as `␣`, so
+//! that they can't be mistaken for the gaps between blocks.
+
+/// Number of blocks on each row.
+const BLOCKS_PER_ROW: usize = 6;
+
+/// `password` in blocks of `size` characters.
+pub fn render(password: &str, size: usize) -> String {
+    let chars: Vec<char> = password
+        .chars()
+        .map(|c| if c == ' ' { '␣' } else { c })
+        .collect();
+    let row_len = size * BLOCKS_PER_ROW;
+    let label_width = chars.len().max(1).to_string().len();
+
+    let offsets: Vec<String> = (0..BLOCKS_PER_ROW.min(chars.len().div_ceil(size)))
+        .map(|block| format!("{:<size$}", format!("+{}", block * size)))
+        .collect();
+    let mut text = format!("{:label_width$}  {}\n", "", offsets.join(" "));
+    for (row, chars) in chars.chunks(row_len).enumerate() {
+        let blocks: Vec<String> = chars
+            .chunks(size)
+            .map(|block| block.iter().collect())
+            .collect();
+        text += &format!(
+            "{:>label_width$}  {}\n",
+            row * row_len + 1,
+            blocks.join(" ")
+        );
+    }
+    text.lines()
+        .map(str::trim_end)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lays_out_blocks() {
+        assert_eq!(
+            render("undergo editor composite maybe", 4),
+            "    +0   +4   +8   +12  +16  +20\n \
+             1  unde rgo␣ edit or␣c ompo site\n\
+             25  ␣may be"
+        );
+        assert_eq!(render("ab", 4), "   +0\n1  ab");
+    }
+}
@@ -0,0 +1,260 @@
+//! Copying text to the system clipboard, through the Win32 API on Windows,
+//! `pbcopy` on macOS and the Wayland or X11 protocols everywhere else.
+//!
+//! On Wayland and X11, the clipboard belongs to the program that copied to it
+//! and goes away with it. xkpass therefore starts itself again in the
+//! background to hold the text, until something else is copied or a
+//! clipboard manager has taken it over, the way `wl-copy` and `xclip` do.
+
+use clap::ValueEnum;
+use std::io;
+#[cfg(not(windows))]
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+};
+
+/// Selections of Wayland and X11 to copy to.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Selection {
+    /// The clipboard, pasted with Ctrl+V
+    Clipboard,
+    /// The primary selection, pasted with the middle mouse button
+    Primary,
+}
+
+/// Copy `text` to the clipboard.
+///
+/// `clip.exe` reads its input in the console's code page and mangles anything
+/// outside of it, so the text is handed to the clipboard as UTF-16 directly.
+#[cfg(windows)]
+pub fn copy(text: &str, selection: Selection) -> io::Result<()> {
+    only_clipboard(selection)?;
+    win32::copy(text)
+}
+
+/// Copy `text` to the clipboard with `pbcopy`.
+#[cfg(target_os = "macos")]
+pub fn copy(text: &str, selection: Selection) -> io::Result<()> {
+    only_clipboard(selection)?;
+    let mut child = Command::new("pbcopy")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    // the text is handed over on stdin so that it never shows up in argv
+    child
+        .stdin
+        .take()
+        .expect("stdin is piped")
+        .write_all(text.as_bytes())?;
+
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(io::Error::other(format!("pbcopy exited with {}", status)));
+    }
+    Ok(())
+}
+
+#[cfg(any(windows, target_os = "macos"))]
+fn only_clipboard(selection: Selection) -> io::Result<()> {
+    match selection {
+        Selection::Clipboard => Ok(()),
+        Selection::Primary => Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "the primary selection only exists on Wayland and X11",
+        )),
+    }
+}
+
+/// How long to wait for the process holding the selection to fail, as it does
+/// right away when the display can't be reached or lacks the selection.
+#[cfg(all(unix, not(target_os = "macos")))]
+const STARTUP: std::time::Duration = std::time::Duration::from_millis(250);
+
+/// Copy `text` to `selection`, held by xkpass started again in the background
+/// with the hidden `hold-clipboard` subcommand.
+#[cfg(all(unix, not(target_os = "macos")))]
+pub fn copy(text: &str, selection: Selection) -> io::Result<()> {
+    use std::{
+        io::Read,
+        os::unix::process::CommandExt,
+        time::{Duration, Instant},
+    };
+
+    let name = selection
+        .to_possible_value()
+        .expect("selections have names")
+        .get_name();
+    let mut child = Command::new(std::env::current_exe()?)
+        .args(["hold-clipboard", "--selection", name])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .current_dir("/")
+        // out of the terminal's process group, so that closing it or Ctrl-C
+        // doesn't take the selection away
+        .process_group(0)
+        .spawn()?;
+
+    // the text is handed over on stdin so that it never shows up in argv
+    child
+        .stdin
+        .take()
+        .expect("stdin is piped")
+        .write_all(text.as_bytes())?;
+
+    let started = Instant::now();
+    while started.elapsed() < STARTUP {
+        if let Some(status) = child.try_wait()? {
+            if status.success() {
+                return Ok(());
+            }
+            let mut message = String::new();
+            child
+                .stderr
+                .take()
+                .expect("stderr is piped")
+                .read_to_string(&mut message)?;
+            let message = message.trim().trim_start_matches("xkpass: ");
+            return Err(io::Error::other(format!(
+                "can't copy to the {} selection: {}",
+                name, message
+            )));
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    Ok(())
+}
+
+/// Read text on stdin and hold it in `selection` until something else is
+/// copied there, the job of the `hold-clipboard` subcommand.
+#[cfg(all(unix, not(target_os = "macos")))]
+pub fn hold(selection: Selection) -> io::Result<()> {
+    use arboard::{LinuxClipboardKind, SetExtLinux};
+    use std::io::Read;
+
+    let mut text = String::new();
+    io::stdin().read_to_string(&mut text)?;
+    let kind = match selection {
+        Selection::Clipboard => LinuxClipboardKind::Clipboard,
+        Selection::Primary => LinuxClipboardKind::Primary,
+    };
+    arboard::Clipboard::new()
+        .and_then(|mut clipboard| clipboard.set().clipboard(kind).wait().text(text))
+        .map_err(io::Error::other)
+}
+
+/// The other platforms keep the clipboard themselves, so there is nothing to hold.
+#[cfg(not(all(unix, not(target_os = "macos"))))]
+pub fn hold(_selection: Selection) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "the clipboard is only held on Wayland and X11",
+    ))
+}
+
+#[cfg(windows)]
+mod win32 {
+    use std::{io, ptr, thread, time::Duration};
+    use windows_sys::Win32::{
+        Foundation::GlobalFree,
+        System::{
+            DataExchange::{CloseClipboard, EmptyClipboard, OpenClipboard, SetClipboardData},
+            Memory::{GlobalAlloc, GlobalLock, GlobalUnlock, GMEM_MOVEABLE},
+            Ole::CF_UNICODETEXT,
+        },
+    };
+
+    /// The open clipboard, closed again when dropped.
+    pub(super) struct Clipboard;
+
+    impl Clipboard {
+        pub(super) fn open() -> io::Result<Self> {
+            // another program may be holding the clipboard for a moment
+            for _ in 0..10 {
+                // SAFETY: a null window associates the clipboard with the current task
+                if unsafe { OpenClipboard(ptr::null_mut()) } != 0 {
+                    return Ok(Self);
+                }
+                thread::sleep(Duration::from_millis(20));
+            }
+
+            Err(io::Error::last_os_error())
+        }
+    }
+
+    impl Drop for Clipboard {
+        fn drop(&mut self) {
+            // SAFETY: the clipboard was opened by `Clipboard::open`
+            unsafe { CloseClipboard() };
+        }
+    }
+
+    pub(super) fn copy(text: &str) -> io::Result<()> {
+        let text: Vec<u16> = text.encode_utf16().chain(Some(0)).collect();
+        let _clipboard = Clipboard::open()?;
+
+        // SAFETY: the clipboard is open, the allocation is large enough for `text`
+        // and is only freed here if the clipboard didn't take ownership of it
+        unsafe {
+            if EmptyClipboard() == 0 {
+                return Err(io::Error::last_os_error());
+            }
+
+            let memory = GlobalAlloc(GMEM_MOVEABLE, text.len() * 2);
+            if memory.is_null() {
+                return Err(io::Error::last_os_error());
+            }
+            let locked = GlobalLock(memory).cast::<u16>();
+            if locked.is_null() {
+                let err = io::Error::last_os_error();
+                GlobalFree(memory);
+                return Err(err);
+            }
+            ptr::copy_nonoverlapping(text.as_ptr(), locked, text.len());
+            GlobalUnlock(memory);
+
+            if SetClipboardData(u32::from(CF_UNICODETEXT), memory).is_null() {
+                let err = io::Error::last_os_error();
+                GlobalFree(memory);
+                return Err(err);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(all(test, windows))]
+mod tests {
+    use super::*;
+    use windows_sys::Win32::System::{
+        DataExchange::GetClipboardData,
+        Memory::{GlobalLock, GlobalUnlock},
+        Ole::CF_UNICODETEXT,
+    };
+
+    fn paste() -> String {
+        let _clipboard = win32::Clipboard::open().unwrap();
+
+        unsafe {
+            let memory = GetClipboardData(u32::from(CF_UNICODETEXT));
+            assert!(!memory.is_null());
+            let text = GlobalLock(memory).cast::<u16>();
+            let len = (0..).take_while(|&i| *text.add(i) != 0).count();
+            let pasted = String::from_utf16(std::slice::from_raw_parts(text, len)).unwrap();
+            GlobalUnlock(memory);
+            pasted
+        }
+    }
+
+    #[test]
+    fn copies_unicode_text() {
+        let text = "correct·horse→battery staple ✓";
+        copy(text, Selection::Clipboard).unwrap();
+
+        assert_eq!(paste(), text);
+    }
+}
@@ -0,0 +1,78 @@
+//! Writing bytes read on stdin as words, and reading them back.
+//!
+//! Keys, UUIDs and recovery codes are mostly written in hex, so both ways can
+//! take hex instead of raw bytes.
+
+use std::io::{self, Read, Write};
+
+/// Read bytes on stdin, or hex with `hex`, and print them as words.
+pub fn encode(hex: bool) -> Result<(), String> {
+    let mut input = Vec::new();
+    io::stdin()
+        .lock()
+        .read_to_end(&mut input)
+        .map_err(|err| format!("stdin: {}", err))?;
+    let bytes = if hex {
+        from_hex(&String::from_utf8_lossy(&input))?
+    } else {
+        input
+    };
+    println!("{}", xkpass::encode_bytes(&bytes).join(" "));
+    Ok(())
+}
+
+/// Read words on stdin and print the bytes they stand for, or hex with `hex`.
+pub fn decode(hex: bool) -> Result<(), String> {
+    let mut input = String::new();
+    io::stdin()
+        .lock()
+        .read_to_string(&mut input)
+        .map_err(|err| format!("stdin: {}", err))?;
+    let bytes = xkpass::decode_words(input.split_whitespace()).map_err(|err| err.to_string())?;
+    let mut stdout = io::stdout().lock();
+    let written = if hex {
+        writeln!(stdout, "{}", to_hex(&bytes))
+    } else {
+        stdout.write_all(&bytes)
+    };
+    written.map_err(|err| format!("stdout: {}", err))
+}
+
+/// Parse hex digits, skipping whitespace and the dashes and colons of UUIDs and MAC addresses.
+fn from_hex(text: &str) -> Result<Vec<u8>, String> {
+    let digits = text
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-' && *c != ':')
+        .map(|c| {
+            c.to_digit(16)
+                .ok_or_else(|| format!("{:?} isn't a hex digit", c))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if digits.len() % 2 == 1 {
+        return Err(String::from("odd number of hex digits"));
+    }
+    Ok(digits
+        .chunks(2)
+        .map(|pair| (pair[0] << 4 | pair[1]) as u8)
+        .collect())
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_hex() {
+        assert_eq!(
+            from_hex("6f9619ff-8b86-d011\n"),
+            Ok(vec![0x6f, 0x96, 0x19, 0xff, 0x8b, 0x86, 0xd0, 0x11])
+        );
+        assert!(from_hex("abc").is_err());
+        assert!(from_hex("xy").is_err());
+        assert_eq!(to_hex(&[0x0a, 0xff]), "0aff");
+    }
+}
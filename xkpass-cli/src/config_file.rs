@@ -0,0 +1,227 @@
+//! Default settings read from a TOML configuration file.
+//!
+//! ```toml
+//! number = 5
+//! separator = "."
+//! list = "short1"
+//! case = "capitalized"
+//! ```
+//!
+//! Every key is optional, options given on the command line take precedence.
+//!
+//! Named profiles hold sets of settings to switch between with `--profile`,
+//! their keys override the top-level ones:
+//!
+//! ```toml
+//! [profiles.work]
+//! number = 8
+//! case = "mixed"
+//! ```
+
+use crate::{word_lists::ListName, Settings};
+use serde::Deserialize;
+use std::{
+    collections::BTreeMap,
+    env, fs, io,
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
+};
+use xkpass::{Case, Config};
+
+/// Settings found in the configuration file.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigFile {
+    number: Option<NonZeroUsize>,
+    separator: Option<String>,
+    list: Option<ListName>,
+    case: Option<Case>,
+    #[serde(default)]
+    profiles: BTreeMap<String, Profile>,
+}
+
+/// Settings of a `[profiles.<name>]` section.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Profile {
+    number: Option<NonZeroUsize>,
+    separator: Option<String>,
+    list: Option<ListName>,
+    case: Option<Case>,
+}
+
+impl ConfigFile {
+    /// Read the file at `path`, a missing file counts as an empty one.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(contents) => Self::parse(&contents).map_err(|err| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{}: {}", path.display(), err),
+                )
+            }),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn parse(contents: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(contents)
+    }
+
+    /// Override the top-level settings with those of the profile called `name`.
+    pub fn select(mut self, name: &str) -> Result<Self, String> {
+        let profile = self
+            .profiles
+            .remove(name)
+            .ok_or_else(|| format!("there is no profile {:?} in the configuration file", name))?;
+
+        Ok(Self {
+            number: profile.number.or(self.number),
+            separator: profile.separator.or(self.separator),
+            list: profile.list.or(self.list),
+            case: profile.case.or(self.case),
+            profiles: BTreeMap::new(),
+        })
+    }
+
+    /// Use `list` when the file doesn't choose one.
+    pub fn or_list(mut self, list: Option<ListName>) -> Self {
+        self.list = self.list.or(list);
+        self
+    }
+
+    /// Turn the file into settings, using the built-in defaults for missing keys.
+    pub fn into_settings(self) -> Settings {
+        let defaults = Config::default();
+        Settings::new(
+            self.number.map_or(defaults.number, NonZeroUsize::get),
+            self.separator.unwrap_or(defaults.separator),
+            self.list.unwrap_or(ListName::Embedded(defaults.list)),
+            self.case.unwrap_or(defaults.case),
+        )
+    }
+}
+
+/// Directory holding xkpass' configuration: `$XDG_CONFIG_HOME/xkpass` or
+/// `~/.config/xkpass` on Unix, `~/Library/Application Support/xkpass` on macOS
+/// and `%APPDATA%\xkpass` on Windows.
+pub fn config_dir() -> Option<PathBuf> {
+    let base = if cfg!(windows) {
+        PathBuf::from(env::var_os("APPDATA")?)
+    } else if cfg!(target_os = "macos") {
+        PathBuf::from(env::var_os("HOME")?).join("Library/Application Support")
+    } else {
+        match env::var_os("XDG_CONFIG_HOME").map(PathBuf::from) {
+            // relative paths are invalid according to the XDG base directory specification
+            Some(dir) if dir.is_absolute() => dir,
+            _ => PathBuf::from(env::var_os("HOME")?).join(".config"),
+        }
+    };
+
+    Some(base.join("xkpass"))
+}
+
+/// Directory for xkpass' caches: `$XDG_CACHE_HOME/xkpass` or `~/.cache/xkpass`
+/// on Unix, `~/Library/Caches/xkpass` on macOS and `%LOCALAPPDATA%\xkpass` on Windows.
+pub fn cache_dir() -> Option<PathBuf> {
+    let base = if cfg!(windows) {
+        PathBuf::from(env::var_os("LOCALAPPDATA")?)
+    } else if cfg!(target_os = "macos") {
+        PathBuf::from(env::var_os("HOME")?).join("Library/Caches")
+    } else {
+        match env::var_os("XDG_CACHE_HOME").map(PathBuf::from) {
+            Some(dir) if dir.is_absolute() => dir,
+            _ => PathBuf::from(env::var_os("HOME")?).join(".cache"),
+        }
+    };
+
+    Some(base.join("xkpass"))
+}
+
+/// Directory for state kept between runs: `$XDG_STATE_HOME/xkpass` or
+/// `~/.local/state/xkpass` on Unix, `~/Library/Application Support/xkpass` on
+/// macOS and `%LOCALAPPDATA%\xkpass` on Windows.
+pub fn state_dir() -> Option<PathBuf> {
+    let base = if cfg!(windows) {
+        PathBuf::from(env::var_os("LOCALAPPDATA")?)
+    } else if cfg!(target_os = "macos") {
+        PathBuf::from(env::var_os("HOME")?).join("Library/Application Support")
+    } else {
+        match env::var_os("XDG_STATE_HOME").map(PathBuf::from) {
+            Some(dir) if dir.is_absolute() => dir,
+            _ => PathBuf::from(env::var_os("HOME")?).join(".local/state"),
+        }
+    };
+
+    Some(base.join("xkpass"))
+}
+
+/// Path of the configuration file: `$XKPASS_CONFIG` if it is set, otherwise
+/// `config.toml` in [`config_dir`].
+pub fn default_path() -> Option<PathBuf> {
+    match env::var_os("XKPASS_CONFIG") {
+        Some(path) => Some(PathBuf::from(path)),
+        None => config_dir().map(|dir| dir.join("config.toml")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use xkpass::List;
+
+    #[test]
+    fn reads_settings() {
+        let settings = ConfigFile::parse(
+            r#"
+            number = 4
+            list = "short2"
+            case = "capitalized"
+            "#,
+        )
+        .unwrap()
+        .into_settings();
+
+        assert_eq!(settings.config.number, 4);
+        assert_eq!(settings.config.separator, " ");
+        assert!(matches!(settings.list, ListName::Embedded(List::Short2)));
+        assert!(matches!(settings.config.case, Case::Capitalized));
+    }
+
+    #[test]
+    fn selects_profiles() {
+        let file = r#"
+            number = 4
+            separator = "-"
+
+            [profiles.work]
+            number = 8
+            case = "mixed"
+            "#;
+        let settings = ConfigFile::parse(file)
+            .unwrap()
+            .select("work")
+            .unwrap()
+            .into_settings();
+
+        assert_eq!(settings.config.number, 8);
+        assert_eq!(settings.config.separator, "-");
+        assert!(matches!(settings.config.case, Case::Mixed));
+        assert!(ConfigFile::parse(file).unwrap().select("home").is_err());
+        assert!(ConfigFile::parse("[profiles.work]\nnubmer = 4").is_err());
+        assert!(ConfigFile::parse("number = 0").is_err());
+    }
+
+    #[test]
+    fn rejects_unknown_keys() {
+        assert!(ConfigFile::parse("nubmer = 4").is_err());
+        assert!(ConfigFile::parse(r#"list = "../huge""#).is_err());
+    }
+
+    #[test]
+    fn ignores_missing_file() {
+        let config = ConfigFile::load(Path::new("/nonexistent/xkpass/config.toml")).unwrap();
+        assert_eq!(config.into_settings().config.number, 6);
+    }
+}
@@ -0,0 +1,262 @@
+//! Showing a password on the terminal only until a key is pressed.
+//!
+//! With `--confirm`, the password is drawn on the alternate screen, which
+//! terminals keep out of the scrollback, and erased before switching back to
+//! the normal screen, so that it is neither left in view nor in the history of
+//! the terminal. With `--reveal`, it is printed in place under a countdown and
+//! erased along with the scrollback, on terminals that let programs erase it.
+
+use std::{
+    io::{self, IsTerminal, Write},
+    time::Duration,
+};
+
+/// Switch to the alternate screen, clear it and move the cursor home.
+const ENTER: &str = "\x1b[?1049h\x1b[2J\x1b[H";
+/// Erase the screen and the scrollback, then switch back to the normal screen.
+const LEAVE: &str = "\x1b[2J\x1b[3J\x1b[?1049l";
+/// Erase from the cursor to the end of the screen, then the scrollback.
+const ERASE_BELOW: &str = "\x1b[J\x1b[3J";
+
+/// Show `password` until a key is pressed or `timeout` has passed, then erase it.
+pub fn show(password: &str, timeout: Duration) -> io::Result<()> {
+    if !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+        return Err(io::Error::other("--confirm needs a terminal"));
+    }
+    platform::enable_escape_codes()?;
+
+    let mut stdout = io::stdout().lock();
+    write!(
+        stdout,
+        "{}{}\n\nPress any key to clear the password, it is cleared in {} seconds anyway.",
+        ENTER,
+        password,
+        timeout.as_secs()
+    )?;
+    stdout.flush()?;
+
+    let waited = platform::wait_for_key(timeout);
+    write!(stdout, "{}", LEAVE)?;
+    stdout.flush()?;
+
+    waited.map(drop)
+}
+
+/// Show `password` for `duration` under a countdown, or until a key is
+/// pressed, then erase it.
+pub fn reveal(password: &str, duration: Duration) -> io::Result<()> {
+    if !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+        return Err(io::Error::other("--reveal needs a terminal"));
+    }
+    platform::enable_escape_codes()?;
+
+    let mut stdout = io::stdout().lock();
+    writeln!(stdout, "{}", password)?;
+    let mut left = duration.as_secs();
+    let waited = loop {
+        if left == 0 {
+            break Ok(());
+        }
+        write!(
+            stdout,
+            "\r\x1b[KCleared in {}s, or when a key is pressed.",
+            left
+        )?;
+        stdout.flush()?;
+        match platform::wait_for_key(Duration::from_secs(1)) {
+            Ok(false) => left -= 1,
+            Ok(true) => break Ok(()),
+            Err(err) => break Err(err),
+        }
+    };
+
+    // back to the first line of the password, the countdown being on the line after it
+    write!(
+        stdout,
+        "\r\x1b[{}A{}",
+        password.lines().count(),
+        ERASE_BELOW
+    )?;
+    stdout.flush()?;
+
+    waited
+}
+
+/// Parse a duration of `--reveal`, in seconds such as `10` or `10s`, or in
+/// minutes such as `2m`.
+pub fn parse_duration(value: &str) -> Result<Duration, String> {
+    let (number, unit) = match value.strip_suffix('m') {
+        Some(minutes) => (minutes, 60),
+        None => (value.strip_suffix('s').unwrap_or(value), 1),
+    };
+    match number.parse::<u64>() {
+        Ok(0) | Err(_) => Err(format!("{:?} isn't a duration such as 10s or 2m", value)),
+        Ok(number) => Ok(Duration::from_secs(number.saturating_mul(unit))),
+    }
+}
+
+#[cfg(unix)]
+mod platform {
+    use std::{io, mem::MaybeUninit, time::Duration};
+
+    pub(super) fn enable_escape_codes() -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Wait until a key is pressed on the terminal, without echoing it, and
+    /// tell whether one was.
+    pub(super) fn wait_for_key(timeout: Duration) -> io::Result<bool> {
+        let fd = libc::STDIN_FILENO;
+        let mut original = MaybeUninit::<libc::termios>::uninit();
+        // SAFETY: `original` is only read once `tcgetattr` has filled it in
+        let original = unsafe {
+            if libc::tcgetattr(fd, original.as_mut_ptr()) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            original.assume_init()
+        };
+
+        // without ISIG, Ctrl-C is a key like any other instead of killing xkpass
+        // before the password is erased
+        let mut raw = original;
+        raw.c_lflag &= !(libc::ICANON | libc::ECHO | libc::ISIG);
+        // SAFETY: `raw` is a valid termios, taken from the terminal itself
+        if unsafe { libc::tcsetattr(fd, libc::TCSANOW, &raw) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let mut poll = libc::pollfd {
+            fd,
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let millis = timeout.as_millis().min(libc::c_int::MAX as u128) as libc::c_int;
+        // SAFETY: `poll` points to a single valid pollfd
+        let ready = unsafe { libc::poll(&mut poll, 1, millis) };
+        let err = io::Error::last_os_error();
+
+        // SAFETY: discards the key, then restores the settings read above
+        unsafe {
+            libc::tcflush(fd, libc::TCIFLUSH);
+            libc::tcsetattr(fd, libc::TCSANOW, &original);
+        }
+
+        match ready {
+            -1 => Err(err),
+            ready => Ok(ready > 0),
+        }
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use std::{
+        io,
+        time::{Duration, Instant},
+    };
+    use windows_sys::Win32::{
+        Foundation::{HANDLE, WAIT_FAILED, WAIT_TIMEOUT},
+        System::{
+            Console::{
+                FlushConsoleInputBuffer, GetConsoleMode, GetStdHandle, ReadConsoleInputW,
+                SetConsoleMode, CONSOLE_MODE, ENABLE_ECHO_INPUT, ENABLE_LINE_INPUT,
+                ENABLE_PROCESSED_INPUT, ENABLE_VIRTUAL_TERMINAL_PROCESSING, INPUT_RECORD,
+                KEY_EVENT, STD_INPUT_HANDLE, STD_OUTPUT_HANDLE,
+            },
+            Threading::WaitForSingleObject,
+        },
+    };
+
+    fn console_mode(handle: HANDLE) -> io::Result<CONSOLE_MODE> {
+        let mut mode = 0;
+        // SAFETY: `mode` is a valid pointer for the duration of the call
+        if unsafe { GetConsoleMode(handle, &mut mode) } == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(mode)
+    }
+
+    fn set_console_mode(handle: HANDLE, mode: CONSOLE_MODE) -> io::Result<()> {
+        // SAFETY: the handle is one of the standard handles of the console
+        if unsafe { SetConsoleMode(handle, mode) } == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    /// Have the console interpret the escape codes used to erase the password.
+    pub(super) fn enable_escape_codes() -> io::Result<()> {
+        // SAFETY: GetStdHandle has no preconditions
+        let output = unsafe { GetStdHandle(STD_OUTPUT_HANDLE) };
+        set_console_mode(
+            output,
+            console_mode(output)? | ENABLE_VIRTUAL_TERMINAL_PROCESSING,
+        )
+    }
+
+    /// Wait until a key is pressed in the console, without echoing it, and
+    /// tell whether one was.
+    pub(super) fn wait_for_key(timeout: Duration) -> io::Result<bool> {
+        // SAFETY: GetStdHandle has no preconditions
+        let input = unsafe { GetStdHandle(STD_INPUT_HANDLE) };
+        let original = console_mode(input)?;
+        // without processed input, Ctrl-C is a key like any other
+        let raw = original & !(ENABLE_LINE_INPUT | ENABLE_ECHO_INPUT | ENABLE_PROCESSED_INPUT);
+        set_console_mode(input, raw)?;
+
+        let waited = wait(input, timeout);
+        // SAFETY: the handle is the console input
+        unsafe { FlushConsoleInputBuffer(input) };
+        set_console_mode(input, original)?;
+
+        waited
+    }
+
+    fn wait(input: HANDLE, timeout: Duration) -> io::Result<bool> {
+        // the release of the key that started xkpass must not count
+        // SAFETY: the handle is the console input
+        unsafe { FlushConsoleInputBuffer(input) };
+
+        let deadline = Instant::now() + timeout;
+        loop {
+            let left = deadline.saturating_duration_since(Instant::now());
+            let millis = left.as_millis().min(u32::MAX as u128 - 1) as u32;
+            // SAFETY: the handle is the console input
+            match unsafe { WaitForSingleObject(input, millis) } {
+                WAIT_TIMEOUT => return Ok(false),
+                WAIT_FAILED => return Err(io::Error::last_os_error()),
+                _ => {}
+            }
+
+            let mut record = INPUT_RECORD::default();
+            let mut read = 0;
+            // SAFETY: `record` has room for the single record asked for
+            if unsafe { ReadConsoleInputW(input, &mut record, 1, &mut read) } == 0 {
+                return Err(io::Error::last_os_error());
+            }
+            // SAFETY: the event is a key event, as its type says
+            if read == 1
+                && u32::from(record.EventType) == KEY_EVENT
+                && unsafe { record.Event.KeyEvent.bKeyDown } != 0
+            {
+                return Ok(true);
+            }
+        }
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+mod platform {
+    use std::{io, time::Duration};
+
+    pub(super) fn enable_escape_codes() -> io::Result<()> {
+        Ok(())
+    }
+
+    pub(super) fn wait_for_key(_timeout: Duration) -> io::Result<bool> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "--confirm and --reveal aren't supported on this platform",
+        ))
+    }
+}
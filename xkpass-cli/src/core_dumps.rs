@@ -0,0 +1,69 @@
+//! Keeping passwords out of core dumps.
+//!
+//! Should xkpass crash while a password is in its memory, the password would
+//! otherwise end up in a core file or a crash report, and outlive the process.
+
+use std::io;
+
+/// Stop the process from dumping core for the rest of its life.
+///
+/// On Linux the process is also marked as not dumpable, which keeps the
+/// user's other processes from attaching to it and reading its memory.
+#[cfg(unix)]
+pub fn disable() -> io::Result<()> {
+    let limit = libc::rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    // SAFETY: `limit` is a valid rlimit for the duration of the call
+    if unsafe { libc::setrlimit(libc::RLIMIT_CORE, &limit) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    #[cfg(target_os = "linux")]
+    // SAFETY: PR_SET_DUMPABLE takes a single integer argument
+    if unsafe { libc::prctl(libc::PR_SET_DUMPABLE, 0 as libc::c_ulong) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(())
+}
+
+/// Stop crashes of the process from being handed to Windows Error Reporting,
+/// which would write its memory to a dump.
+#[cfg(windows)]
+pub fn disable() -> io::Result<()> {
+    use windows_sys::Win32::System::Diagnostics::Debug::{
+        GetErrorMode, SetErrorMode, SEM_NOGPFAULTERRORBOX,
+    };
+
+    // SAFETY: only changes how the process itself reports crashes
+    unsafe { SetErrorMode(GetErrorMode() | SEM_NOGPFAULTERRORBOX) };
+    Ok(())
+}
+
+#[cfg(not(any(unix, windows)))]
+pub fn disable() -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "core dumps can't be disabled on this platform",
+    ))
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disables_core_dumps() {
+        disable().unwrap();
+
+        let mut limit = libc::rlimit {
+            rlim_cur: 1,
+            rlim_max: 1,
+        };
+        assert_eq!(unsafe { libc::getrlimit(libc::RLIMIT_CORE, &mut limit) }, 0);
+        assert_eq!((limit.rlim_cur, limit.rlim_max), (0, 0));
+        assert_eq!(unsafe { libc::prctl(libc::PR_GET_DUMPABLE) }, 0);
+    }
+}
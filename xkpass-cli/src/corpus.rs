@@ -0,0 +1,56 @@
+//! Streaming millions of passwords for research, such as testing strength
+//! meters and crackers against diceware-style inputs.
+//!
+//! Unlike `--count`, passwords never become a [`Password`](xkpass::Password):
+//! each one is written into the same buffer, which goes straight to a locked
+//! and buffered stdout.
+
+use std::io::{self, Write};
+use xkpass::{Config, PasswordGenerator};
+
+/// Print `count` passwords generated with `config`, one per line.
+pub fn run(config: Config, count: u64) -> io::Result<()> {
+    let generator = PasswordGenerator::builder()
+        .config(config)
+        .build()
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+
+    let mut out = io::BufWriter::with_capacity(1 << 16, io::stdout().lock());
+    match write(&generator, count, &mut out) {
+        // stop quietly when piped to a program such as `head`
+        Err(err) if err.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        result => result,
+    }
+}
+
+fn write<W: Write>(generator: &PasswordGenerator, count: u64, out: &mut W) -> io::Result<()> {
+    let mut line = String::new();
+    for _ in 0..count {
+        line.clear();
+        generator.generate_into(&mut line);
+        line.push('\n');
+        out.write_all(line.as_bytes())?;
+    }
+
+    out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn streams_passwords() {
+        let generator = PasswordGenerator::builder()
+            .words(3)
+            .separator("+")
+            .build()
+            .unwrap();
+        let mut out = Vec::new();
+        write(&generator, 1000, &mut out).unwrap();
+
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(out.lines().count(), 1000);
+        assert!(out.lines().all(|line| line.split('+').count() == 3));
+    }
+}
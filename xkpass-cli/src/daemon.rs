@@ -0,0 +1,449 @@
+//! A long-running daemon answering generation requests on a Unix socket, or
+//! a named pipe on Windows.
+//!
+//! The word lists are compiled into the binary, so each request only pays
+//! for picking the words.
+
+use crate::{request::GenerateRequest, service};
+use serde_json::json;
+use std::{
+    io::{self, BufRead, BufReader, Read, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+};
+use xkpass::Config;
+
+/// Most clients served at once, each on a thread of its own.
+const MAX_CLIENTS: usize = 64;
+
+/// Socket used when none is given: `$XDG_RUNTIME_DIR/xkpass.sock`, or
+/// `$TMPDIR/xkpass.sock` on macOS, which has no `$XDG_RUNTIME_DIR` but a
+/// temporary directory of its own for each user.
+#[cfg(unix)]
+pub fn default_socket_path() -> Option<PathBuf> {
+    let dir = std::env::var_os("XDG_RUNTIME_DIR").or_else(|| {
+        if cfg!(target_os = "macos") {
+            std::env::var_os("TMPDIR")
+        } else {
+            None
+        }
+    });
+    dir.map(|dir| Path::new(&dir).join("xkpass.sock"))
+}
+
+/// Pipe used when none is given: `\\.\pipe\xkpass`.
+#[cfg(windows)]
+pub fn default_socket_path() -> Option<PathBuf> {
+    Some(PathBuf::from(r"\\.\pipe\xkpass"))
+}
+
+/// Listen on the socket at `path` and answer requests until the process is
+/// told to stop, removing the socket on the way out.
+///
+/// Clients send one JSON request per line and get one JSON response per line back.
+#[cfg(unix)]
+pub fn run(path: &Path, defaults: Config) -> io::Result<()> {
+    unix::remove_stale_socket(path)?;
+
+    let listener = unix::bind_private(path)?;
+    unix::remove_socket_on_exit(path)?;
+    service::log(&format!("listening on {}", path.display()));
+
+    let defaults = Arc::new(defaults);
+    let clients = Clients::default();
+
+    for stream in listener.incoming() {
+        match stream.and_then(|stream| Ok((stream.try_clone()?, stream))) {
+            Ok((reader, writer)) => spawn_client(&clients, &defaults, reader, writer),
+            Err(err) => service::log_error(&format!("could not accept connection: {}", err)),
+        }
+    }
+
+    Ok(())
+}
+
+/// Listen on the named pipe at `path` and answer requests until the process
+/// is told to stop.
+///
+/// Clients send one JSON request per line and get one JSON response per line back.
+#[cfg(windows)]
+pub fn run(path: &Path, defaults: Config) -> io::Result<()> {
+    let security = windows::Security::new()?;
+    let defaults = Arc::new(defaults);
+    let clients = Clients::default();
+    let mut first = true;
+
+    loop {
+        let pipe = windows::create_pipe(path, &security, first)?;
+        if first {
+            service::log(&format!("listening on {}", path.display()));
+            first = false;
+        }
+        match windows::connect(&pipe).and_then(|()| pipe.try_clone()) {
+            Ok(reader) => spawn_client(&clients, &defaults, reader, pipe),
+            Err(err) => service::log_error(&format!("could not accept connection: {}", err)),
+        }
+    }
+}
+
+/// The number of clients being served.
+#[derive(Default)]
+struct Clients(Arc<AtomicUsize>);
+
+/// A client being served, until dropped.
+struct Admitted(Arc<AtomicUsize>);
+
+impl Clients {
+    /// Count one more client, unless [`MAX_CLIENTS`] are being served already.
+    fn admit(&self) -> Option<Admitted> {
+        let served = self
+            .0
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |served| {
+                (served < MAX_CLIENTS).then_some(served + 1)
+            });
+        served.ok().map(|_| Admitted(Arc::clone(&self.0)))
+    }
+}
+
+impl Drop for Admitted {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// Serve a client on a thread of its own, or turn it away with an error when
+/// too many are being served, so that connecting over and over can't exhaust
+/// the threads of the daemon.
+fn spawn_client<R, W>(clients: &Clients, defaults: &Arc<Config>, reader: R, mut writer: W)
+where
+    R: Read + Send + 'static,
+    W: Write + Send + 'static,
+{
+    let admitted = match clients.admit() {
+        Some(admitted) => admitted,
+        None => {
+            service::log_error("too many clients, turning one away");
+            // the client is dropped either way
+            let _ = writeln!(writer, "{}", json!({ "error": "too many clients" }));
+            return;
+        }
+    };
+
+    let defaults = Arc::clone(defaults);
+    thread::spawn(move || {
+        let _admitted = admitted;
+        serve_client(reader, writer, &defaults)
+    });
+}
+
+/// Answer every line the client sends until it hangs up.
+fn serve_client(reader: impl Read, mut writer: impl Write, defaults: &Config) -> io::Result<()> {
+    for line in BufReader::new(reader).lines() {
+        let response = answer(line?.as_bytes(), defaults);
+        writeln!(writer, "{}", response)?;
+    }
+
+    Ok(())
+}
+
+/// Produce the JSON response to a single request line.
+fn answer(line: &[u8], defaults: &Config) -> serde_json::Value {
+    let password = GenerateRequest::from_json(line).and_then(|request| request.generate(defaults));
+
+    match password {
+        Ok(password) => json!({ "password": password }),
+        Err(err) => json!({ "error": err }),
+    }
+}
+
+#[cfg(unix)]
+mod unix {
+    use std::{
+        ffi::CString,
+        fs, io,
+        os::unix::{
+            ffi::OsStrExt,
+            fs::FileTypeExt,
+            net::{UnixListener, UnixStream},
+        },
+        path::Path,
+        sync::OnceLock,
+    };
+
+    /// The socket to remove when the daemon is told to stop.
+    static SOCKET: OnceLock<CString> = OnceLock::new();
+
+    /// Remove a socket left behind by a daemon that is no longer running.
+    pub(super) fn remove_stale_socket(path: &Path) -> io::Result<()> {
+        match fs::symlink_metadata(path) {
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(err),
+            Ok(metadata) if !metadata.file_type().is_socket() => Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{} exists and is not a socket", path.display()),
+            )),
+            Ok(_) if UnixStream::connect(path).is_ok() => Err(io::Error::new(
+                io::ErrorKind::AddrInUse,
+                format!("another daemon is already listening on {}", path.display()),
+            )),
+            Ok(_) => fs::remove_file(path),
+        }
+    }
+
+    /// Bind a socket at `path` that only the user running the daemon can
+    /// connect to.
+    ///
+    /// The socket is created without permissions for anyone else, rather than
+    /// restricted once it exists, so that nobody can connect in between.
+    pub(super) fn bind_private(path: &Path) -> io::Result<UnixListener> {
+        // SAFETY: umask can't fail, and the daemon has no other threads yet
+        // creating files that the mask would apply to
+        let previous = unsafe { libc::umask(0o077) };
+        let listener = UnixListener::bind(path);
+        // SAFETY: see above
+        unsafe { libc::umask(previous) };
+        listener
+    }
+
+    /// Remove the socket at `path` and exit when the daemon gets SIGTERM, as
+    /// launchd and systemd send to stop it, SIGINT or SIGHUP.
+    ///
+    /// Requests take microseconds to answer, so exiting right away is as
+    /// graceful as waiting for them, and only takes calls that are safe in a
+    /// signal handler.
+    pub(super) fn remove_socket_on_exit(path: &Path) -> io::Result<()> {
+        let path = CString::new(path.as_os_str().as_bytes())?;
+        if SOCKET.set(path).is_err() {
+            return Ok(());
+        }
+
+        extern "C" fn stop(_signal: libc::c_int) {
+            if let Some(path) = SOCKET.get() {
+                // SAFETY: unlink and _exit are async-signal-safe, and `path`
+                // is never changed once set
+                unsafe { libc::unlink(path.as_ptr()) };
+            }
+            // SAFETY: see above
+            unsafe { libc::_exit(0) };
+        }
+
+        for signal in [libc::SIGTERM, libc::SIGINT, libc::SIGHUP] {
+            // SAFETY: `stop` only makes async-signal-safe calls
+            if unsafe { libc::signal(signal, stop as *const () as libc::sighandler_t) }
+                == libc::SIG_ERR
+            {
+                return Err(io::Error::last_os_error());
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(windows)]
+mod windows {
+    use std::{
+        fs::File,
+        io,
+        os::windows::{ffi::OsStrExt, io::FromRawHandle},
+        path::Path,
+        ptr,
+    };
+    use windows_sys::Win32::{
+        Foundation::{LocalFree, ERROR_PIPE_CONNECTED, INVALID_HANDLE_VALUE},
+        Security::{
+            Authorization::{
+                ConvertStringSecurityDescriptorToSecurityDescriptorW, SDDL_REVISION_1,
+            },
+            PSECURITY_DESCRIPTOR, SECURITY_ATTRIBUTES,
+        },
+        Storage::FileSystem::{FILE_FLAG_FIRST_PIPE_INSTANCE, PIPE_ACCESS_DUPLEX},
+        System::Pipes::{
+            ConnectNamedPipe, CreateNamedPipeW, PIPE_READMODE_BYTE, PIPE_REJECT_REMOTE_CLIENTS,
+            PIPE_TYPE_BYTE, PIPE_UNLIMITED_INSTANCES, PIPE_WAIT,
+        },
+    };
+
+    /// Who may connect: the system, administrators and whoever is logged in
+    /// at the computer, so that the users of a daemon running as a service
+    /// can reach it. Their passwords are drawn at random for each of them,
+    /// so they learn nothing of one another's.
+    ///
+    /// The owner, LocalService for the service, keeps every right, creating
+    /// the next instances of the pipe among them. Users logged in only get
+    /// FILE_GENERIC_READ and FILE_WRITE_DATA (0x12008b), without
+    /// FILE_CREATE_PIPE_INSTANCE, so they can't serve instances of their own
+    /// to the clients of the daemon.
+    const SDDL: &str = "D:P(A;;GA;;;SY)(A;;GA;;;BA)(A;;GA;;;OW)(A;;0x12008b;;;IU)";
+
+    const BUFFER_SIZE: u32 = 4096;
+
+    /// The security descriptor given to every instance of the pipe.
+    pub(super) struct Security(PSECURITY_DESCRIPTOR);
+
+    impl Security {
+        pub(super) fn new() -> io::Result<Self> {
+            let sddl: Vec<u16> = SDDL.encode_utf16().chain(Some(0)).collect();
+            let mut descriptor = ptr::null_mut();
+            // SAFETY: `sddl` is nul-terminated and `descriptor` is a valid pointer
+            let converted = unsafe {
+                ConvertStringSecurityDescriptorToSecurityDescriptorW(
+                    sddl.as_ptr(),
+                    SDDL_REVISION_1,
+                    &mut descriptor,
+                    ptr::null_mut(),
+                )
+            };
+            if converted == 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(Self(descriptor))
+        }
+    }
+
+    impl Drop for Security {
+        fn drop(&mut self) {
+            // SAFETY: the descriptor was allocated by LocalAlloc in `Security::new`
+            unsafe { LocalFree(self.0) };
+        }
+    }
+
+    /// Create an instance of the pipe at `path`, failing if `first` and
+    /// another daemon already has the pipe.
+    pub(super) fn create_pipe(path: &Path, security: &Security, first: bool) -> io::Result<File> {
+        let name: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+        let attributes = SECURITY_ATTRIBUTES {
+            nLength: std::mem::size_of::<SECURITY_ATTRIBUTES>() as u32,
+            lpSecurityDescriptor: security.0,
+            bInheritHandle: 0,
+        };
+        let mut open_mode = PIPE_ACCESS_DUPLEX;
+        if first {
+            open_mode |= FILE_FLAG_FIRST_PIPE_INSTANCE;
+        }
+
+        // SAFETY: `name` is nul-terminated and `attributes` outlives the call
+        let handle = unsafe {
+            CreateNamedPipeW(
+                name.as_ptr(),
+                open_mode,
+                PIPE_TYPE_BYTE | PIPE_READMODE_BYTE | PIPE_WAIT | PIPE_REJECT_REMOTE_CLIENTS,
+                PIPE_UNLIMITED_INSTANCES,
+                BUFFER_SIZE,
+                BUFFER_SIZE,
+                0,
+                &attributes,
+            )
+        };
+        if handle == INVALID_HANDLE_VALUE {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY: the handle was just created and nothing else owns it
+        Ok(unsafe { File::from_raw_handle(handle) })
+    }
+
+    /// Wait for a client to connect to the instance `pipe`.
+    pub(super) fn connect(pipe: &File) -> io::Result<()> {
+        use std::os::windows::io::AsRawHandle;
+
+        // SAFETY: `pipe` is an instance of a named pipe opened without overlapped I/O
+        if unsafe { ConnectNamedPipe(pipe.as_raw_handle(), ptr::null_mut()) } != 0 {
+            return Ok(());
+        }
+        // a client may connect between creating the instance and waiting for one
+        let err = io::Error::last_os_error();
+        if err.raw_os_error() == Some(ERROR_PIPE_CONNECTED as i32) {
+            Ok(())
+        } else {
+            Err(err)
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::{
+        fs,
+        os::unix::{
+            fs::PermissionsExt,
+            net::{UnixListener, UnixStream},
+        },
+    };
+
+    #[test]
+    fn answers_with_requested_list() {
+        let response = answer(br#"{"number": 4, "list": "short1"}"#, &Config::default());
+        let password = response["password"].as_str().unwrap();
+
+        let words = xkpass::List::Short1.as_slice();
+        assert!(password.split(' ').all(|word| words.contains(&word)));
+        assert_eq!(password.split(' ').count(), 4);
+    }
+
+    #[test]
+    fn answers_errors_as_json() {
+        let response = answer(b"{", &Config::default());
+        assert!(response["error"].is_string());
+    }
+
+    #[test]
+    fn binds_sockets_for_the_user_only() {
+        let path = std::env::temp_dir().join(format!("xkpass-mode-{}.sock", std::process::id()));
+        let _listener = unix::bind_private(&path).unwrap();
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(mode & 0o077, 0);
+    }
+
+    #[test]
+    fn admits_a_bounded_number_of_clients() {
+        let clients = Clients::default();
+        let mut admitted: Vec<_> = (0..MAX_CLIENTS).map(|_| clients.admit().unwrap()).collect();
+        assert!(clients.admit().is_none());
+
+        admitted.pop();
+        assert!(clients.admit().is_some());
+    }
+
+    #[test]
+    fn serves_clients_on_socket() {
+        let path = std::env::temp_dir().join(format!("xkpass-test-{}.sock", std::process::id()));
+        let listener = UnixListener::bind(&path).unwrap();
+        thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            serve_client(stream.try_clone()?, stream, &Config::default())
+        });
+
+        let mut client = UnixStream::connect(&path).unwrap();
+        client.write_all(b"{\"number\": 3}\n\n").unwrap();
+        let mut lines = BufReader::new(client).lines();
+        let first: serde_json::Value =
+            serde_json::from_str(&lines.next().unwrap().unwrap()).unwrap();
+        let second: serde_json::Value =
+            serde_json::from_str(&lines.next().unwrap().unwrap()).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(first["password"].as_str().unwrap().split(' ').count(), 3);
+        assert_eq!(second["password"].as_str().unwrap().split(' ').count(), 6);
+    }
+}
+
+#[cfg(all(test, windows))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn creates_more_instances_of_the_pipe() {
+        let path = PathBuf::from(format!(r"\\.\pipe\xkpass-test-{}", std::process::id()));
+        let security = windows::Security::new().unwrap();
+        let _first = windows::create_pipe(&path, &security, true).unwrap();
+        let _second = windows::create_pipe(&path, &security, false).unwrap();
+
+        assert!(windows::create_pipe(&path, &security, true).is_err());
+    }
+}
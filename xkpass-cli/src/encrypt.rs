@@ -0,0 +1,103 @@
+//! Encrypting the password for its recipients with [age](https://age-encryption.org)
+//! or GnuPG, so it can be shared without the plaintext ever being displayed.
+
+use std::{
+    io::{self, Write},
+    process::{Command, Stdio},
+};
+
+/// Program used to encrypt for a set of recipients.
+#[derive(Debug, PartialEq)]
+enum Backend {
+    Age,
+    Gpg,
+}
+
+/// Pick the backend matching the recipients: age and SSH public keys go to age,
+/// anything else is treated as a GnuPG key id, fingerprint or user id.
+fn backend(recipients: &[String]) -> io::Result<Backend> {
+    let is_age = |recipient: &String| {
+        recipient.starts_with("age1")
+            || recipient.starts_with("ssh-")
+            || recipient.starts_with("sk-ssh-")
+    };
+
+    if recipients.iter().all(is_age) {
+        Ok(Backend::Age)
+    } else if !recipients.iter().any(is_age) {
+        Ok(Backend::Gpg)
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "age and GnuPG recipients can't be mixed",
+        ))
+    }
+}
+
+/// Encrypt `plaintext` for every recipient, returning the ASCII-armored message.
+pub fn encrypt(plaintext: &str, recipients: &[String]) -> io::Result<String> {
+    let mut command = match backend(recipients)? {
+        Backend::Age => {
+            let mut command = Command::new("age");
+            command.arg("--armor");
+            command
+        }
+        Backend::Gpg => {
+            let mut command = Command::new("gpg");
+            command.args(["--encrypt", "--armor", "--batch"]);
+            command
+        }
+    };
+    for recipient in recipients {
+        command.arg("--recipient").arg(recipient);
+    }
+
+    // the plaintext goes through a pipe and never touches argv or the disk
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()?;
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    stdin.write_all(plaintext.as_bytes())?;
+    stdin.write_all(b"\n")?;
+    drop(stdin);
+
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "encryption failed, {:?} exited with {}",
+            command.get_program(),
+            output.status
+        )));
+    }
+
+    String::from_utf8(output.stdout)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "armored output is not UTF-8"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recipients(recipients: &[&str]) -> Vec<String> {
+        recipients.iter().map(|r| r.to_string()).collect()
+    }
+
+    #[test]
+    fn picks_backend_from_recipients() {
+        let age = recipients(&[
+            "age1ql3z7hjy54pw3hyww5ayyfg7zqgvc7w3j2elw8zmrj2kg5sfn9aqmcac8p",
+            "ssh-ed25519 AAAA",
+        ]);
+        let gpg = recipients(&["alice@example.com", "0xDEADBEEF"]);
+        let mixed = recipients(&[
+            "age1ql3z7hjy54pw3hyww5ayyfg7zqgvc7w3j2elw8zmrj2kg5sfn9aqmcac8p",
+            "alice@example.com",
+        ]);
+
+        assert_eq!(backend(&age).unwrap(), Backend::Age);
+        assert_eq!(backend(&gpg).unwrap(), Backend::Gpg);
+        assert!(backend(&mixed).is_err());
+    }
+}
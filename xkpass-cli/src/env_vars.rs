@@ -0,0 +1,64 @@
+//! The password as an environment variable: a line of a `.env` file for
+//! `--format dotenv`, or an `export` for `--format sh` to `eval`.
+
+/// Parse the name of a variable: letters, digits and `_`, not starting with a digit.
+pub fn parse_name(value: &str) -> Result<String, String> {
+    let valid = value
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && value.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if valid {
+        Ok(value.to_string())
+    } else {
+        Err(format!(
+            "{:?} isn't the name of a variable, made of letters, digits and '_'",
+            value
+        ))
+    }
+}
+
+/// `name` set to `password` in a `.env` file.
+///
+/// Single quotes keep every character as it is in the readers of `.env`
+/// files, Docker Compose's among them, but can't hold a single quote, so a
+/// password with one is double-quoted, escaping what they expand.
+pub fn dotenv(name: &str, password: &str) -> String {
+    if password.contains('\'') {
+        let mut quoted = String::with_capacity(password.len() + 2);
+        for c in password.chars() {
+            if matches!(c, '\\' | '"' | '$' | '`') {
+                quoted.push('\\');
+            }
+            quoted.push(c);
+        }
+        format!("{}=\"{}\"\n", name, quoted)
+    } else {
+        format!("{}='{}'\n", name, password)
+    }
+}
+
+/// `name` exported with `password` as its value, for a POSIX shell.
+pub fn sh(name: &str, password: &str) -> String {
+    format!("export {}='{}'\n", name, password.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quotes_passwords() {
+        assert_eq!(dotenv("DB_PASSWORD", "a b$c"), "DB_PASSWORD='a b$c'\n");
+        assert_eq!(
+            dotenv("P", r#"it's "$HOME""#),
+            "P=\"it's \\\"\\$HOME\\\"\"\n"
+        );
+        assert_eq!(sh("DB_PASSWORD", "a b$c"), "export DB_PASSWORD='a b$c'\n");
+        assert_eq!(sh("P", "it's"), "export P='it'\\''s'\n");
+        assert!(parse_name("_DB_PASSWORD2").is_ok());
+        for invalid in ["", "2FA", "DB-PASSWORD", "A B", "A=B"] {
+            assert!(parse_name(invalid).is_err(), "{}", invalid);
+        }
+    }
+}
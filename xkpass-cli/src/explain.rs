@@ -0,0 +1,219 @@
+//! Explaining where the entropy of a password comes from.
+
+use crate::{
+    render::{Format, Renderer},
+    separators,
+};
+use serde::Serialize;
+use std::{f64::consts::LOG2_10, io, io::Write};
+use xkpass::{Case, Config, WordTransform};
+
+/// A source of entropy: its name, the choices it makes and the bits they bring.
+pub type Row = (&'static str, String, f64);
+
+/// A row as a record of the output formats, the total being the last one.
+#[derive(Serialize, Debug)]
+struct Record<'a> {
+    source: &'a str,
+    choices: &'a str,
+    bits: f64,
+}
+
+impl Record<'_> {
+    fn line(&self) -> String {
+        format!(
+            "{:<10} {:<50} {:>6.1}",
+            self.source, self.choices, self.bits
+        )
+    }
+}
+
+/// Guesses per second of an attacker with dedicated hardware and a fast hash.
+pub const GUESSES_PER_SECOND: f64 = 1e10;
+
+/// Describe each source of entropy of passwords generated with `config` out of
+/// a list of `pool` words, with `digits` random digits added and the bits of
+/// `adjustments` made by other options, and the assumptions behind the estimate.
+pub fn explain(
+    config: &Config,
+    pool: usize,
+    list: &str,
+    digits: usize,
+    adjustments: &[Row],
+) -> String {
+    let mut out = Vec::new();
+    let mut renderer = Renderer::new(Format::Plain, &mut out);
+    render(&mut renderer, config, pool, list, digits, adjustments)
+        .and_then(|()| renderer.finish())
+        .expect("writing to memory can't fail");
+
+    String::from_utf8(out).expect("the explanation is UTF-8")
+}
+
+/// Write what [`explain`] describes with `renderer`, the rows and their total
+/// as records.
+pub fn render<W: Write>(
+    renderer: &mut Renderer<W>,
+    config: &Config,
+    pool: usize,
+    list: &str,
+    digits: usize,
+    adjustments: &[Row],
+) -> io::Result<()> {
+    let rows = rows(config, pool, list, digits, adjustments);
+    let total = total(&rows);
+
+    renderer.plain(format_args!(
+        "{:<10} {:<50} {:>6}\n",
+        "SOURCE", "CHOICES", "BITS"
+    ))?;
+    let records = rows
+        .iter()
+        .map(|(source, choices, bits)| Record {
+            source,
+            choices,
+            bits: *bits,
+        })
+        .chain([Record {
+            source: "total",
+            choices: "",
+            bits: total,
+        }]);
+    for record in records {
+        renderer.record(&record, record.line())?;
+    }
+    renderer.plain(format_args!(
+        "\nThis assumes that the attacker knows the word list and every setting above, and that\n\
+         the words were picked by a cryptographically secure random number generator, never by hand.\n\
+         At {:.0e} guesses per second, finding the password takes {} on average.\n",
+        GUESSES_PER_SECOND,
+        crack_time(total)
+    ))
+}
+
+/// The sources of entropy [`explain`] describes, followed by `adjustments`.
+pub fn rows(
+    config: &Config,
+    pool: usize,
+    list: &str,
+    digits: usize,
+    adjustments: &[Row],
+) -> Vec<Row> {
+    let case = match config.case {
+        Case::Mixed => format!("mixed, a coin flip for each of the {} words", config.number),
+        _ => format!("{}, the same for every word", config.case.name()),
+    };
+    let mut rows: Vec<Row> = vec![
+        (
+            "words",
+            format!(
+                "{} distinct words out of the {} of {}",
+                config.number, pool, list
+            ),
+            xkpass::word_entropy(pool, config.number),
+        ),
+        ("case", case, config.case.entropy() * config.number as f64),
+        match separators::digits(&config.separator) {
+            Some(len) => (
+                "separator",
+                match len {
+                    1 => String::from("a random digit between each pair of words"),
+                    _ => format!("{} random digits between each pair of words", len),
+                },
+                separators::entropy(config.number, len),
+            ),
+            None => (
+                "separator",
+                format!("{:?}, the same between every word", config.separator),
+                0.0,
+            ),
+        },
+        (
+            "digits",
+            match digits {
+                0 => String::from("none"),
+                _ => format!("{} random digits", digits),
+            },
+            LOG2_10 * digits as f64,
+        ),
+    ];
+    rows.extend_from_slice(adjustments);
+    rows
+}
+
+/// The bits of entropy of all `rows` together.
+pub fn total(rows: &[Row]) -> f64 {
+    rows.iter().map(|(_, _, bits)| bits).sum()
+}
+
+/// Average time to find a password with `bits` of entropy, trying half of the
+/// possibilities at [`GUESSES_PER_SECOND`].
+pub fn crack_time(bits: f64) -> String {
+    const UNITS: [(&str, f64); 6] = [
+        ("seconds", 60.0),
+        ("minutes", 60.0),
+        ("hours", 24.0),
+        ("days", 365.25),
+        ("years", 1000.0),
+        ("thousand years", 1000.0),
+    ];
+
+    let mut time = 2f64.powf(bits - 1.0) / GUESSES_PER_SECOND;
+    if time < 1.0 {
+        return String::from("less than a second");
+    }
+    for (unit, size) in UNITS {
+        if time < size {
+            return format!("{:.0} {}", time, unit);
+        }
+        time /= size;
+    }
+
+    String::from("millions of years")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adds_up_sources() {
+        let config = Config {
+            number: 4,
+            case: Case::Mixed,
+            ..Config::default()
+        };
+        let explanation = explain(&config, 7776, "long", 0, &[]);
+
+        assert!(explanation.contains("4 distinct words out of the 7776 of long"));
+        let total = xkpass::word_entropy(7776, 4) + 4.0;
+        assert!(explanation.contains(&format!("{:>6.1}\n\n", total)));
+        let rows = rows(&config, 7776, "long", 0, &[]);
+        assert!((super::total(&rows) - total).abs() < 1e-9);
+
+        let explanation = explain(&config, 7776, "long", 2, &[]);
+        assert!(explanation.contains("2 random digits"));
+        assert!(explanation.contains(&format!("{:>6.1}\n\n", total + 100f64.log2())));
+
+        let separated = Config {
+            separator: String::from("digits"),
+            ..config.clone()
+        };
+        let explanation = explain(&separated, 7776, "long", 0, &[]);
+        assert!(explanation.contains("a random digit between each pair of words"));
+        assert!(explanation.contains(&format!("{:>6.1}\n\n", total + 3.0 * LOG2_10)));
+
+        let rhyme = ("rhyme", String::from("every word ends alike"), -20.0);
+        let explanation = explain(&config, 7776, "long", 0, &[rhyme]);
+        assert!(explanation.contains("every word ends alike"));
+        assert!(explanation.contains(&format!("{:>6.1}\n\n", total - 20.0)));
+    }
+
+    #[test]
+    fn estimates_crack_time() {
+        assert_eq!(crack_time(20.0), "less than a second");
+        assert_eq!(crack_time(40.0), "55 seconds");
+        assert_eq!(crack_time(77.5), "339 thousand years");
+        assert_eq!(crack_time(90.0), "millions of years");
+    }
+}
@@ -0,0 +1,82 @@
+//! Telling passwords apart without showing them, by the art of their hash.
+//!
+//! The art is drawn like the randomart of OpenSSH keys: a bishop starts in the
+//! middle of the board and walks diagonally as told by each pair of bits of
+//! the SHA-256 hash of the password, and each square shows how often it was
+//! visited. Two passwords that differ by a single character give art that
+//! looks nothing alike.
+
+use sha2::{Digest, Sha256};
+use std::io::{self, BufRead};
+
+const WIDTH: usize = 17;
+const HEIGHT: usize = 9;
+
+/// Symbols for the number of visits of a square, then the start and the end.
+const SYMBOLS: &[u8] = b" .o+=*BOX@%&#/^SE";
+
+/// Read a password on stdin and print its fingerprint.
+pub fn run() -> io::Result<()> {
+    let mut password = String::new();
+    io::stdin().lock().read_line(&mut password)?;
+    print!(
+        "{}",
+        randomart(password.trim_end_matches(&['\r', '\n'][..]))
+    );
+    Ok(())
+}
+
+/// The fingerprint of `password`, framed and one line per row.
+pub fn randomart(password: &str) -> String {
+    let hash = Sha256::digest(password.as_bytes());
+    let end = SYMBOLS.len() - 1;
+    let start = end - 1;
+
+    let mut board = [[0usize; WIDTH]; HEIGHT];
+    let (mut x, mut y) = (WIDTH / 2, HEIGHT / 2);
+    for byte in hash {
+        for step in 0..4 {
+            let bits = byte >> (2 * step);
+            x = if bits & 1 == 1 {
+                (x + 1).min(WIDTH - 1)
+            } else {
+                x.saturating_sub(1)
+            };
+            y = if bits & 2 == 2 {
+                (y + 1).min(HEIGHT - 1)
+            } else {
+                y.saturating_sub(1)
+            };
+            if board[y][x] < start - 1 {
+                board[y][x] += 1;
+            }
+        }
+    }
+    board[HEIGHT / 2][WIDTH / 2] = start;
+    board[y][x] = end;
+
+    let mut art = String::from("+----[xkpass]-----+\n");
+    for row in board {
+        art.push('|');
+        art.extend(row.iter().map(|&visits| char::from(SYMBOLS[visits])));
+        art.push_str("|\n");
+    }
+    art.push_str("+----[SHA256]-----+\n");
+    art
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn draws_the_walk_of_the_hash() {
+        let art = randomart("correct horse battery staple");
+        assert_eq!(art.lines().count(), HEIGHT + 2);
+        assert!(art.lines().all(|line| line.chars().count() == WIDTH + 2));
+        let board: String = art.lines().skip(1).take(HEIGHT).collect();
+        assert_eq!(board.matches(['S', 'E']).count(), 2);
+        assert_eq!(art, randomart("correct horse battery staple"));
+        assert_ne!(art, randomart("correct horse battery staples"));
+    }
+}
@@ -0,0 +1,86 @@
+//! Looking at the word lists: their size and strength, and their words.
+
+use crate::{
+    render::{Format, Renderer, Word},
+    word_lists::ListName,
+};
+use serde::Serialize;
+use std::io;
+
+/// The size of a list, the record of `inspect`.
+#[derive(Serialize, Debug)]
+struct Size {
+    list: String,
+    words: usize,
+    bits_per_word: f64,
+}
+
+/// Print the size of `list`, or of every list, and the bits each word is worth.
+pub fn inspect(list: Option<ListName>, format: Format) -> io::Result<()> {
+    let lists = match list {
+        Some(list) => vec![list],
+        None => ListName::all(),
+    };
+
+    let mut out = Renderer::new(format, io::BufWriter::new(io::stdout().lock()));
+    out.plain(format_args!(
+        "{:<12} {:>8} {:>10}\n",
+        "LIST", "WORDS", "BITS/WORD"
+    ))?;
+    for list in lists {
+        let source = list.open()?;
+        let size = Size {
+            list: list.to_string(),
+            words: source.len(),
+            bits_per_word: bits_per_word(source.len()),
+        };
+        let line = format!(
+            "{:<12} {:>8} {:>10.2}",
+            size.list, size.words, size.bits_per_word
+        );
+        out.record(&size, line)?;
+    }
+
+    out.finish()
+}
+
+/// Print every word of `list`, one per line.
+pub fn dump(list: &ListName, format: Format) -> io::Result<()> {
+    let mut out = Renderer::new(format, io::BufWriter::new(io::stdout().lock()));
+    let source = list.open()?;
+    for word in source.iter() {
+        out.record(&Word { word }, word)?;
+    }
+
+    out.finish()
+}
+
+/// Print `count` distinct words of `list` picked at random, one per line.
+pub fn sample(list: &ListName, count: usize, format: Format) -> Result<(), String> {
+    let source = list.open().map_err(|err| err.to_string())?;
+    let words = source.words();
+    let sample = xkpass::sample_words(&words, count).map_err(|err| err.to_string())?;
+
+    let mut out = Renderer::new(format, io::stdout().lock());
+    for word in sample {
+        out.record(&Word { word }, word)
+            .map_err(|err| err.to_string())?;
+    }
+
+    out.finish().map_err(|err| err.to_string())
+}
+
+fn bits_per_word(len: usize) -> f64 {
+    xkpass::word_entropy(len, len.min(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn computes_bits_per_word() {
+        assert!((bits_per_word(7776) - 12.925).abs() < 0.001);
+        assert_eq!(bits_per_word(0), 0.0);
+    }
+}
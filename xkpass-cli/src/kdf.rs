@@ -0,0 +1,242 @@
+//! Deriving keys from passphrases with Argon2id, for the vault, keyfiles and
+//! the keys of `--derive-key`.
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::Engine;
+use clap::ValueEnum;
+use rand::RngCore;
+use std::{fs, io, path::Path};
+
+/// Bytes of the salts xkpass picks.
+pub const SALT_LEN: usize = 16;
+
+/// Bytes of the keyfiles of `--keyfile`, as many as the 512-bit keys of
+/// AES-XTS that LUKS uses by default.
+pub const KEYFILE_LEN: usize = 64;
+
+/// Cost of deriving a key with Argon2id.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cost {
+    /// Memory, in KiB.
+    pub memory: u32,
+    pub passes: u32,
+    pub lanes: u32,
+}
+
+impl Cost {
+    /// The cost of the keys xkpass derives, more than the minimum OWASP
+    /// recommends since a key is only derived once per command.
+    pub const DEFAULT: Self = Self {
+        memory: 64 * 1024,
+        passes: 3,
+        lanes: 1,
+    };
+
+    /// The most a cost read from a file may ask for, 1 GiB and 10 passes, so
+    /// that a tampered header can't make xkpass use up the memory or time of
+    /// the computer before the key turns out to be wrong.
+    pub const MAX: Self = Self {
+        memory: 1024 * 1024,
+        passes: 10,
+        lanes: 8,
+    };
+
+    /// Whether the cost is at most `max` in every parameter.
+    pub fn within(self, max: Self) -> bool {
+        self.memory <= max.memory && self.passes <= max.passes && self.lanes <= max.lanes
+    }
+}
+
+/// Key derivation functions of `--kdf`.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kdf {
+    Argon2id,
+}
+
+/// Encodings of the keys of `--derive-key`.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Encoding {
+    Hex,
+    Base64,
+}
+
+/// A key derived from a passphrase, with all it takes to derive it again.
+#[derive(Debug)]
+pub struct Derived {
+    pub key: String,
+    /// In hex digits.
+    pub salt: String,
+    /// The function and its parameters, in the PHC string format such as
+    /// `argon2id$v=19$m=65536,t=3,p=1`.
+    pub kdf: String,
+}
+
+/// Derive a key of `len` bytes from `passphrase` with `kdf` at `cost` and
+/// `salt`, or a new salt if there is none, and encode it with `encoding`.
+pub fn derive(
+    passphrase: &str,
+    len: usize,
+    kdf: Kdf,
+    cost: Cost,
+    salt: Option<&[u8]>,
+    encoding: Encoding,
+) -> io::Result<Derived> {
+    let salt = salt.map_or_else(|| self::salt().to_vec(), <[u8]>::to_vec);
+    let mut key = vec![0; len];
+    let kdf = match kdf {
+        Kdf::Argon2id => {
+            argon2id(passphrase.as_bytes(), &salt, cost, &mut key)?;
+            format!(
+                "argon2id$v=19$m={},t={},p={}",
+                cost.memory, cost.passes, cost.lanes
+            )
+        }
+    };
+    let key = match encoding {
+        Encoding::Hex => hex(&key),
+        Encoding::Base64 => base64::engine::general_purpose::STANDARD.encode(&key),
+    };
+    Ok(Derived {
+        key,
+        salt: hex(&salt),
+        kdf,
+    })
+}
+
+/// The salt of `--salt`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Salt(pub Vec<u8>);
+
+/// Parse the salt of `--salt`, from 8 to 64 bytes in hex digits.
+pub fn parse_salt(value: &str) -> Result<Salt, String> {
+    let invalid = || format!("{:?} isn't a salt of 16 to 128 hex digits", value);
+    if !(16..=128).contains(&value.len()) || !value.len().is_multiple_of(2) || !value.is_ascii() {
+        return Err(invalid());
+    }
+    value
+        .as_bytes()
+        .chunks(2)
+        .map(|hex| {
+            let hex = std::str::from_utf8(hex).map_err(|_| invalid())?;
+            u8::from_str_radix(hex, 16).map_err(|_| invalid())
+        })
+        .collect::<Result<_, _>>()
+        .map(Salt)
+}
+
+/// Fill `key` with the key of `passphrase` and `salt`.
+pub fn argon2id(passphrase: &[u8], salt: &[u8], cost: Cost, key: &mut [u8]) -> io::Result<()> {
+    let params = Params::new(cost.memory, cost.passes, cost.lanes, Some(key.len()))
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err.to_string()))?;
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase, salt, key)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err.to_string()))
+}
+
+/// A new random salt.
+pub fn salt() -> [u8; SALT_LEN] {
+    let mut salt = [0; SALT_LEN];
+    rand::rngs::OsRng.fill_bytes(&mut salt);
+    salt
+}
+
+/// Write the key of `passphrase` and a new salt to a new file at `path`,
+/// readable by the user only, and return the salt to derive it again with.
+pub fn write_keyfile(path: &Path, passphrase: &str, cost: Cost) -> io::Result<[u8; SALT_LEN]> {
+    let salt = salt();
+    let mut key = [0; KEYFILE_LEN];
+    argon2id(passphrase.as_bytes(), &salt, cost, &mut key)?;
+
+    let mut options = fs::OpenOptions::new();
+    // never overwrite a keyfile, the volume it opens would be lost
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    io::Write::write_all(&mut options.open(path)?, &key)?;
+    Ok(salt)
+}
+
+/// `bytes` as lowercase hex digits.
+pub fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Cheap enough for tests.
+    const COST: Cost = Cost {
+        memory: 64,
+        passes: 1,
+        lanes: 1,
+    };
+
+    #[test]
+    fn derives_keys_of_passphrase_and_salt() {
+        let derive = |passphrase: &str, salt: &[u8]| {
+            let mut key = [0; 32];
+            argon2id(passphrase.as_bytes(), salt, COST, &mut key).unwrap();
+            key
+        };
+        let key = derive("correct horse", &[1; SALT_LEN]);
+        assert_eq!(derive("correct horse", &[1; SALT_LEN]), key);
+        assert_ne!(derive("correct horse", &[2; SALT_LEN]), key);
+        assert_ne!(derive("battery staple", &[1; SALT_LEN]), key);
+        // Argon2 wants salts of 8 bytes at least
+        assert!(argon2id(b"correct horse", &[1; 4], COST, &mut [0; 32]).is_err());
+    }
+
+    #[test]
+    fn derives_keys_again_from_their_salt() {
+        let salt = parse_salt("000102030405060708090a0b0c0d0e0f").unwrap();
+        assert_eq!(salt.0, (0..16).collect::<Vec<u8>>());
+        let derived = derive(
+            "correct horse",
+            32,
+            Kdf::Argon2id,
+            COST,
+            None,
+            Encoding::Hex,
+        )
+        .unwrap();
+        assert_eq!(derived.key.len(), 64);
+        assert_eq!(derived.kdf, "argon2id$v=19$m=64,t=1,p=1");
+
+        let salt = parse_salt(&derived.salt).unwrap();
+        let again = derive(
+            "correct horse",
+            32,
+            Kdf::Argon2id,
+            COST,
+            Some(&salt.0),
+            Encoding::Hex,
+        )
+        .unwrap();
+        assert_eq!(again.key, derived.key);
+        let encoded = derive(
+            "correct horse",
+            32,
+            Kdf::Argon2id,
+            COST,
+            Some(&salt.0),
+            Encoding::Base64,
+        );
+        assert_eq!(encoded.unwrap().key.len(), 44);
+
+        for invalid in ["00", "zz00000000000000", "0001020304050607080"] {
+            assert!(parse_salt(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn writes_new_keyfiles_only() {
+        let path = std::env::temp_dir().join(format!("xkpass-keyfile-{}", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let salt = write_keyfile(&path, "correct horse", COST).unwrap();
+        assert_eq!(fs::read(&path).unwrap().len(), KEYFILE_LEN);
+        assert_ne!(salt, [0; SALT_LEN]);
+        assert!(write_keyfile(&path, "correct horse", COST).is_err());
+        fs::remove_file(&path).unwrap();
+    }
+}
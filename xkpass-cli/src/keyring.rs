@@ -0,0 +1,366 @@
+//! Keeping generated passwords in the system keychain, where only Touch ID on
+//! macOS or Windows Hello on Windows gives them back.
+//!
+//! The platform enforces the prompt, not xkpass: on macOS the item sits in the
+//! data protection keychain with an access control requiring the currently
+//! enrolled fingers, and on Windows the secret is encrypted under a key derived
+//! from a signature of the Windows Hello key of xkpass, which the TPM only
+//! makes after recognizing the user. Another program running as the user gets
+//! the same prompt, or nothing at all.
+
+use crate::clipboard::{self, Selection};
+use std::io;
+use xkpass::Config;
+use zeroize::Zeroizing;
+
+#[cfg(target_os = "macos")]
+use touch_id as platform;
+#[cfg(windows)]
+use windows_hello as platform;
+
+/// Name of the way the user is recognized, for messages.
+#[cfg(target_os = "macos")]
+pub const NAME: &str = "Touch ID";
+#[cfg(windows)]
+pub const NAME: &str = "Windows Hello";
+#[cfg(not(any(target_os = "macos", windows)))]
+pub const NAME: &str = "biometrics";
+
+/// Keep `secret` under `account`, replacing what was kept there before.
+#[cfg(any(target_os = "macos", windows))]
+pub fn store(account: &str, secret: &[u8]) -> io::Result<()> {
+    platform::store(account, secret)
+}
+
+/// The secret kept under `account`, once the user is recognized.
+///
+/// Nothing is asked of the user when no secret is kept.
+#[cfg(any(target_os = "macos", windows))]
+pub fn load(account: &str) -> io::Result<Option<Zeroizing<Vec<u8>>>> {
+    platform::load(account)
+}
+
+/// Forget the secret kept under `account`, if there is one.
+#[cfg(any(target_os = "macos", windows))]
+pub fn remove(account: &str) -> io::Result<()> {
+    platform::remove(account)
+}
+
+/// The other platforms have no keychain that asks to recognize the user.
+#[cfg(not(any(target_os = "macos", windows)))]
+pub fn store(_account: &str, _secret: &[u8]) -> io::Result<()> {
+    Err(unsupported())
+}
+
+/// Nothing is ever kept.
+#[cfg(not(any(target_os = "macos", windows)))]
+pub fn load(_account: &str) -> io::Result<Option<Zeroizing<Vec<u8>>>> {
+    Ok(None)
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+pub fn remove(_account: &str) -> io::Result<()> {
+    Err(unsupported())
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+fn unsupported() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        "the keyring needs Touch ID on macOS or Windows Hello on Windows",
+    )
+}
+
+/// Generate a password with `config`, keep it under `label` and print it.
+pub fn run_add(label: &str, config: Config) -> io::Result<()> {
+    let password = xkpass::generate_password(config)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err.to_string()))?;
+    store(label, password.expose().as_bytes())?;
+    eprintln!("kept the password under {} behind {}", label, NAME);
+    println!("{}", password.expose());
+    Ok(())
+}
+
+/// Print the password kept under `label`, or copy it to `selection`.
+pub fn run_show(label: &str, copy: bool, selection: Selection) -> io::Result<()> {
+    let secret = load(label)?.ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("no password is kept under {}", label),
+        )
+    })?;
+    let password = std::str::from_utf8(&secret).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("the password kept under {} isn't UTF-8", label),
+        )
+    })?;
+
+    if copy {
+        clipboard::copy(password, selection)?;
+        eprintln!("copied the password of {} to the clipboard", label);
+    } else {
+        println!("{}", password);
+    }
+    Ok(())
+}
+
+/// Forget the password kept under `label`.
+pub fn run_remove(label: &str) -> io::Result<()> {
+    remove(label)
+}
+
+#[cfg(target_os = "macos")]
+mod touch_id {
+    use security_framework::{
+        access_control::{ProtectionMode, SecAccessControl},
+        base::Error,
+        passwords::{self, AccessControlOptions, PasswordOptions},
+    };
+    use std::io;
+    use zeroize::Zeroizing;
+
+    /// Service of the keychain items holding the secrets.
+    const SERVICE: &str = "xkpass keyring";
+
+    /// `errSecItemNotFound`, for a secret that was never kept.
+    const ITEM_NOT_FOUND: i32 = -25300;
+    /// `errSecUserCanceled`, when Touch ID is dismissed.
+    const USER_CANCELED: i32 = -128;
+    /// `errSecAuthFailed`, when Touch ID doesn't recognize the user.
+    const AUTH_FAILED: i32 = -25293;
+    /// `errSecMissingEntitlement`, for a binary the data protection keychain refuses.
+    const MISSING_ENTITLEMENT: i32 = -34018;
+
+    /// Options naming the item of `account` in the data protection keychain,
+    /// the only one honoring biometric access controls.
+    fn options(account: &str) -> PasswordOptions {
+        let mut options = PasswordOptions::new_generic_password(SERVICE, account);
+        options.use_protected_keychain();
+        options
+    }
+
+    fn error(err: Error) -> io::Error {
+        let denied = |message| io::Error::new(io::ErrorKind::PermissionDenied, message);
+        match err.code() {
+            USER_CANCELED => denied("Touch ID was canceled"),
+            AUTH_FAILED => denied("Touch ID didn't recognize the user"),
+            MISSING_ENTITLEMENT => denied(
+                "the keychain only accepts a binary signed with a keychain-access-groups entitlement",
+            ),
+            _ => io::Error::other(err),
+        }
+    }
+
+    pub(super) fn store(account: &str, secret: &[u8]) -> io::Result<()> {
+        // an item can't be updated without Touch ID, so it's replaced instead
+        remove(account)?;
+
+        // readable only with the fingers enrolled now, on this Mac, while it has a passcode
+        let access = SecAccessControl::create_with_protection(
+            Some(ProtectionMode::AccessibleWhenPasscodeSetThisDeviceOnly),
+            AccessControlOptions::BIOMETRY_CURRENT_SET.bits(),
+        )
+        .map_err(error)?;
+        let mut options = options(account);
+        options.set_access_control(access);
+        passwords::set_generic_password_options(secret, options).map_err(error)
+    }
+
+    pub(super) fn load(account: &str) -> io::Result<Option<Zeroizing<Vec<u8>>>> {
+        match passwords::generic_password(options(account)) {
+            Ok(secret) => Ok(Some(Zeroizing::new(secret))),
+            Err(err) if err.code() == ITEM_NOT_FOUND => Ok(None),
+            Err(err) => Err(error(err)),
+        }
+    }
+
+    pub(super) fn remove(account: &str) -> io::Result<()> {
+        match passwords::delete_generic_password_options(options(account)) {
+            Err(err) if err.code() != ITEM_NOT_FOUND => Err(error(err)),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(windows)]
+mod windows_hello {
+    use chacha20poly1305::{aead::Aead, KeyInit, XChaCha20Poly1305, XNonce};
+    use rand::RngCore;
+    use sha2::{Digest, Sha256};
+    use std::{io, ptr, slice};
+    use windows::{
+        core::{Array, HSTRING},
+        Security::{
+            Credentials::{
+                KeyCredential, KeyCredentialCreationOption, KeyCredentialManager,
+                KeyCredentialStatus,
+            },
+            Cryptography::CryptographicBuffer,
+        },
+    };
+    use windows_sys::Win32::{
+        Foundation::ERROR_NOT_FOUND,
+        Security::Credentials::{
+            CredDeleteW, CredFree, CredReadW, CredWriteW, CREDENTIALW, CRED_PERSIST_LOCAL_MACHINE,
+            CRED_TYPE_GENERIC,
+        },
+    };
+    use zeroize::{Zeroize, Zeroizing};
+
+    /// Name of the Windows Hello key shared by every secret of xkpass.
+    const KEY_NAME: &str = "xkpass";
+
+    const NONCE_LEN: usize = 24;
+
+    fn status_error(status: KeyCredentialStatus) -> io::Error {
+        let message = match status {
+            KeyCredentialStatus::UserCanceled => "Windows Hello was canceled",
+            KeyCredentialStatus::UserPrefersPassword => {
+                "Windows Hello was declined in favor of a password"
+            }
+            KeyCredentialStatus::SecurityDeviceLocked => {
+                "the security device of Windows Hello is locked"
+            }
+            KeyCredentialStatus::NotFound => "Windows Hello has no key for xkpass",
+            _ => "Windows Hello isn't set up for this user",
+        };
+        io::Error::new(io::ErrorKind::PermissionDenied, message)
+    }
+
+    /// The Windows Hello key of xkpass, created when `create` is set and it's missing.
+    fn credential(create: bool) -> io::Result<KeyCredential> {
+        let name = HSTRING::from(KEY_NAME);
+        let mut result = KeyCredentialManager::OpenAsync(&name)?.join()?;
+        if create && result.Status()? == KeyCredentialStatus::NotFound {
+            result = KeyCredentialManager::RequestCreateAsync(
+                &name,
+                KeyCredentialCreationOption::FailIfExists,
+            )?
+            .join()?;
+        }
+        match result.Status()? {
+            KeyCredentialStatus::Success => Ok(result.Credential()?),
+            status => Err(status_error(status)),
+        }
+    }
+
+    /// The key wrapping the secret of `account`.
+    ///
+    /// The Windows Hello key signs with PKCS#1 v1.5, so signing the same
+    /// challenge always gives the same signature, and the key never leaves
+    /// the TPM.
+    fn wrapping_key(account: &str, create: bool) -> io::Result<Zeroizing<[u8; 32]>> {
+        let challenge = format!("xkpass keyring:{}", account);
+        let challenge = CryptographicBuffer::CreateFromByteArray(challenge.as_bytes())?;
+        let signed = credential(create)?.RequestSignAsync(&challenge)?.join()?;
+        if signed.Status()? != KeyCredentialStatus::Success {
+            return Err(status_error(signed.Status()?));
+        }
+
+        let mut signature = Array::<u8>::new();
+        CryptographicBuffer::CopyToByteArray(&signed.Result()?, &mut signature)?;
+        let key = Zeroizing::new(Sha256::digest(&signature[..]).into());
+        signature[..].zeroize();
+        Ok(key)
+    }
+
+    pub(super) fn store(account: &str, secret: &[u8]) -> io::Result<()> {
+        let key = wrapping_key(account, true)?;
+        let mut nonce = [0; NONCE_LEN];
+        rand::rngs::OsRng.fill_bytes(&mut nonce);
+        let ciphertext = XChaCha20Poly1305::new((&*key).into())
+            .encrypt(&XNonce::from(nonce), secret)
+            .map_err(|_| io::Error::other("the password couldn't be encrypted"))?;
+
+        let mut blob = [&nonce[..], &ciphertext].concat();
+        write(account, &mut blob)
+    }
+
+    pub(super) fn load(account: &str) -> io::Result<Option<Zeroizing<Vec<u8>>>> {
+        let blob = match read(account)? {
+            Some(blob) if blob.len() >= NONCE_LEN => blob,
+            Some(_) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "the kept password is truncated",
+                ))
+            }
+            None => return Ok(None),
+        };
+
+        let (nonce, ciphertext) = blob.split_at(NONCE_LEN);
+        let nonce = XNonce::try_from(nonce).expect("nonce length");
+        let key = wrapping_key(account, false)?;
+        let secret = XChaCha20Poly1305::new((&*key).into())
+            .decrypt(&nonce, ciphertext)
+            .map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "the kept password was sealed with another Windows Hello key",
+                )
+            })?;
+        Ok(Some(Zeroizing::new(secret)))
+    }
+
+    pub(super) fn remove(account: &str) -> io::Result<()> {
+        let target = target(account);
+        // SAFETY: the target is NUL-terminated
+        if unsafe { CredDeleteW(target.as_ptr(), CRED_TYPE_GENERIC, 0) } == 0 {
+            let err = io::Error::last_os_error();
+            if err.raw_os_error() != Some(ERROR_NOT_FOUND as i32) {
+                return Err(err);
+            }
+        }
+        Ok(())
+    }
+
+    /// Name of the credential holding the secret of `account`, NUL-terminated.
+    fn target(account: &str) -> Vec<u16> {
+        format!("xkpass keyring:{}", account)
+            .encode_utf16()
+            .chain(Some(0))
+            .collect()
+    }
+
+    fn write(account: &str, blob: &mut [u8]) -> io::Result<()> {
+        let mut target = target(account);
+        // SAFETY: a zeroed CREDENTIALW is valid, with no optional fields set
+        let mut credential: CREDENTIALW = unsafe { std::mem::zeroed() };
+        credential.Type = CRED_TYPE_GENERIC;
+        credential.TargetName = target.as_mut_ptr();
+        credential.CredentialBlobSize = blob.len() as u32;
+        credential.CredentialBlob = blob.as_mut_ptr();
+        credential.Persist = CRED_PERSIST_LOCAL_MACHINE;
+        // SAFETY: the credential points to buffers that outlive the call
+        if unsafe { CredWriteW(&credential, 0) } == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    fn read(account: &str) -> io::Result<Option<Vec<u8>>> {
+        let target = target(account);
+        let mut credential: *mut CREDENTIALW = ptr::null_mut();
+        // SAFETY: the target is NUL-terminated, the credential is freed below
+        if unsafe { CredReadW(target.as_ptr(), CRED_TYPE_GENERIC, 0, &mut credential) } == 0 {
+            let err = io::Error::last_os_error();
+            return match err.raw_os_error() {
+                Some(code) if code == ERROR_NOT_FOUND as i32 => Ok(None),
+                _ => Err(err),
+            };
+        }
+        // SAFETY: CredReadW succeeded, so the credential and its blob are valid
+        let blob = unsafe {
+            let credential = &*credential;
+            slice::from_raw_parts(
+                credential.CredentialBlob,
+                credential.CredentialBlobSize as usize,
+            )
+            .to_vec()
+        };
+        // SAFETY: the credential was allocated by CredReadW
+        unsafe { CredFree(credential.cast()) };
+        Ok(Some(blob))
+    }
+}
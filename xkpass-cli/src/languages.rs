@@ -0,0 +1,181 @@
+//! Passphrases mixing the words of several lists, such as lists in different
+//! languages, with at least one word of each.
+//!
+//! The words are drawn out of all the lists together, and drawn again until
+//! each list has a word in the password, so that every such password is as
+//! likely as any other. The share of draws that are kept is worked out by
+//! inclusion-exclusion over the lists, which gives the bits the requirement
+//! costs. A word found in several lists counts for each of them.
+
+use crate::{explain::Row, word_lists::ListName};
+use rand::Rng;
+use std::collections::HashMap;
+
+/// Most draws before giving up on one with a word of every list.
+pub const MAX_ATTEMPTS: usize = 100_000;
+
+/// Most lists that can be mixed.
+pub const MAX_LISTS: usize = 8;
+
+/// The lists of `mixed` other than `list`, each only once.
+pub fn others(list: &ListName, mixed: &[ListName]) -> Vec<ListName> {
+    let mut names = vec![list.to_string()];
+    let mut others = Vec::new();
+    for other in mixed {
+        if !names.contains(&other.to_string()) {
+            names.push(other.to_string());
+            others.push(other.clone());
+        }
+    }
+    others
+}
+
+/// The names of `list` and the `others` mixed with it, such as "long, fr and de".
+pub fn names(list: &ListName, others: &[ListName]) -> String {
+    match others.split_last() {
+        None => list.to_string(),
+        Some((last, rest)) => {
+            let mut names = list.to_string();
+            for other in rest {
+                names += &format!(", {}", other);
+            }
+            format!("{} and {}", names, last)
+        }
+    }
+}
+
+/// The words of several lists together, with the lists each of them belongs to.
+pub struct Mix<'a> {
+    words: Vec<&'a str>,
+    lists: Vec<u8>,
+    count: usize,
+}
+
+impl<'a> Mix<'a> {
+    /// Mix `lists` for passwords of `number` words.
+    pub fn new(lists: &[Vec<&'a str>], number: usize) -> Result<Self, String> {
+        if lists.len() > MAX_LISTS {
+            return Err(format!("--mix-languages mixes at most {} lists", MAX_LISTS));
+        }
+        if number < lists.len() {
+            return Err(format!(
+                "--mix-languages needs at least a word for each of the {} lists, not {}",
+                lists.len(),
+                number
+            ));
+        }
+
+        let mut index: HashMap<&str, usize> = HashMap::new();
+        let mut mix = Self {
+            words: Vec::new(),
+            lists: Vec::new(),
+            count: lists.len(),
+        };
+        for (i, list) in lists.iter().enumerate() {
+            for &word in list {
+                let at = *index.entry(word).or_insert_with(|| {
+                    mix.words.push(word);
+                    mix.lists.push(0);
+                    mix.words.len() - 1
+                });
+                mix.lists[at] |= 1 << i;
+            }
+        }
+        if number > mix.words.len() {
+            return Err(format!(
+                "--number {} is more than the {} words of the lists",
+                number,
+                mix.words.len()
+            ));
+        }
+        Ok(mix)
+    }
+
+    /// The words of every list, each only once.
+    pub fn words(&self) -> &[&'a str] {
+        &self.words
+    }
+
+    /// `number` distinct words, at least one of each list, in random order.
+    pub fn pick(&self, number: usize, rng: &mut impl Rng) -> Result<Vec<&'a str>, String> {
+        let every = (1u8 << self.count) - 1;
+        for _ in 0..MAX_ATTEMPTS {
+            let picked = rand::seq::index::sample(rng, self.words.len(), number);
+            let lists = picked.iter().fold(0, |lists, i| lists | self.lists[i]);
+            if lists == every {
+                return Ok(picked.iter().map(|i| self.words[i]).collect());
+            }
+        }
+
+        Err(format!(
+            "none of {} draws had a word of every list, one of them is too small",
+            MAX_ATTEMPTS
+        ))
+    }
+
+    /// Share of the draws of `number` words with a word of every list.
+    fn share(&self, number: usize) -> f64 {
+        let total = self.words.len();
+        (0u8..1 << self.count)
+            .map(|avoided| {
+                // draws leaving out every list of `avoided`
+                let left = self.lists.iter().filter(|&&l| l & avoided == 0).count();
+                let draws: f64 = (0..number)
+                    .map(|i| left.saturating_sub(i) as f64 / (total - i) as f64)
+                    .product();
+                if avoided.count_ones() % 2 == 0 {
+                    draws
+                } else {
+                    -draws
+                }
+            })
+            .sum()
+    }
+
+    /// The bits lost to having a word of each of the lists `names`, in
+    /// passwords of `number` words.
+    pub fn row(&self, names: &str, number: usize) -> Row {
+        (
+            "languages",
+            format!("at least one word of each of {}", names),
+            self.share(number).log2(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_a_word_of_each_list() {
+        let lists = vec![
+            vec!["un", "deux", "trois"],
+            vec!["one", "two", "three", "un"],
+        ];
+        let mix = Mix::new(&lists, 2).unwrap();
+        assert_eq!(mix.words().len(), 6);
+
+        for _ in 0..100 {
+            let picked = mix.pick(2, &mut rand::thread_rng()).unwrap();
+            assert!(picked.iter().any(|word| lists[0].contains(word)));
+            assert!(picked.iter().any(|word| lists[1].contains(word)));
+        }
+        // 6 * 5 ordered draws, less the 2 * 1 of French words alone other than "un"
+        // and the 3 * 2 of English ones
+        assert!((mix.share(2) - 22.0 / 30.0).abs() < 1e-9);
+
+        assert!(Mix::new(&lists, 1).is_err());
+        assert!(Mix::new(&lists, 7).is_err());
+    }
+
+    #[test]
+    fn names_lists_once() {
+        let name = |name: &str| name.parse::<ListName>().unwrap();
+        let mixed = [name("fr"), name("long"), name("de"), name("fr")];
+        let others = others(&name("long"), &mixed);
+        assert_eq!(names(&name("long"), &others), "long, fr and de");
+        assert_eq!(names(&name("long"), &others[..1]), "long and fr");
+        assert_eq!(names(&name("long"), &[]), "long");
+    }
+}
@@ -0,0 +1,223 @@
+//! The settings of the last password generated, replayed with `--last`.
+//!
+//! Only the settings are kept, never the password nor a seed it was drawn
+//! from: they are written after every password generated to `last.toml` in
+//! the state directory, as they were once `--recipe` and `--mobile` changed
+//! them. The number of words, the separator, the list and the case are in the
+//! format of the configuration file, followed by the options that change
+//! which passwords come out, such as `--mutate` or `--rhyme`.
+
+use crate::{config_file, typing::Layout, Output, Settings};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::{fs, io, path::PathBuf};
+use xkpass::{Case, Order};
+
+/// The settings a password was generated with.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Last {
+    number: usize,
+    separator: String,
+    list: String,
+    case: Case,
+    #[serde(flatten)]
+    options: Options,
+}
+
+/// The options of [`Output`] a password was generated with, all of them off
+/// in the files written before they were kept.
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default, rename_all = "kebab-case")]
+struct Options {
+    order: Option<Order>,
+    mutate: Option<usize>,
+    embed_symbol: Option<String>,
+    force: bool,
+    digits_as_words: bool,
+    match_regex: Option<String>,
+    mix_languages: Vec<String>,
+    rhyme: bool,
+    mobile: bool,
+    easy_to_type: Option<String>,
+}
+
+impl Last {
+    pub fn new(settings: &Settings, output: &Output) -> Self {
+        let layout = output.easy_to_type.map(|layout| {
+            let name = layout.to_possible_value().expect("layouts have names");
+            name.get_name().to_string()
+        });
+        Self {
+            number: settings.config.number,
+            separator: settings.config.separator.clone(),
+            list: settings.list.to_string(),
+            case: settings.config.case.clone(),
+            options: Options {
+                order: output.order,
+                mutate: output.mutate,
+                embed_symbol: output.embed_symbol.clone(),
+                force: output.force,
+                digits_as_words: output.digits_as_words,
+                match_regex: output.match_regex.clone(),
+                mix_languages: output
+                    .mix_languages
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect(),
+                rhyme: output.rhyme,
+                mobile: output.mobile,
+                easy_to_type: layout,
+            },
+        }
+    }
+
+    /// The number of words, the separator, the list and the case.
+    pub fn settings(&self) -> Result<Settings, String> {
+        let list = self.list.parse()?;
+        Ok(Settings::new(
+            self.number,
+            self.separator.clone(),
+            list,
+            self.case.clone(),
+        ))
+    }
+
+    /// Fill in the options of `output` left out on the command line, which
+    /// still overrides them.
+    pub fn apply(self, output: &mut Output) -> Result<(), String> {
+        let options = self.options;
+        output.order = output.order.or(options.order);
+        output.mutate = output.mutate.or(options.mutate);
+        output.embed_symbol = output.embed_symbol.take().or(options.embed_symbol);
+        output.force |= options.force;
+        output.digits_as_words |= options.digits_as_words;
+        output.match_regex = output.match_regex.take().or(options.match_regex);
+        if output.mix_languages.is_empty() {
+            output.mix_languages = options
+                .mix_languages
+                .iter()
+                .map(|list| list.parse())
+                .collect::<Result<_, _>>()?;
+        }
+        output.rhyme |= options.rhyme;
+        output.mobile |= options.mobile;
+        if output.easy_to_type.is_none() {
+            output.easy_to_type = options
+                .easy_to_type
+                .map(|name| Layout::from_str(&name, false))
+                .transpose()?;
+        }
+        Ok(())
+    }
+
+    /// Write the settings to [`path`], replacing those of the previous password.
+    pub fn save(&self) -> io::Result<()> {
+        let Some(path) = path() else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let contents =
+            toml::to_string(self).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        fs::write(path, contents)
+    }
+}
+
+/// Path of the file holding the last settings: `last.toml` in [`config_file::state_dir`].
+pub fn path() -> Option<PathBuf> {
+    config_file::state_dir().map(|dir| dir.join("last.toml"))
+}
+
+/// The settings of the last password generated.
+pub fn load() -> Result<Last, String> {
+    let path = path().ok_or("there is no state directory to keep the last settings in")?;
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            return Err(String::from(
+                "no password was generated yet, so --last has no settings to use",
+            ))
+        }
+        Err(err) => return Err(format!("{}: {}", path.display(), err)),
+    };
+
+    toml::from_str(&contents).map_err(|err| format!("{}: {}", path.display(), err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{recipe, word_lists::ListName, Cli};
+    use clap::Parser;
+
+    fn round_trip(last: &Last) -> Last {
+        toml::from_str(&toml::to_string(last).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn reads_back_settings() {
+        let list = "mine".parse().unwrap();
+        let output = Cli::parse_from(["xkpass"]).output;
+        let last = Last::new(
+            &Settings::new(4, String::from("+"), list, Case::Mixed),
+            &output,
+        );
+        let settings = round_trip(&last).settings().unwrap();
+
+        assert_eq!(settings.config.number, 4);
+        assert_eq!(settings.config.separator, "+");
+        assert!(matches!(settings.list, ListName::Custom(name) if name == "mine"));
+        assert!(matches!(settings.config.case, Case::Mixed));
+    }
+
+    #[test]
+    fn reads_back_options() {
+        let cli = Cli::parse_from([
+            "xkpass",
+            "--order",
+            "length",
+            "--mutate",
+            "2",
+            "--embed-symbol=!?",
+            "--digits-as-words",
+            "--match-regex",
+            "^[a-z ]+$",
+            "--rhyme",
+            "--mobile",
+            "--easy-to-type=dvorak",
+        ]);
+        let settings = Settings::new(5, String::from(" "), "short1".parse().unwrap(), Case::Lower);
+        let last = round_trip(&Last::new(&settings, &cli.output));
+
+        let mut output = Cli::parse_from(["xkpass"]).output;
+        let replayed = last.settings().unwrap();
+        last.apply(&mut output).unwrap();
+        assert_eq!(
+            recipe::write(&replayed, &output, None).unwrap(),
+            recipe::write(&settings, &cli.output, None).unwrap()
+        );
+    }
+
+    #[test]
+    fn lets_the_command_line_override_options() {
+        let cli = Cli::parse_from(["xkpass", "--order", "length", "--mutate", "2"]);
+        let settings = Settings::new(4, String::from(" "), "long".parse().unwrap(), Case::Lower);
+        let last = round_trip(&Last::new(&settings, &cli.output));
+
+        let mut output = Cli::parse_from(["xkpass", "--order", "alphabetical"]).output;
+        last.apply(&mut output).unwrap();
+        assert_eq!(output.order, Some(Order::Alphabetical));
+        assert_eq!(output.mutate, Some(2));
+    }
+
+    #[test]
+    fn reads_files_without_options() {
+        let last: Last =
+            toml::from_str("number = 3\nseparator = \"-\"\nlist = \"short2\"\ncase = \"upper\"\n")
+                .unwrap();
+        let mut output = Cli::parse_from(["xkpass"]).output;
+        last.apply(&mut output).unwrap();
+        assert!(output.order.is_none() && !output.rhyme && !output.mobile);
+    }
+}
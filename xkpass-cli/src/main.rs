@@ -0,0 +1,1677 @@
+use clap::{
+    builder::PossibleValuesParser, builder::TypedValueParser, CommandFactory, ErrorKind, Parser,
+    Subcommand,
+};
+use clap_complete::Shell;
+use clipboard::Selection;
+use config_file::ConfigFile;
+use preset::Preset;
+use rand::RngCore;
+use render::{Format, Generated, Renderer};
+use status::Failure;
+use std::{
+    cell::RefCell, fmt::Debug, fmt::Display, io, net::SocketAddr, path::PathBuf, str::FromStr,
+    time::Duration,
+};
+use word_lists::{ListName, Source};
+use xkpass::{Case, Config, List, Order, Password, VariantNames};
+
+mod access;
+mod accessible;
+mod anki;
+#[cfg(target_os = "linux")]
+mod ask;
+mod backup_codes;
+mod batch;
+mod bulk;
+mod check;
+mod chunked;
+mod clipboard;
+mod codec;
+mod config_file;
+mod confirm;
+mod core_dumps;
+mod corpus;
+#[cfg(any(unix, windows))]
+mod daemon;
+mod encrypt;
+mod env_vars;
+mod explain;
+mod fingerprint;
+mod inspect;
+mod kdf;
+mod keyring;
+mod languages;
+mod last;
+mod manifest;
+mod matching;
+mod memorability;
+mod menu;
+mod metrics;
+mod mobile;
+mod native_messaging;
+mod pair;
+mod paper;
+mod phonetic;
+mod preset;
+mod recipe;
+mod render;
+mod request;
+mod reuse;
+mod rhyme;
+mod rpc;
+mod separators;
+mod server;
+#[cfg(any(unix, windows))]
+mod service;
+mod shamir;
+mod sheet;
+#[cfg(unix)]
+mod ssh_keygen;
+mod status;
+mod symbols;
+mod totp;
+#[cfg(feature = "tui")]
+mod tui;
+mod typing;
+mod vault;
+mod word_lists;
+
+/// Options controlling the generated password, accepted by every subcommand.
+///
+/// They are optional so that the configuration file can provide the defaults.
+#[derive(clap::Args, Debug)]
+struct Args {
+    /// Number of words to include in the password, at most as many as the list has [default: 6]
+    #[clap(
+        short,
+        long,
+        global = true,
+        value_parser = clap::value_parser!(u64).range(1..).map(|n| n as usize),
+        env = "XKPASS_NUMBER"
+    )]
+    number: Option<usize>,
+
+    /// A separator to use between words, or digits for a random digit between each pair and digits:2 for two [default: " "]
+    #[clap(short, long, global = true, value_parser, env = "XKPASS_SEPARATOR")]
+    separator: Option<String>,
+
+    /// List of words to use for random password generation, including your own lists, or - to read words from stdin [default: long]
+    #[clap(short, long, global = true, value_parser = list_parser(), env = "XKPASS_LIST")]
+    list: Option<ListName>,
+
+    /// Case to use on the words [default: lower]
+    #[clap(short, long, global = true, value_parser = variant_parser::<Case>(), env = "XKPASS_CASE")]
+    case: Option<Case>,
+}
+
+impl Args {
+    /// Override `settings` with the options given on the command line.
+    fn apply(self, settings: Settings) -> Settings {
+        let Settings { config, list } = settings;
+        Settings::new(
+            self.number.unwrap_or(config.number),
+            self.separator.unwrap_or(config.separator),
+            self.list.unwrap_or(list),
+            self.case.unwrap_or(config.case),
+        )
+    }
+}
+
+/// Settings for the generated passwords.
+///
+/// The word list is kept by name, as the user's own lists have no place in [`Config`].
+#[derive(Debug)]
+pub struct Settings {
+    config: Config,
+    list: ListName,
+}
+
+impl Settings {
+    pub fn new(number: usize, separator: String, list: ListName, case: Case) -> Self {
+        let config = Config {
+            number,
+            separator,
+            list: match &list {
+                ListName::Embedded(list) => list.clone(),
+                ListName::Custom(_) | ListName::Stdin => List::Long,
+            },
+            case,
+        };
+
+        Self { config, list }
+    }
+
+    /// Generate a password, reading the user's word list if one was chosen,
+    /// and return it with the number of words it was picked from.
+    ///
+    /// Separators found in words of the list are refused unless `--force` is
+    /// given, words piped to stdin are never checked as they aren't kept.
+    ///
+    /// With `--match-regex`, only candidates matching it are kept, and with
+    /// `--easy-to-type` the password is the easiest to type on that layout
+    /// out of several of them. With `--rhyme` its words rhyme and
+    /// with `--order` they are put in that order, and the bits either costs
+    /// are returned as well, as are those each change of `--mutate` adds.
+    /// Every random choice is drawn from `rng`.
+    ///
+    /// Random digit separators are drawn last, spelled out with
+    /// `--digits-as-words`, followed by the symbol of `--embed-symbol` and the
+    /// bits it adds.
+    ///
+    /// Options that can't all be satisfied fail with [`status::Status::Unsatisfiable`].
+    fn generate(
+        &self,
+        output: &Output,
+        rng: &mut dyn RngCore,
+    ) -> Result<(Password, usize, Vec<explain::Row>), Failure> {
+        if output.digits_as_words && separators::digits(&self.config.separator).is_none() {
+            return Err(Failure::unsatisfiable(
+                "--digits-as-words has no digits to spell, add some with --separator digits",
+            ));
+        }
+        let symbols = match &output.embed_symbol {
+            Some(given) => {
+                Some(symbols::set(given, &self.config.separator).map_err(Failure::unsatisfiable)?)
+            }
+            None => None,
+        };
+        let rng = RefCell::new(rng);
+        let finish = |password: Password| {
+            let mut rng = rng.borrow_mut();
+            let mut password = match output.order {
+                Some(order) => password.reorder(order, &mut *rng),
+                None => password,
+            };
+            let mut mutations = Vec::new();
+            for _ in 0..output.mutate.unwrap_or(0) {
+                let before = password.entropy();
+                password = password.mutate(&mut *rng);
+                mutations.push(mutation_row(password.entropy() - before));
+            }
+            let separator = &self.config.separator;
+            let password =
+                separators::apply(password, separator, output.digits_as_words, &mut *rng);
+            let password = match &symbols {
+                Some(symbols) => password.embed_symbol(symbols, &mut *rng),
+                None => password,
+            };
+            (password, mutations)
+        };
+        let symbol_row = |password: &Password| {
+            let symbols = symbols.as_ref()?;
+            Some(symbols::row(password, symbols.len()))
+        };
+        let mut adjustments = Vec::new();
+        if let Some(order) = output.order {
+            let lost = order.lost_entropy(self.config.number);
+            if lost > 0.0 {
+                let choices = format!("{}, the same for every password", order);
+                adjustments.push(("order", choices, -lost));
+            }
+        }
+
+        if let ListName::Stdin = self.list {
+            let unsatisfiable = if output.easy_to_type.is_some() {
+                Some("--easy-to-type can't pick among words read from stdin")
+            } else if output.rhyme {
+                Some("--rhyme can't pick among words read from stdin")
+            } else if output.match_regex.is_some() {
+                Some("--match-regex can't try again with words read from stdin")
+            } else if !output.mix_languages.is_empty() {
+                Some("--mix-languages can't mix words read from stdin")
+            } else {
+                None
+            };
+            if let Some(message) = unsatisfiable {
+                return Err(Failure::unsatisfiable(message));
+            }
+            let (password, pool) = word_lists::generate_from_stdin(
+                &self.config,
+                output.mobile,
+                &mut **rng.borrow_mut(),
+            )?;
+            let (password, mutations) = finish(password);
+            adjustments.extend(mutations);
+            adjustments.extend(symbol_row(&password));
+            return Ok((password, pool, adjustments));
+        }
+
+        let source = self.list.open().map_err(|err| err.to_string())?;
+        let others = languages::others(&self.list, &output.mix_languages);
+        let other_sources = others
+            .iter()
+            .map(|name| match name {
+                ListName::Stdin => Err(Failure::unsatisfiable(
+                    "--mix-languages can't mix words read from stdin",
+                )),
+                _ => name.open().map_err(|err| Failure::from(err.to_string())),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let words = self.gather_words(&source, output, output.rhyme || !others.is_empty());
+        let listed = words.as_ref().map_or(source.len(), Vec::len);
+        let mix = match &words {
+            Some(words) if !others.is_empty() => {
+                let mut lists = vec![words.clone()];
+                for (source, name) in other_sources.iter().zip(&others) {
+                    lists.push(self.other_words(source, name, output));
+                }
+                Some(
+                    languages::Mix::new(&lists, self.config.number)
+                        .map_err(Failure::unsatisfiable)?,
+                )
+            }
+            _ => None,
+        };
+        let pool = mix.as_ref().map_or(listed, |mix| mix.words().len());
+        let rhymes = match &words {
+            Some(words) if output.rhyme => Some(
+                rhyme::Rhymes::new(words, self.config.number).map_err(Failure::unsatisfiable)?,
+            ),
+            _ => None,
+        };
+        let generate = || {
+            let password = {
+                let mut rng = rng.borrow_mut();
+                match (&rhymes, &mix, &words) {
+                    (Some(rhymes), _, _) => {
+                        let words = rhymes.pick(&mut *rng);
+                        xkpass::generate_password_with(words, &self.config, &mut *rng)
+                    }
+                    (None, Some(mix), _) => {
+                        let mixed = mix.pick(self.config.number, &mut *rng)?;
+                        xkpass::generate_password_with(&mixed, &self.config, &mut *rng)
+                    }
+                    (None, None, Some(words)) => {
+                        xkpass::generate_password_with(words, &self.config, &mut *rng)
+                    }
+                    (None, None, None) => xkpass::generate_password_indexed(
+                        source.len(),
+                        |index| source.word(index),
+                        &self.config,
+                        &mut *rng,
+                    ),
+                }
+            };
+            password.map(finish).map_err(|err| err.to_string())
+        };
+        let filter = match &output.match_regex {
+            Some(pattern) => Some(matching::Filter::new(pattern).map_err(Failure::unsatisfiable)?),
+            None => None,
+        };
+        // the settings were checked against the lists, so a candidate only
+        // fails when no password meets every constraint
+        let candidate = || {
+            match &filter {
+                Some(filter) => filter.first(generate),
+                None => generate(),
+            }
+            .map_err(Failure::unsatisfiable)
+        };
+        let (password, mutations) = match output.easy_to_type {
+            None => candidate()?,
+            Some(layout) => typing::easiest(
+                (0..typing::CANDIDATES)
+                    .map(|_| candidate())
+                    .collect::<Result<_, _>>()?,
+                layout,
+            ),
+        };
+        adjustments.extend(output.easy_to_type.map(typing::row));
+        adjustments.extend(
+            rhymes
+                .iter()
+                .map(|rhymes| rhymes.row(listed, self.config.number)),
+        );
+        if let Some(mix) = &mix {
+            let names = languages::names(&self.list, &others);
+            adjustments.push(mix.row(&names, self.config.number));
+        }
+        adjustments.extend(mutations);
+        adjustments.extend(symbol_row(&password));
+        adjustments.extend(filter.as_ref().map(matching::Filter::row));
+        Ok((password, pool, adjustments))
+    }
+
+    /// The words of `source` to generate passwords from, checked against the
+    /// settings and left with only those typable on a phone with `--mobile`,
+    /// if they must be gathered for `--mobile` or because `gather` is set.
+    ///
+    /// Otherwise the list is only checked and nothing is returned: the words
+    /// picked are then looked up in `source`, however large the list is.
+    fn gather_words<'a>(
+        &self,
+        source: &'a Source,
+        output: &Output,
+        gather: bool,
+    ) -> Option<Vec<&'a str>> {
+        if !gather && !output.mobile {
+            self.check_separator(source.iter(), &self.list, output.force);
+            self.check_number(source.len());
+            return None;
+        }
+        let words = self.other_words(source, &self.list, output);
+        self.check_number(words.len());
+        Some(words)
+    }
+
+    /// The words of `source`, the list `name` mixed with the chosen one by
+    /// `--mix-languages`, checked against the separator and left with only
+    /// those typable on a phone with `--mobile`.
+    fn other_words<'a>(
+        &self,
+        source: &'a Source,
+        name: &ListName,
+        output: &Output,
+    ) -> Vec<&'a str> {
+        let mut words = source.words();
+        if output.mobile {
+            words.retain(|word| mobile::word(word));
+        }
+        self.check_separator(words.iter().copied(), name, output.force);
+        words
+    }
+
+    /// Exit with a usage error if the list of `pool` words is too small for
+    /// passwords of the requested number of distinct words.
+    fn check_number(&self, pool: usize) {
+        if self.config.number > pool {
+            let message = format!(
+                "--number {} is more than the {} words of the {} list",
+                self.config.number, pool, self.list
+            );
+            Cli::command()
+                .error(ErrorKind::ValueValidation, message)
+                .exit();
+        }
+    }
+
+    /// Exit with an error if the separator appears inside a word of `words`,
+    /// the list `name`, or digits do for random digit separators, unless
+    /// `force` is set.
+    fn check_separator<'a>(
+        &self,
+        words: impl IntoIterator<Item = &'a str>,
+        name: &ListName,
+        force: bool,
+    ) {
+        if force {
+            return;
+        }
+        let ambiguous = match separators::digits(&self.config.separator) {
+            Some(_) => words
+                .into_iter()
+                .find(|word| word.contains(|c: char| c.is_ascii_digit())),
+            None => ambiguous_word(words, &self.config.separator),
+        };
+        if let Some(word) = ambiguous {
+            fail(format!(
+                "the separator {:?} appears in words of the {} list such as {:?}, \
+                 so the words of the password can't be told apart, use --force to keep it anyway",
+                self.config.separator, name, word
+            ));
+        }
+    }
+}
+
+/// The row of `--explain` for a change of `--mutate` that added `bits`.
+fn mutation_row(bits: f64) -> explain::Row {
+    let choices = format!("one of {:.0} small changes to the words", bits.exp2());
+    ("mutation", choices, bits)
+}
+
+/// A word of `words` containing `separator` in any case, which would make the
+/// boundaries between the words of a password ambiguous.
+fn ambiguous_word<'a>(
+    words: impl IntoIterator<Item = &'a str>,
+    separator: &str,
+) -> Option<&'a str> {
+    if separator.is_empty() {
+        return None;
+    }
+
+    let separator = separator.to_lowercase();
+    words
+        .into_iter()
+        .find(|word| word.to_lowercase().contains(&separator))
+}
+
+/// Parse one of the variant names of `T`, listing them in `--help`.
+fn variant_parser<T>() -> impl TypedValueParser<Value = T>
+where
+    T: VariantNames + FromStr + Clone + Send + Sync + 'static,
+    T::Err: Debug,
+{
+    // the possible values have been checked already, so parsing can't fail
+    PossibleValuesParser::new(T::VARIANTS.iter().copied()).map(|name| name.parse().unwrap())
+}
+
+/// Parse the name of an embedded word list or one of the user's, listing them
+/// in `--help` and in the completion scripts.
+fn list_parser() -> impl TypedValueParser<Value = ListName> {
+    // clap wants names that live as long as the program, which they do anyway
+    let custom = word_lists::custom_names()
+        .into_iter()
+        .map(|name| &*name.leak());
+    let names: Vec<&'static str> = List::VARIANTS
+        .iter()
+        .copied()
+        .chain(custom)
+        .chain(["-"])
+        .collect();
+
+    PossibleValuesParser::new(names).map(|name| name.parse().unwrap())
+}
+
+/// Command line interface: password options plus an optional subcommand,
+/// `generate` when none is given.
+#[derive(Parser, Debug)]
+#[clap(
+    name = "xkpass",
+    version,
+    about,
+    long_about = None,
+    args_conflicts_with_subcommands = true
+)]
+struct Cli {
+    #[clap(flatten)]
+    args: Args,
+
+    /// Ignore the configuration file
+    #[clap(long, global = true, env = "XKPASS_NO_CONFIG")]
+    no_config: bool,
+
+    /// Keep passwords out of core dumps and crash reports, should xkpass crash
+    #[clap(long, global = true, env = "XKPASS_NO_CORE_DUMPS")]
+    no_core_dumps: bool,
+
+    /// Don't pick the default word list after the locale
+    #[clap(long, global = true, env = "XKPASS_NO_LOCALE")]
+    no_locale: bool,
+
+    /// Use the settings of the [profiles.<PROFILE>] section of the configuration file
+    #[clap(
+        long,
+        global = true,
+        value_parser,
+        env = "XKPASS_PROFILE",
+        conflicts_with = "no-config"
+    )]
+    profile: Option<String>,
+
+    /// Use the settings of the last password generated, which are kept without the password
+    #[clap(long, global = true, conflicts_with = "profile")]
+    last: bool,
+
+    /// Use the settings of a preset instead of the configuration file, and refuse passwords weaker than it asks for
+    #[clap(long, global = true, value_enum, conflicts_with_all = &["profile", "last"])]
+    preset: Option<Preset>,
+
+    /// Format of what generate, entropy, inspect, dump, words and check print
+    #[clap(
+        long,
+        global = true,
+        value_enum,
+        default_value_t = Format::Plain,
+        env = "XKPASS_FORMAT"
+    )]
+    format: Format,
+
+    /// Selection that --copy, menu and tui copy to on Wayland and X11
+    #[clap(
+        long,
+        global = true,
+        value_enum,
+        default_value_t = Selection::Clipboard,
+        env = "XKPASS_SELECTION"
+    )]
+    selection: Selection,
+
+    #[clap(flatten)]
+    output: Output,
+
+    #[clap(subcommand)]
+    command: Option<Command>,
+}
+
+/// Where the generated password goes, stdout by default.
+#[derive(clap::Args, Debug)]
+struct Output {
+    /// Only print the password encrypted for this age recipient or GnuPG key (can be repeated)
+    #[clap(long, value_parser, value_name = "RECIPIENT")]
+    encrypt_to: Vec<String>,
+
+    /// Don't print the password, cache it in the kernel keyring for `systemd-ask-password --accept-cached --keyname=KEYNAME`
+    #[cfg(target_os = "linux")]
+    #[clap(
+        long,
+        value_parser,
+        value_name = "KEYNAME",
+        conflicts_with_all = &["encrypt-to", "count", "confirm", "reveal", "chunked", "phonetic", "accessible", "copy", "derive-key", "paper"]
+    )]
+    ask: Option<String>,
+
+    /// Number of passwords to print, one per line, generated in parallel
+    #[clap(
+        long,
+        value_parser = clap::value_parser!(u64).range(1..),
+        default_value_t = 1,
+        conflicts_with = "encrypt-to"
+    )]
+    count: u64,
+
+    /// Show the password until a key is pressed or SECONDS have passed, then erase it from the terminal
+    #[clap(
+        long,
+        value_parser,
+        value_name = "SECONDS",
+        min_values = 0,
+        require_equals = true,
+        default_missing_value = "30",
+        conflicts_with_all = &["encrypt-to", "count"]
+    )]
+    confirm: Option<u64>,
+
+    /// Show the password for DURATION, such as 10s or 2m, under a countdown, then erase it and the scrollback
+    #[clap(
+        long,
+        value_parser = confirm::parse_duration,
+        value_name = "DURATION",
+        conflicts_with_all = &["encrypt-to", "count", "confirm"]
+    )]
+    reveal: Option<Duration>,
+
+    /// Lay the password out in blocks of SIZE characters, for copying it by hand
+    #[clap(
+        long,
+        value_name = "SIZE",
+        value_parser = clap::value_parser!(u64).range(3..).map(|n| n as usize),
+        conflicts_with_all = &["encrypt-to", "count"]
+    )]
+    chunked: Option<usize>,
+
+    /// Also print each character of the password in the NATO phonetic alphabet beneath it, for reading it over the phone
+    #[clap(long, conflicts_with_all = &["encrypt-to", "count"])]
+    phonetic: bool,
+
+    /// Print each word and separator of the password on a line of its own, announcing capitals and naming symbols, for screen readers
+    #[clap(
+        long,
+        conflicts_with_all = &["encrypt-to", "count", "confirm", "chunked", "phonetic", "fingerprint"]
+    )]
+    accessible: bool,
+
+    /// Print a page to keep the password on paper: in blocks of --chunked SIZE characters, as a QR code and as a fingerprint
+    #[clap(
+        long,
+        conflicts_with_all = &["encrypt-to", "count", "confirm", "reveal", "copy", "phonetic", "accessible", "derive-key"]
+    )]
+    paper: bool,
+
+    /// Leave the password itself out of the page of --paper, to only have it in the QR code
+    #[clap(long, requires = "paper")]
+    qr_only: bool,
+
+    /// Don't print the password, copy it to the clipboard and confirm on stderr
+    #[clap(
+        long,
+        conflicts_with_all = &["encrypt-to", "count", "confirm", "reveal", "chunked", "phonetic", "accessible"]
+    )]
+    copy: bool,
+
+    /// With --copy, don't confirm either, leaving only the exit status
+    #[clap(long, requires = "copy")]
+    quiet: bool,
+
+    /// Also print where the entropy of the password comes from, on stderr
+    #[clap(long)]
+    explain: bool,
+
+    /// Refuse to print a password with fewer than BITS bits of entropy, exiting with status 3
+    #[clap(long, value_parser, value_name = "BITS")]
+    min_entropy: Option<f64>,
+
+    /// Use the separator even if it appears inside words of the list
+    #[clap(long)]
+    force: bool,
+
+    /// Pick the easiest to type of several candidates on this keyboard layout, at the cost of a few bits of entropy
+    #[clap(
+        long,
+        value_enum,
+        value_name = "LAYOUT",
+        min_values = 0,
+        require_equals = true,
+        default_missing_value = "qwerty",
+        conflicts_with = "count"
+    )]
+    easy_to_type: Option<typing::Layout>,
+
+    /// Also print art drawn from a hash of the password on stderr, to later check it was typed the same elsewhere
+    #[clap(long, conflicts_with = "count")]
+    fingerprint: bool,
+
+    /// Also write a key derived from the password with Argon2id to this new file, for a disk encryption keyfile
+    #[clap(long, value_parser, value_name = "PATH", conflicts_with = "count")]
+    keyfile: Option<PathBuf>,
+
+    /// Also print a key of BYTES bytes derived from the password, to encrypt with it directly
+    #[clap(
+        long,
+        value_name = "BYTES",
+        value_parser = clap::value_parser!(u64).range(16..=1024).map(|n| n as usize),
+        conflicts_with_all = &["encrypt-to", "count", "confirm", "reveal", "copy"]
+    )]
+    derive_key: Option<usize>,
+
+    /// Key derivation function of --derive-key, with 64 MiB and 3 passes [default: argon2id]
+    #[clap(long, value_enum, requires = "derive-key")]
+    kdf: Option<kdf::Kdf>,
+
+    /// Salt of --derive-key in hex digits, to derive a key again [default: a random one]
+    #[clap(long, value_parser = kdf::parse_salt, value_name = "HEX", requires = "derive-key")]
+    salt: Option<kdf::Salt>,
+
+    /// Encoding of the key of --derive-key [default: hex]
+    #[clap(long, value_enum, value_name = "ENCODING", requires = "derive-key")]
+    key_encoding: Option<kdf::Encoding>,
+
+    /// Name of the Secret of --format k8s-secret
+    #[clap(long, value_parser = manifest::parse_name, value_name = "NAME")]
+    name: Option<String>,
+
+    /// Key of the password in the Secret of --format k8s-secret [default: password]
+    #[clap(long, value_parser = manifest::parse_key, value_name = "KEY")]
+    key: Option<String>,
+
+    /// Name of the variable of --format dotenv and sh [default: PASSWORD]
+    #[clap(long, value_parser = env_vars::parse_name, value_name = "NAME")]
+    var_name: Option<String>,
+
+    /// Also write an Anki deck to learn the password by heart to this CSV file
+    #[clap(long, value_parser, value_name = "PATH", conflicts_with = "count")]
+    anki: Option<PathBuf>,
+
+    /// Order to put the words in, sorting them costs the bits of their random order [default: shuffle]
+    #[clap(long, value_parser = variant_parser::<Order>(), conflicts_with = "count")]
+    order: Option<Order>,
+
+    /// Insert a random symbol inside a random word, never at its edges, out of SET or !#$%&*+=?@^~
+    #[clap(
+        long,
+        value_parser,
+        value_name = "SET",
+        min_values = 0,
+        require_equals = true,
+        default_missing_value = symbols::SYMBOLS,
+        conflicts_with = "count"
+    )]
+    embed_symbol: Option<String>,
+
+    /// Make N small random changes to the words, swapping two letters, doubling one or reversing a word
+    #[clap(
+        long,
+        value_name = "N",
+        value_parser = clap::value_parser!(u64).range(1..).map(|n| n as usize),
+        conflicts_with = "count"
+    )]
+    mutate: Option<usize>,
+
+    /// Spell the random digits out as words, such as forty-two, to keep the password alphabetic
+    #[clap(long, conflicts_with = "count")]
+    digits_as_words: bool,
+
+    /// Generate candidates until one matches REGEX, for site rules no other option covers
+    #[clap(long, value_parser, value_name = "REGEX", conflicts_with = "count")]
+    match_regex: Option<String>,
+
+    /// Mix the words of these other lists with those of --list, at least one of each list, such as fr,de
+    #[clap(
+        long,
+        value_parser = list_parser(),
+        value_name = "LIST",
+        use_value_delimiter = true,
+        value_delimiter = ',',
+        conflicts_with_all = &["count", "rhyme"]
+    )]
+    mix_languages: Vec<ListName>,
+
+    /// Only pick words that rhyme with each other, at the cost of many bits of entropy
+    #[clap(long, conflicts_with = "count")]
+    rhyme: bool,
+
+    /// Keep to what phone keyboards show without switching layers: lowercase words of letters, separated by spaces
+    #[clap(long)]
+    mobile: bool,
+
+    /// Generate the password as RECIPE says, as printed with the password by --format json, instead of after the other options
+    #[clap(
+        long,
+        value_parser,
+        value_name = "RECIPE",
+        conflicts_with_all = &[
+            "number", "separator", "list", "case", "profile", "last", "count", "order", "mutate",
+            "embed-symbol", "digits-as-words", "match-regex", "mix-languages", "rhyme", "mobile",
+            "easy-to-type",
+        ]
+    )]
+    recipe: Option<String>,
+
+    /// Draw every random choice from a generator seeded with the SHA-256 of this file of at least 256 bits of entropy, such as dice rolls, to generate the same password again offline
+    #[clap(
+        long,
+        value_parser,
+        value_name = "PATH",
+        conflicts_with_all = &["recipe", "count"]
+    )]
+    seed_file: Option<PathBuf>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Generate a password, what xkpass does without a subcommand
+    Generate(Box<Output>),
+    /// Print a password for every line of settings read on stdin, such as `number=4 list=short1`, or answer JSON requests with --format ndjson
+    Batch,
+    /// Stream passwords as fast as possible, for testing strength meters and crackers
+    Corpus {
+        /// Number of passwords to print
+        #[clap(long, value_parser)]
+        count: u64,
+    },
+    /// Read a passphrase on stdin and estimate its strength, if its words come from a known list
+    Check,
+    /// Read a password on stdin and print its fingerprint, to compare with the one shown by --fingerprint
+    Fingerprint,
+    /// Print a username and a password that share no words, for signing up to an account
+    Pair {
+        /// Print them as a JSON object instead of on two lines
+        #[clap(long)]
+        json: bool,
+    },
+    /// Split a password into shares, any THRESHOLD of which put it back together with `combine`
+    Split {
+        /// Number of shares
+        #[clap(long, value_parser = clap::value_parser!(u8).range(2..))]
+        shares: u8,
+        /// Number of shares needed to put the password back together
+        #[clap(long, value_parser = clap::value_parser!(u8).range(2..))]
+        threshold: u8,
+        /// Split a password read on stdin instead of generating one
+        #[clap(long)]
+        read: bool,
+    },
+    /// Generate a password that isn't one of those exported from a password manager to a CSV file
+    CheckReuse {
+        /// CSV export of a password manager, with a header naming the password column
+        #[clap(long, value_parser, value_name = "PATH")]
+        against: PathBuf,
+    },
+    /// Read shares made by `split` on stdin, one per line, and print the password they put together
+    Combine,
+    /// Write bytes read on stdin as words that can be read aloud and typed back in
+    Encode {
+        /// Read hex digits instead of raw bytes, such as a key or a UUID
+        #[clap(long)]
+        hex: bool,
+    },
+    /// Read words written by `encode` on stdin and print the bytes they stand for
+    Decode {
+        /// Print hex digits instead of raw bytes
+        #[clap(long)]
+        hex: bool,
+    },
+    /// Show the size of the word lists and the bits of entropy each word is worth
+    Inspect {
+        /// Word list to show [default: every list]
+        #[clap(value_parser = list_parser(), value_name = "LIST")]
+        name: Option<ListName>,
+    },
+    /// Print every word of a list, one per line
+    Dump {
+        /// Word list to print
+        #[clap(value_parser = list_parser(), value_name = "LIST")]
+        name: ListName,
+    },
+    /// Estimate the entropy and crack time of passwords with the given options, without generating any
+    Entropy {
+        /// Number of random digits to count on top of the words
+        #[clap(long, value_parser, default_value_t = 0)]
+        digits: usize,
+    },
+    /// Print random words of the list, one per line, as they are in the list
+    Words {
+        /// Number of distinct words to print
+        #[clap(long, value_parser = clap::value_parser!(u64).range(1..).map(|n| n as usize), default_value_t = 10)]
+        count: usize,
+    },
+    /// Serve a JSON API for password generation over HTTP
+    Serve {
+        /// Address to listen on
+        #[clap(
+            long,
+            value_parser,
+            default_value = "127.0.0.1:8732",
+            env = "XKPASS_LISTEN"
+        )]
+        listen: SocketAddr,
+        /// File holding the bearer token clients must send to /generate
+        #[clap(long, value_parser, value_name = "PATH", env = "XKPASS_TOKEN_FILE")]
+        token_file: Option<PathBuf>,
+        /// Bearer token clients must send to /generate, better set in the environment than in argv
+        #[clap(
+            long,
+            value_parser,
+            env = "XKPASS_TOKEN",
+            hide_env_values = true,
+            conflicts_with = "token-file"
+        )]
+        token: Option<String>,
+        /// Requests a minute each client address may make to /generate
+        #[clap(long, value_parser = clap::value_parser!(u32).range(1..), value_name = "N", env = "XKPASS_RATE_LIMIT")]
+        rate_limit: Option<u32>,
+    },
+    /// Answer generation requests on a Unix socket or a named pipe, without starting a process for each
+    #[cfg(any(unix, windows))]
+    Daemon {
+        /// Path of the socket [default: $XDG_RUNTIME_DIR/xkpass.sock, $TMPDIR/xkpass.sock on macOS, \\.\pipe\xkpass on Windows]
+        #[clap(long, value_parser, env = "XKPASS_SOCKET")]
+        socket: Option<PathBuf>,
+        /// Register the daemon as a launchd agent on macOS or a service on Windows, and start it
+        #[clap(long, conflicts_with = "uninstall")]
+        install: bool,
+        /// Stop the daemon and unregister it from launchd or the services of Windows
+        #[clap(long)]
+        uninstall: bool,
+        /// Log to the system log, as the daemon does when started by a service manager
+        #[clap(long, hide = true, conflicts_with_all = &["install", "uninstall"])]
+        service: bool,
+    },
+    /// Speak line-delimited JSON-RPC on stdin and stdout
+    Rpc,
+    /// Print candidates for a menu like rofi, dmenu or fzf, read the selection back on stdin and copy it to the clipboard
+    Menu {
+        /// Number of candidates to print
+        #[clap(long, value_parser, default_value_t = 10, env = "XKPASS_CANDIDATES")]
+        candidates: usize,
+    },
+    /// Act as a native messaging host for a browser extension
+    NativeMessaging,
+    /// Keep generated passwords in the system keychain, behind Touch ID or Windows Hello
+    Keyring {
+        #[clap(subcommand)]
+        command: KeyringCommand,
+    },
+    /// Change the settings and reroll words in a full-screen interface
+    #[cfg(feature = "tui")]
+    Tui,
+    /// Run ssh-keygen with a generated passphrase, handed to it without argv, then print the passphrase
+    #[cfg(unix)]
+    SshKeygen {
+        /// Copy the passphrase to the clipboard instead of printing it
+        #[clap(long)]
+        copy: bool,
+        /// Arguments of ssh-keygen, after --, such as `-- -t ed25519 -f ~/.ssh/id_work`
+        #[clap(last = true, value_parser)]
+        args: Vec<String>,
+    },
+    /// Generate a TOTP secret for two-factor authentication and print its otpauth:// URI and QR code
+    Totp {
+        /// Service the account is on, shown by authenticator apps
+        #[clap(long, value_parser)]
+        issuer: String,
+        /// Name of the account, such as an email address
+        #[clap(long, value_parser)]
+        account: String,
+        /// Then ask for the code the app shows, to check it was set up right
+        #[clap(long)]
+        verify: bool,
+    },
+    /// Print a numbered sheet of backup codes made of short words, to print and keep in a safe
+    BackupCodes {
+        /// Number of codes
+        #[clap(long, value_parser = clap::value_parser!(u64).range(1..=1000).map(|n| n as usize), default_value_t = 10)]
+        sets: usize,
+        /// Number of words of each code
+        #[clap(long, value_parser = clap::value_parser!(u64).range(2..=8).map(|n| n as usize), default_value_t = 3)]
+        words: usize,
+        /// Read a code on stdin and check it against its check character instead
+        #[clap(long, conflicts_with_all = &["sets", "words"])]
+        check: bool,
+    },
+    /// Keep generated passwords in a file encrypted with a passphrase
+    #[clap(subcommand)]
+    Vault(VaultCommand),
+    /// Print a completion script for a shell
+    Completions {
+        #[clap(value_parser)]
+        shell: Shell,
+    },
+    /// Hold the text read on stdin in the selection until something else is copied, for --copy
+    #[clap(hide = true)]
+    HoldClipboard,
+}
+
+#[derive(Subcommand, Debug)]
+enum KeyringCommand {
+    /// Generate a password, keep it under LABEL and print it
+    Add {
+        /// Name to find the password under later
+        #[clap(value_parser)]
+        label: String,
+    },
+    /// Print the password kept under LABEL once Touch ID or Windows Hello recognizes you
+    Show {
+        #[clap(value_parser)]
+        label: String,
+        /// Copy the password to the clipboard instead of printing it
+        #[clap(long)]
+        copy: bool,
+    },
+    /// Forget the password kept under LABEL
+    Remove {
+        #[clap(value_parser)]
+        label: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum VaultCommand {
+    /// Generate a password, keep it in the vault under LABEL and print it
+    Add {
+        #[clap(value_parser)]
+        label: String,
+        /// Address of the site the password is for, which find searches too
+        #[clap(long, value_parser)]
+        url: Option<String>,
+    },
+    /// Print the password kept in the vault under LABEL, or the only one matching it
+    Show {
+        #[clap(value_parser)]
+        label: String,
+        /// Copy the password to the clipboard instead of printing it
+        #[clap(long)]
+        copy: bool,
+    },
+    /// Print the labels of the vault, with their address
+    List,
+    /// Print the labels matching QUERY, letters of which may be left out, best first
+    Find {
+        #[clap(value_parser)]
+        query: String,
+    },
+    /// Open the vault with Touch ID on macOS or Windows Hello on Windows instead of its passphrase
+    Biometric {
+        /// Only open the vault with its passphrase again
+        #[clap(long)]
+        off: bool,
+    },
+}
+
+fn main() {
+    // started by ssh-keygen to ask for the passphrase, with the prompt as the only argument
+    #[cfg(unix)]
+    if let Some(socket) = std::env::var_os(ssh_keygen::SOCKET) {
+        return exit_on_error(ssh_keygen::askpass(&socket));
+    }
+    let cli = Cli::parse();
+    if cli.no_core_dumps {
+        exit_on_error(core_dumps::disable());
+    }
+    let command = cli
+        .command
+        .unwrap_or(Command::Generate(Box::new(cli.output)));
+    if let Command::Completions { shell } = command {
+        clap_complete::generate(shell, &mut Cli::command(), "xkpass", &mut io::stdout());
+        return;
+    }
+    if let Command::HoldClipboard = command {
+        return exit_on_error(clipboard::hold(cli.selection));
+    }
+
+    // the other modes only know the embedded lists
+    let locale = !cli.no_locale && matches!(command, Command::Generate(_));
+    let last = cli
+        .last
+        .then(|| last::load().unwrap_or_else(|err| fail(err)));
+    let settings = if let Some(preset) = cli.preset {
+        preset.settings()
+    } else if let Some(last) = &last {
+        last.settings().unwrap_or_else(|err| fail(err))
+    } else {
+        load_settings(cli.no_config, cli.profile.as_deref(), locale)
+    };
+    let settings = cli.args.apply(settings);
+
+    if cli.format.single_password() && !matches!(command, Command::Generate(_)) {
+        let message = format!(
+            "--format {} only applies to generating a password",
+            cli.format.name()
+        );
+        Cli::command()
+            .error(ErrorKind::ArgumentConflict, message)
+            .exit();
+    }
+    match command {
+        Command::Generate(mut output) => {
+            if let Some(preset) = cli.preset {
+                let min = output
+                    .min_entropy
+                    .map_or(preset.min_entropy(), |min| min.max(preset.min_entropy()));
+                output.min_entropy = Some(min);
+            }
+            if let Some(last) = last {
+                last.apply(&mut output).unwrap_or_else(|err| fail(err));
+            }
+            generate(settings, *output, cli.format, cli.selection);
+        }
+        Command::Batch => {
+            batch::run(embedded_only(settings), cli.format).unwrap_or_else(|failure| failure.exit())
+        }
+        Command::Corpus { count } => exit_on_error(corpus::run(embedded_only(settings), count)),
+        Command::Check => exit_on_error(check::run(&settings.config.separator, cli.format)),
+        Command::Fingerprint => exit_on_error(fingerprint::run()),
+        Command::Pair { json } => exit_on_error(pair::run(embedded_only(settings), json)),
+        Command::Split {
+            shares,
+            threshold,
+            read,
+        } => {
+            if threshold > shares {
+                let message = format!(
+                    "--threshold {} is more than the {} shares",
+                    threshold, shares
+                );
+                Cli::command()
+                    .error(ErrorKind::ValueValidation, message)
+                    .exit();
+            }
+            let config = (!read).then(|| embedded_only(settings));
+            exit_on_error(shamir::run_split(config, shares, threshold))
+        }
+        Command::Combine => exit_on_error(shamir::run_combine()),
+        Command::CheckReuse { against } => {
+            exit_on_error(reuse::run(embedded_only(settings), &against))
+        }
+        Command::Vault(VaultCommand::Add { label, url }) => exit_on_error(vault::run_add(
+            embedded_only(settings),
+            &label,
+            url.as_deref(),
+        )),
+        Command::Vault(VaultCommand::Show { label, copy }) => {
+            exit_on_error(vault::run_show(&label, copy.then_some(cli.selection)))
+        }
+        #[cfg(unix)]
+        Command::SshKeygen { copy, args } => exit_on_error(ssh_keygen::run(
+            embedded_only(settings),
+            &args,
+            copy.then_some(cli.selection),
+        )),
+        Command::Totp {
+            issuer,
+            account,
+            verify,
+        } => exit_on_error(totp::run(&issuer, &account, verify)),
+        Command::BackupCodes { check: true, .. } => exit_on_error(backup_codes::check_stdin()),
+        Command::BackupCodes { sets, words, .. } => {
+            exit_on_error(backup_codes::run(sets, words, cli.format))
+        }
+        Command::Vault(VaultCommand::List) => exit_on_error(vault::run_list()),
+        Command::Vault(VaultCommand::Find { query }) => exit_on_error(vault::run_find(&query)),
+        Command::Vault(VaultCommand::Biometric { off }) => exit_on_error(vault::run_biometric(off)),
+        Command::Encode { hex } => exit_on_error(codec::encode(hex)),
+        Command::Decode { hex } => exit_on_error(codec::decode(hex)),
+        Command::Inspect { name } => exit_on_error(inspect::inspect(name, cli.format)),
+        Command::Dump { name } => exit_on_error(inspect::dump(&name, cli.format)),
+        Command::Entropy { digits } => exit_on_error(estimate(&settings, digits, cli.format)),
+        Command::Words { count } => {
+            exit_on_error(inspect::sample(&settings.list, count, cli.format))
+        }
+        Command::Serve {
+            listen,
+            token_file,
+            token,
+            rate_limit,
+        } => {
+            let token = match token_file {
+                Some(path) => Some(
+                    access::Access::read_token(&path)
+                        .unwrap_or_else(|err| fail(format!("{}: {}", path.display(), err))),
+                ),
+                None => token,
+            };
+            let access = access::Access::new(token.as_deref(), rate_limit);
+            exit_on_error(server::serve(listen, embedded_only(settings), access))
+        }
+        #[cfg(any(unix, windows))]
+        Command::Daemon {
+            socket,
+            install,
+            uninstall,
+            service,
+        } => {
+            if uninstall {
+                return exit_on_error(crate::service::uninstall());
+            }
+            let socket = match socket.or_else(daemon::default_socket_path) {
+                Some(socket) => socket,
+                None => fail("$XDG_RUNTIME_DIR is not set, pass --socket explicitly"),
+            };
+            if install {
+                exit_on_error(crate::service::install(&socket));
+            } else if service {
+                exit_on_error(crate::service::run(&socket, embedded_only(settings)));
+            } else {
+                exit_on_error(daemon::run(&socket, embedded_only(settings)));
+            }
+        }
+        Command::Rpc => exit_on_error(rpc::run(embedded_only(settings))),
+        Command::Menu { candidates } => exit_on_error(menu::run(
+            embedded_only(settings),
+            candidates,
+            cli.selection,
+        )),
+        Command::NativeMessaging => exit_on_error(native_messaging::run(embedded_only(settings))),
+        Command::Keyring { command } => exit_on_error(match command {
+            KeyringCommand::Add { label } => keyring::run_add(&label, embedded_only(settings)),
+            KeyringCommand::Show { label, copy } => keyring::run_show(&label, copy, cli.selection),
+            KeyringCommand::Remove { label } => keyring::run_remove(&label),
+        }),
+        #[cfg(feature = "tui")]
+        Command::Tui => exit_on_error(tui::run(embedded_only(settings), cli.selection)),
+        Command::Completions { .. } | Command::HoldClipboard => {
+            unreachable!("handled before reading the settings")
+        }
+    }
+}
+
+/// Generate a password and hand it to its destination, printing it in `format`
+/// along with its recipe in the formats other than plain text.
+fn generate(settings: Settings, mut output: Output, format: Format, selection: Selection) {
+    #[cfg(target_os = "linux")]
+    let asked = output.ask.is_some();
+    #[cfg(not(target_os = "linux"))]
+    let asked = false;
+    if format != Format::Plain
+        && (asked
+            || output.copy
+            || output.paper
+            || output.confirm.is_some()
+            || output.reveal.is_some()
+            || !output.encrypt_to.is_empty())
+    {
+        Cli::command()
+            .error(
+                ErrorKind::ArgumentConflict,
+                "--format only applies to passwords printed as they are, not with --encrypt-to, --confirm, --reveal, --copy, --paper or --ask",
+            )
+            .exit();
+    }
+    if format.single_password() && (output.count > 1 || output.derive_key.is_some()) {
+        let message = format!(
+            "--format {} holds a single password, not with --count or --derive-key",
+            format.name()
+        );
+        Cli::command()
+            .error(ErrorKind::ArgumentConflict, message)
+            .exit();
+    }
+    if format == Format::K8sSecret && output.name.is_none() {
+        Cli::command()
+            .error(
+                ErrorKind::MissingRequiredArgument,
+                "--format k8s-secret needs the --name of the Secret",
+            )
+            .exit();
+    }
+    if format != Format::K8sSecret && (output.name.is_some() || output.key.is_some()) {
+        Cli::command()
+            .error(
+                ErrorKind::ArgumentConflict,
+                "--name and --key only apply to --format k8s-secret",
+            )
+            .exit();
+    }
+    if !matches!(format, Format::Dotenv | Format::Sh) && output.var_name.is_some() {
+        Cli::command()
+            .error(
+                ErrorKind::ArgumentConflict,
+                "--var-name only applies to --format dotenv and sh",
+            )
+            .exit();
+    }
+    let (settings, seed) = match output.recipe.take() {
+        Some(given) => recipe::read(&given, &mut output)
+            .unwrap_or_else(|err| Failure::unsatisfiable(err).exit()),
+        None => (settings, None),
+    };
+    let seed = match &output.seed_file {
+        Some(path) => Some(
+            recipe::read_seed_file(path)
+                .unwrap_or_else(|err| fail(format!("{}: {}", path.display(), err))),
+        ),
+        None => seed,
+    };
+    let settings = if output.mobile {
+        mobile::settings(settings)
+    } else {
+        settings
+    };
+    let last = last::Last::new(&settings, &output);
+    deliver(settings, output, seed, format, selection);
+    // keeping the settings is a convenience, failing to doesn't fail the password
+    let _ = last.save();
+}
+
+/// Generate the passwords of `settings` and `output`, once a recipe and
+/// `--mobile` have been applied, and send them where `output` asks.
+fn deliver(
+    settings: Settings,
+    output: Output,
+    seed: Option<[u8; recipe::SEED_LEN]>,
+    format: Format,
+    selection: Selection,
+) {
+    let recipe = if format == Format::Plain {
+        String::new()
+    } else {
+        recipe::write(&settings, &output, seed.as_ref()).unwrap_or_else(|err| fail(err))
+    };
+    if output.count > 1 {
+        let source = settings.list.open().unwrap_or_else(|err| fail(err));
+        let words = settings.gather_words(&source, &output, false);
+        let pool = words.as_ref().map_or(source.len(), Vec::len);
+        if let Some(min) = output.min_entropy {
+            check_entropy(&settings, pool, &[], min).unwrap_or_else(|failure| failure.exit());
+        }
+        let word = |index| match &words {
+            Some(words) => words[index],
+            None => source.word(index),
+        };
+        exit_on_error(bulk::print(
+            pool,
+            &word,
+            &settings.config,
+            output.count as usize,
+            format,
+            &recipe,
+        ));
+        if output.explain {
+            let list = settings.list.to_string();
+            eprint!("{}", explain(&settings, &list, pool, &[]));
+        }
+        return;
+    }
+
+    let mut rng: Box<dyn RngCore> = match seed {
+        Some(seed) => Box::new(recipe::rng(seed)),
+        None => Box::new(rand::thread_rng()),
+    };
+    let (xkcd_password, pool, adjustments) = settings
+        .generate(&output, &mut *rng)
+        .unwrap_or_else(|failure| failure.exit());
+    if output.explain {
+        let others = languages::others(&settings.list, &output.mix_languages);
+        let list = languages::names(&settings.list, &others);
+        eprint!("{}", explain(&settings, &list, pool, &adjustments));
+        let words = xkcd_password.words().iter().map(String::as_str);
+        eprintln!("The words have a {}.", memorability::score(words));
+    }
+    if let Some(min) = output.min_entropy {
+        check_entropy(&settings, pool, &adjustments, min).unwrap_or_else(|failure| failure.exit());
+    }
+    if let Some(layout) = output.easy_to_type {
+        eprintln!(
+            "picked the easiest to type on {} of {} candidates, which costs at most {} bits of entropy",
+            layout,
+            typing::CANDIDATES,
+            typing::CANDIDATES.ilog2()
+        );
+    }
+
+    if output.fingerprint {
+        eprint!("{}", fingerprint::randomart(xkcd_password.expose()));
+    }
+    if let Some(path) = &output.keyfile {
+        let cost = kdf::Cost::DEFAULT;
+        match kdf::write_keyfile(path, xkcd_password.expose(), cost) {
+            Ok(salt) => eprintln!(
+                "wrote the keyfile, derived with Argon2id ({} MiB, {} passes) and the salt {}",
+                cost.memory / 1024,
+                cost.passes,
+                kdf::hex(&salt)
+            ),
+            Err(err) => fail(format!("{}: {}", path.display(), err)),
+        }
+    }
+    if let Some(path) = &output.anki {
+        if let Err(err) = anki::write(path, &xkcd_password) {
+            fail(format!("{}: {}", path.display(), err));
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    if let Some(keyname) = &output.ask {
+        return exit_on_error(ask::store(keyname, xkcd_password.expose()));
+    }
+
+    if output.copy {
+        exit_on_error(clipboard::copy(xkcd_password.expose(), selection));
+        if !output.quiet {
+            eprintln!("copied the password to the clipboard");
+        }
+        return;
+    }
+
+    if output.paper {
+        let size = output.chunked.unwrap_or(paper::BLOCK_SIZE);
+        let page = paper::render(
+            xkcd_password.expose(),
+            size,
+            output.qr_only,
+            &sheet::today(),
+        )
+        .unwrap_or_else(|err| fail(err));
+        print!("{}", page);
+        return;
+    }
+
+    let blocks = output
+        .chunked
+        .map(|size| chunked::render(xkcd_password.expose(), size));
+    let shown = blocks.as_deref().unwrap_or(xkcd_password.expose());
+    let spelled = output
+        .phonetic
+        .then(|| format!("{}\n{}", shown, phonetic::spell(xkcd_password.expose())));
+    let shown = spelled.as_deref().unwrap_or(shown);
+    let described = output
+        .accessible
+        .then(|| accessible::describe(&xkcd_password));
+    let shown = described.as_deref().unwrap_or(shown);
+
+    if let Some(seconds) = output.confirm {
+        let timeout = Duration::from_secs(seconds);
+        return exit_on_error(confirm::show(shown, timeout));
+    }
+    if let Some(duration) = output.reveal {
+        return exit_on_error(confirm::reveal(shown, duration));
+    }
+
+    if output.encrypt_to.is_empty() {
+        let derived = output.derive_key.map(|len| {
+            kdf::derive(
+                xkcd_password.expose(),
+                len,
+                output.kdf.unwrap_or(kdf::Kdf::Argon2id),
+                kdf::Cost::DEFAULT,
+                output.salt.as_ref().map(|salt| salt.0.as_slice()),
+                output.key_encoding.unwrap_or(kdf::Encoding::Hex),
+            )
+            .unwrap_or_else(|err| fail(err))
+        });
+        let line = match &derived {
+            Some(derived) => format!(
+                "{}\nkey: {}\nsalt: {}\nkdf: {}",
+                shown, derived.key, derived.salt, derived.kdf
+            ),
+            None => shown.to_string(),
+        };
+        let password = xkcd_password.expose();
+        let var_name = output.var_name.as_deref().unwrap_or("PASSWORD");
+        let single = match format {
+            Format::K8sSecret => {
+                let name = output.name.as_deref().expect("checked above");
+                let key = output.key.as_deref().unwrap_or("password");
+                Some(manifest::secret(name, key, password))
+            }
+            Format::Dotenv => Some(env_vars::dotenv(var_name, password)),
+            Format::Sh => Some(env_vars::sh(var_name, password)),
+            _ => None,
+        };
+        if let Some(text) = single {
+            print!("{}", text);
+            return;
+        }
+        if format == Format::Html {
+            let mut rows = vec![
+                ("Words", settings.config.number.to_string()),
+                ("List", settings.list.to_string()),
+                ("Case", settings.config.case.to_string()),
+                ("Separator", format!("{:?}", settings.config.separator)),
+            ];
+            if let Some(derived) = &derived {
+                rows.push(("Key", derived.key.clone()));
+                rows.push(("Salt", derived.salt.clone()));
+                rows.push(("KDF", derived.kdf.clone()));
+            }
+            let sheet = sheet::Sheet {
+                password: xkcd_password.expose(),
+                shown,
+                fingerprint: &fingerprint::randomart(xkcd_password.expose()),
+                settings: &rows,
+                recipe: &recipe,
+            };
+            let page = sheet::password(&sheet, &sheet::today()).unwrap_or_else(|err| fail(err));
+            print!("{}", page);
+            return;
+        }
+        let mut out = Renderer::new(format, io::stdout().lock());
+        let record = Generated {
+            password: xkcd_password.expose(),
+            recipe: &recipe,
+            key: derived.as_ref().map(|derived| derived.key.as_str()),
+            salt: derived.as_ref().map(|derived| derived.salt.as_str()),
+            kdf: derived.as_ref().map(|derived| derived.kdf.as_str()),
+        };
+        exit_on_error(out.record(&record, line).and_then(|()| out.finish()));
+    } else {
+        match encrypt::encrypt(xkcd_password.expose(), &output.encrypt_to) {
+            Ok(armored) => print!("{}", armored),
+            Err(err) => fail(err),
+        }
+    }
+}
+
+/// Where the entropy of passwords generated with `settings` out of the
+/// `pool` words of `list` comes from.
+fn explain(settings: &Settings, list: &str, pool: usize, adjustments: &[explain::Row]) -> String {
+    explain::explain(&settings.config, pool, list, 0, adjustments)
+}
+
+/// Fail with [`status::Status::Weak`] if passwords generated with `settings`
+/// out of `pool` words have fewer than `min` bits of entropy.
+fn check_entropy(
+    settings: &Settings,
+    pool: usize,
+    adjustments: &[explain::Row],
+    min: f64,
+) -> Result<(), Failure> {
+    let rows = explain::rows(&settings.config, pool, "", 0, adjustments);
+    let bits = explain::total(&rows);
+    if bits < min {
+        let message = format!(
+            "the password would have {:.1} bits of entropy, fewer than the {} of --min-entropy",
+            bits, min
+        );
+        return Err(Failure::new(status::Status::Weak, message));
+    }
+    Ok(())
+}
+
+/// Print the entropy and crack time of passwords generated with `settings`,
+/// plus `digits` random digits, without generating any.
+fn estimate(settings: &Settings, digits: usize, format: Format) -> Result<(), String> {
+    let source = settings.list.open().map_err(|err| err.to_string())?;
+    let pool = source.len();
+    settings.check_number(pool);
+
+    let list = settings.list.to_string();
+    let mut out = Renderer::new(format, io::BufWriter::new(io::stdout().lock()));
+    explain::render(&mut out, &settings.config, pool, &list, digits, &[])
+        .and_then(|()| out.finish())
+        .map_err(|err| err.to_string())
+}
+
+/// Settings for the modes that only know the embedded word lists and fixed separators.
+fn embedded_only(settings: Settings) -> Config {
+    match &settings.list {
+        ListName::Embedded(list) => settings.check_number(list.len()),
+        _ => fail(format!(
+            "the {} word list can only be used to generate passwords",
+            settings.list
+        )),
+    }
+    if separators::digits(&settings.config.separator).is_some() {
+        fail(format!(
+            "the {:?} separator can only be used to generate passwords",
+            settings.config.separator
+        ));
+    }
+    if let Err(err) = request::check_separator(&settings.config.separator, &settings.config.list) {
+        fail(err);
+    }
+
+    settings.config
+}
+
+/// Read the default settings from the configuration file, unless it is skipped,
+/// then from `profile` if one was chosen. Without a list in either, the list
+/// named after the locale is used if `locale` is set.
+fn load_settings(skip: bool, profile: Option<&str>, locale: bool) -> Settings {
+    let mut file = match config_file::default_path() {
+        Some(path) if !skip => ConfigFile::load(&path).unwrap_or_else(|err| fail(err)),
+        _ => ConfigFile::default(),
+    };
+    if let Some(profile) = profile {
+        file = file.select(profile).unwrap_or_else(|err| fail(err));
+    }
+    if locale {
+        file = file.or_list(word_lists::for_locale());
+    }
+
+    file.into_settings()
+}
+
+/// Report the error of a mode that failed and exit with a non-zero status.
+fn exit_on_error<E: Display>(result: Result<(), E>) {
+    if let Err(err) = result {
+        fail(err);
+    }
+}
+
+/// Report an error and exit with a non-zero status.
+fn fail<E: Display>(err: E) -> ! {
+    eprintln!("xkpass: {}", err);
+    std::process::exit(1);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verifies_cli() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn options_override_config_file() {
+        let file = Settings::new(
+            4,
+            String::from("-"),
+            ListName::Custom(String::from("mine")),
+            Case::Upper,
+        );
+        let settings = Cli::parse_from(["xkpass", "-n", "2", "-l", "short1"])
+            .args
+            .apply(file);
+
+        assert_eq!(settings.config.number, 2);
+        assert_eq!(settings.config.separator, "-");
+        assert!(matches!(settings.list, ListName::Embedded(List::Short1)));
+        assert!(matches!(settings.config.list, List::Short1));
+    }
+
+    #[test]
+    fn counts_easy_to_type_against_min_entropy() {
+        // 6 words of the long list are 77.5 bits, 73.5 once the easiest of
+        // 16 candidates is picked
+        let settings = Settings::new(
+            6,
+            String::from(" "),
+            ListName::Embedded(List::Long),
+            Case::Lower,
+        );
+        let pool = List::Long.as_slice().len();
+        assert!(check_entropy(&settings, pool, &[], 75.0).is_ok());
+        let adjustments = [typing::row(typing::Layout::Qwerty)];
+        let failure = check_entropy(&settings, pool, &adjustments, 75.0).unwrap_err();
+        assert_eq!(failure.status, status::Status::Weak);
+    }
+
+    #[test]
+    fn generates_without_subcommand() {
+        let cli = Cli::parse_from(["xkpass", "-n", "3", "--encrypt-to", "age1me"]);
+        assert!(cli.command.is_none());
+        assert_eq!(cli.args.number, Some(3));
+        assert_eq!(cli.output.encrypt_to, ["age1me"]);
+
+        let cli = Cli::parse_from(["xkpass", "serve", "-n", "3"]);
+        assert!(matches!(cli.command, Some(Command::Serve { .. })));
+        assert_eq!(cli.args.number, Some(3));
+    }
+
+    #[test]
+    fn finds_ambiguous_separators() {
+        let words = List::Long.as_slice().iter().copied();
+        assert_eq!(ambiguous_word(words.clone(), "-"), Some("drop-down"));
+        assert_eq!(ambiguous_word(words.clone(), "ZZ"), Some("blizzard"));
+        assert_eq!(ambiguous_word(words.clone(), "."), None);
+        assert_eq!(ambiguous_word(words, ""), None);
+    }
+
+    #[test]
+    fn confirms_for_a_while() {
+        let cli = Cli::parse_from(["xkpass", "--confirm"]);
+        assert_eq!(cli.output.confirm, Some(30));
+        let cli = Cli::parse_from(["xkpass", "--confirm=5", "-n", "3"]);
+        assert_eq!(cli.output.confirm, Some(5));
+        assert!(Cli::try_parse_from(["xkpass", "--confirm", "--count", "2"]).is_err());
+
+        let cli = Cli::parse_from(["xkpass", "--reveal", "10s"]);
+        assert_eq!(cli.output.reveal, Some(Duration::from_secs(10)));
+        let cli = Cli::parse_from(["xkpass", "--reveal", "2m"]);
+        assert_eq!(cli.output.reveal, Some(Duration::from_secs(120)));
+        for invalid in ["0", "ten", "10h", ""] {
+            assert!(Cli::try_parse_from(["xkpass", "--reveal", invalid]).is_err());
+        }
+    }
+
+    #[test]
+    fn copies_quietly() {
+        let cli = Cli::parse_from(["xkpass", "--copy", "--quiet"]);
+        assert!(cli.output.copy && cli.output.quiet);
+        assert!(Cli::try_parse_from(["xkpass", "--quiet"]).is_err());
+        assert!(Cli::try_parse_from(["xkpass", "--copy", "--count", "2"]).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn passes_arguments_to_ssh_keygen() {
+        let cli = Cli::parse_from(["xkpass", "ssh-keygen", "-n", "8", "--", "-t", "ed25519"]);
+        assert_eq!(cli.args.number, Some(8));
+        match cli.command {
+            Some(Command::SshKeygen { copy, args }) => {
+                assert!(!copy);
+                assert_eq!(args, ["-t", "ed25519"]);
+            }
+            command => panic!("{:?}", command),
+        }
+    }
+
+    #[test]
+    fn rejects_zero_words() {
+        assert!(Cli::try_parse_from(["xkpass", "-n", "0"]).is_err());
+        assert!(Cli::try_parse_from(["xkpass", "-n", "-1"]).is_err());
+    }
+}
@@ -0,0 +1,78 @@
+//! Kubernetes Secrets: the YAML of `--format k8s-secret`, ready for
+//! `kubectl apply`, holding the password base64-encoded under one key.
+
+use base64::Engine;
+
+/// Longest name of a Secret, that of a DNS subdomain.
+const MAX_NAME_LEN: usize = 253;
+
+/// The manifest of a Secret named `name` holding `password` under `key`.
+pub fn secret(name: &str, key: &str, password: &str) -> String {
+    let value = base64::engine::general_purpose::STANDARD.encode(password);
+    format!(
+        "apiVersion: v1\n\
+         kind: Secret\n\
+         metadata:\n  name: {}\n\
+         type: Opaque\n\
+         data:\n  {}: {}\n",
+        name, key, value
+    )
+}
+
+/// Parse the name of a Secret: lowercase letters, digits, `-` and `.`,
+/// starting and ending with a letter or digit.
+pub fn parse_name(value: &str) -> Result<String, String> {
+    let edges = |c: Option<char>| c.is_some_and(|c| c.is_ascii_lowercase() || c.is_ascii_digit());
+    let valid = value.len() <= MAX_NAME_LEN
+        && edges(value.chars().next())
+        && edges(value.chars().last())
+        && value
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '.');
+    if valid {
+        Ok(value.to_string())
+    } else {
+        Err(format!(
+            "{:?} isn't the name of a Secret, made of lowercase letters, digits, '-' and '.'",
+            value
+        ))
+    }
+}
+
+/// Parse a key of a Secret: letters, digits, `-`, `_` and `.`.
+pub fn parse_key(value: &str) -> Result<String, String> {
+    let valid = !value.is_empty()
+        && value.len() <= MAX_NAME_LEN
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.');
+    if valid {
+        Ok(value.to_string())
+    } else {
+        Err(format!(
+            "{:?} isn't a key of a Secret, made of letters, digits, '-', '_' and '.'",
+            value
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_secrets() {
+        assert_eq!(
+            secret("db", "password", "correct horse"),
+            "apiVersion: v1\nkind: Secret\nmetadata:\n  name: db\ntype: Opaque\ndata:\n  password: Y29ycmVjdCBob3JzZQ==\n"
+        );
+        assert!(parse_name("my-secret.v2").is_ok());
+        assert!(parse_key("DB_PASSWORD").is_ok());
+        for invalid in ["", "My-Secret", "-secret", "secret-", "a b", "a: b"] {
+            assert!(parse_name(invalid).is_err(), "{}", invalid);
+        }
+        for invalid in ["", "a b", "a:b", "a/b"] {
+            assert!(parse_key(invalid).is_err(), "{}", invalid);
+        }
+    }
+}
@@ -0,0 +1,102 @@
+//! Keeping only passwords that match a regular expression, for site rules
+//! that no dedicated option covers.
+//!
+//! Candidates are generated until one matches, up to a bound. Keeping only
+//! the matching passwords takes away the bits of those thrown out, so the
+//! share of candidates that match is estimated out of at least [`SAMPLES`]
+//! of them, and the bits it costs are reported.
+
+use crate::explain::Row;
+use regex::Regex;
+use std::cell::Cell;
+use xkpass::Password;
+
+/// Most candidates generated before giving up on a match.
+pub const MAX_ATTEMPTS: usize = 100_000;
+
+/// Fewest candidates the share of matching passwords is estimated out of.
+pub const SAMPLES: usize = 1_000;
+
+/// A regular expression passwords must match, with the candidates it was tried on.
+pub struct Filter {
+    regex: Regex,
+    tried: Cell<usize>,
+    matched: Cell<usize>,
+}
+
+impl Filter {
+    pub fn new(pattern: &str) -> Result<Self, String> {
+        let regex = Regex::new(pattern).map_err(|err| format!("--match-regex: {}", err))?;
+        Ok(Self {
+            regex,
+            tried: Cell::new(0),
+            matched: Cell::new(0),
+        })
+    }
+
+    /// The first candidate made by `generate` that matches, along with what
+    /// came with it, generating more until [`SAMPLES`] candidates have been
+    /// tried in all to estimate the share of those matching.
+    pub fn first<T, F>(&self, generate: F) -> Result<(Password, T), String>
+    where
+        F: Fn() -> Result<(Password, T), String>,
+    {
+        let mut found = None;
+        for _ in 0..MAX_ATTEMPTS {
+            let candidate = generate()?;
+            self.tried.set(self.tried.get() + 1);
+            if self.regex.is_match(candidate.0.expose()) {
+                self.matched.set(self.matched.get() + 1);
+                found.get_or_insert(candidate);
+            }
+            if found.is_some() && self.tried.get() >= SAMPLES {
+                break;
+            }
+        }
+
+        found.ok_or_else(|| {
+            format!(
+                "none of {} candidates matched --match-regex {:?}",
+                MAX_ATTEMPTS,
+                self.regex.as_str()
+            )
+        })
+    }
+
+    /// The bits lost to keeping only the matching candidates.
+    pub fn row(&self) -> Row {
+        let share = self.matched.get() as f64 / self.tried.get() as f64;
+        (
+            "regex",
+            format!("1 in {:.0} candidates matches", 1.0 / share),
+            share.log2(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use xkpass::Config;
+
+    #[test]
+    fn keeps_matching_candidates() {
+        let filter = Filter::new("^[a-m]").unwrap();
+        let generate = || {
+            let password =
+                xkpass::generate_password(Config::default()).map_err(|err| err.to_string())?;
+            Ok((password, ()))
+        };
+        let (password, ()) = filter.first(generate).unwrap();
+        assert!(filter.regex.is_match(password.expose()));
+        assert!(filter.tried.get() >= SAMPLES);
+
+        // about half of the words of the long list start with a to m
+        let (_, _, bits) = filter.row();
+        assert!(-1.5 < bits && bits < -0.5, "{}", bits);
+
+        let never = Filter::new("^$").unwrap();
+        assert!(never.first(generate).is_err());
+        assert!(Filter::new("(").is_err());
+    }
+}
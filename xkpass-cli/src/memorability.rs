@@ -0,0 +1,115 @@
+//! Scoring how easy the words of a password are to remember.
+//!
+//! The score rewards words of few syllables, and words on EFF's first short
+//! list, which EFF picked by hand out of its long list as the most memorable.
+//! Neither measures how common a word is: there is no frequency table, so a
+//! plain word missing from the short list counts as much as a rare one.
+//!
+//! Syllables are counted as groups of vowels, leaving out a silent final "e",
+//! which is right for the vast majority of English words.
+
+use std::fmt::{self, Display};
+use xkpass::List;
+
+/// Weight of each word by number of syllables, from one syllable up, the
+/// last one standing for any longer word.
+const SYLLABLE_WEIGHTS: [f64; 5] = [1.0, 0.85, 0.65, 0.45, 0.3];
+
+/// Share of the score that goes to words on the short list, the rest going to syllables.
+const SHORT_LISTED_WEIGHT: f64 = 0.2;
+
+/// How easy the words of a password are to remember.
+#[derive(Debug, PartialEq)]
+pub struct Memorability {
+    /// From 0 for the hardest to 100 for the easiest.
+    pub score: u32,
+    syllables: usize,
+    short_listed: usize,
+    words: usize,
+}
+
+/// Score `words`, in any case.
+pub fn score<'a>(words: impl IntoIterator<Item = &'a str>) -> Memorability {
+    let mut total = 0.0;
+    let mut memorability = Memorability {
+        score: 0,
+        syllables: 0,
+        short_listed: 0,
+        words: 0,
+    };
+    for word in words {
+        let word = word.to_lowercase();
+        let syllables = syllables(&word);
+        let short_listed = List::Short1.contains(&word);
+        let weight = SYLLABLE_WEIGHTS[syllables.min(SYLLABLE_WEIGHTS.len()) - 1];
+        total += weight * (1.0 - SHORT_LISTED_WEIGHT)
+            + if short_listed {
+                SHORT_LISTED_WEIGHT
+            } else {
+                0.0
+            };
+
+        memorability.syllables += syllables;
+        memorability.short_listed += usize::from(short_listed);
+        memorability.words += 1;
+    }
+    if memorability.words > 0 {
+        memorability.score = (100.0 * total / memorability.words as f64).round() as u32;
+    }
+    memorability
+}
+
+/// Number of syllables of a lowercase `word`, at least one.
+fn syllables(word: &str) -> usize {
+    let vowel = |c: char| "aeiouy".contains(c);
+    let mut count = 0;
+    let mut previous = None;
+    for c in word.chars() {
+        if vowel(c) && !previous.is_some_and(vowel) {
+            count += 1;
+        }
+        previous = Some(c);
+    }
+    // "crane" but not "able" nor "free"
+    if word.ends_with('e') && !word.ends_with("le") && !word.ends_with("ee") && count > 1 {
+        count -= 1;
+    }
+    count.max(1)
+}
+
+impl Display for Memorability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "memorability {}/100, with {:.1} syllables per word and {} of {} words on EFF's short list",
+            self.score,
+            self.syllables as f64 / self.words.max(1) as f64,
+            self.short_listed,
+            self.words
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_syllables() {
+        let counts: Vec<usize> = ["cat", "crane", "able", "free", "acorn", "abdomen", "rhythm"]
+            .into_iter()
+            .map(syllables)
+            .collect();
+        assert_eq!(counts, [1, 1, 2, 1, 2, 3, 1]);
+    }
+
+    #[test]
+    fn prefers_few_syllables_and_short_listed_words() {
+        assert_eq!(score(["Acid", "acorn"]).score, 88);
+        assert_eq!(score(["abacus", "abdomen"]).score, 52);
+        assert_eq!(
+            score(["acid", "abdomen"]).to_string(),
+            "memorability 70/100, with 2.5 syllables per word and 1 of 2 words on EFF's short list"
+        );
+    }
+}
@@ -0,0 +1,125 @@
+//! Integration with menu programs such as rofi, dmenu or fzf.
+//!
+//! A number of candidates is printed one per line, the menu's output is read
+//! back on stdin and the chosen password is copied to the clipboard. Wire the
+//! two ends together with a named pipe:
+//!
+//! ```bash
+//! mkfifo /tmp/xkpass-menu
+//! xkpass menu < /tmp/xkpass-menu | dmenu > /tmp/xkpass-menu
+//! ```
+
+use crate::clipboard::{self, Selection};
+use std::io::{self, BufRead, Write};
+use xkpass::{Config, Password};
+
+/// Print `count` candidates, wait for the choice and copy it to `selection`.
+pub fn run(config: Config, count: usize, selection: Selection) -> io::Result<()> {
+    let candidates: Vec<String> = xkpass::generate_passwords(&config, count)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?
+        .into_iter()
+        .map(Password::into_string)
+        .collect();
+
+    let mut out = io::stdout().lock();
+    for candidate in &candidates {
+        writeln!(out, "{}", candidate)?;
+    }
+    out.flush()?;
+    drop(out);
+    // menus like dmenu only show up once their input has ended
+    close_stdout()?;
+
+    let mut chosen = String::new();
+    io::stdin().lock().read_line(&mut chosen)?;
+
+    match pick(&candidates, &chosen) {
+        Some(password) => clipboard::copy(password, selection),
+        None if chosen.trim().is_empty() => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "nothing was selected",
+        )),
+        None => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "the selection is not one of the candidates",
+        )),
+    }
+}
+
+/// Find the candidate the menu printed back.
+///
+/// Only exact candidates are accepted, so nothing else can end up on the clipboard.
+fn pick<'a>(candidates: &'a [String], selection: &str) -> Option<&'a str> {
+    let selection = selection.trim_end_matches(&['\r', '\n'][..]);
+    candidates
+        .iter()
+        .map(String::as_str)
+        .find(|candidate| *candidate == selection)
+}
+
+/// Point stdout at `/dev/null`, which signals the end of the candidates to the menu.
+#[cfg(unix)]
+fn close_stdout() -> io::Result<()> {
+    use std::{fs::OpenOptions, os::unix::io::AsRawFd};
+
+    let null = OpenOptions::new().write(true).open("/dev/null")?;
+    // SAFETY: both descriptors are open, `dup2` atomically replaces stdout
+    if unsafe { libc::dup2(null.as_raw_fd(), libc::STDOUT_FILENO) } == -1 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(())
+}
+
+/// Point stdout at `NUL` and close the handle it had, which signals the end
+/// of the candidates to the menu.
+#[cfg(windows)]
+fn close_stdout() -> io::Result<()> {
+    use std::{fs::OpenOptions, os::windows::io::IntoRawHandle};
+    use windows_sys::Win32::{
+        Foundation::{CloseHandle, INVALID_HANDLE_VALUE},
+        System::Console::{GetStdHandle, SetStdHandle, STD_OUTPUT_HANDLE},
+    };
+
+    let null = OpenOptions::new()
+        .write(true)
+        .open("NUL")?
+        .into_raw_handle();
+    // SAFETY: `null` was just opened and is owned by the process from now on,
+    // std looks stdout up again on every write, so nothing uses the old handle
+    // once it has been replaced
+    unsafe {
+        let stdout = GetStdHandle(STD_OUTPUT_HANDLE);
+        if SetStdHandle(STD_OUTPUT_HANDLE, null) == 0 {
+            let err = io::Error::last_os_error();
+            CloseHandle(null);
+            return Err(err);
+        }
+        if !stdout.is_null() && stdout != INVALID_HANDLE_VALUE && CloseHandle(stdout) == 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidates() -> Vec<String> {
+        vec![String::from("foo bar"), String::from("buzz fizz")]
+    }
+
+    #[test]
+    fn picks_selected_candidate() {
+        assert_eq!(pick(&candidates(), "buzz fizz\n"), Some("buzz fizz"));
+        assert_eq!(pick(&candidates(), "foo bar\r\n"), Some("foo bar"));
+    }
+
+    #[test]
+    fn ignores_unknown_selection() {
+        assert_eq!(pick(&candidates(), "\n"), None);
+        assert_eq!(pick(&candidates(), "foo"), None);
+    }
+}
@@ -0,0 +1,149 @@
+//! Metrics of `serve` in the text format of Prometheus, for running it as an
+//! internal service.
+//!
+//! They count requests by route and status, and sort the time taken to answer
+//! and the estimated entropy of the passwords generated into histograms.
+//! Nothing about a password but its entropy is ever kept, and routes other
+//! than those of the API are counted together so that scanners can't grow
+//! the metrics without bound.
+
+use std::{collections::BTreeMap, fmt::Write, time::Duration};
+
+/// Upper bounds of the buckets of the time taken to answer, in seconds.
+const LATENCY_BOUNDS: &[f64] = &[0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25];
+
+/// Upper bounds of the buckets of the entropy of passwords, in bits.
+const ENTROPY_BOUNDS: &[f64] = &[32.0, 48.0, 64.0, 80.0, 96.0, 128.0, 192.0, 256.0];
+
+/// The routes counted on their own, the others being counted as `other`.
+const ROUTES: &[&str] = &["/generate", "/healthz", "/metrics"];
+
+/// What `serve` has done since it started.
+#[derive(Debug)]
+pub struct Metrics {
+    /// Requests answered, by route and status.
+    requests: BTreeMap<(&'static str, u16), u64>,
+    latency: Histogram,
+    entropy: Histogram,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self {
+            requests: BTreeMap::new(),
+            latency: Histogram::new(LATENCY_BOUNDS),
+            entropy: Histogram::new(ENTROPY_BOUNDS),
+        }
+    }
+}
+
+impl Metrics {
+    /// Count a request to `url` answered with `status` in `elapsed`.
+    pub fn request(&mut self, url: &str, status: u16, elapsed: Duration) {
+        let route = ROUTES
+            .iter()
+            .find(|&&route| route == url)
+            .map_or("other", |route| route);
+        *self.requests.entry((route, status)).or_default() += 1;
+        self.latency.observe(elapsed.as_secs_f64());
+    }
+
+    /// Count a password of `bits` bits of entropy.
+    pub fn password(&mut self, bits: f64) {
+        self.entropy.observe(bits);
+    }
+
+    /// The metrics in the text format of Prometheus.
+    pub fn render(&self) -> String {
+        let mut text = String::from(
+            "# HELP xkpass_http_requests_total HTTP requests answered, by route and status.\n\
+             # TYPE xkpass_http_requests_total counter\n",
+        );
+        for ((route, status), count) in &self.requests {
+            let _ = writeln!(
+                text,
+                "xkpass_http_requests_total{{route=\"{}\",status=\"{}\"}} {}",
+                route, status, count
+            );
+        }
+        self.latency.render(
+            &mut text,
+            "xkpass_http_request_duration_seconds",
+            "Time taken to answer HTTP requests.",
+        );
+        self.entropy.render(
+            &mut text,
+            "xkpass_password_entropy_bits",
+            "Estimated entropy of the passwords generated.",
+        );
+        text
+    }
+}
+
+#[derive(Debug)]
+struct Histogram {
+    bounds: &'static [f64],
+    /// Observations in each bucket, and above the last bound.
+    counts: Vec<u64>,
+    sum: f64,
+}
+
+impl Histogram {
+    fn new(bounds: &'static [f64]) -> Self {
+        Self {
+            bounds,
+            counts: vec![0; bounds.len() + 1],
+            sum: 0.0,
+        }
+    }
+
+    fn observe(&mut self, value: f64) {
+        let bucket = self.bounds.partition_point(|&bound| bound < value);
+        self.counts[bucket] += 1;
+        self.sum += value;
+    }
+
+    /// Write the histogram as `name`, with cumulative buckets as Prometheus wants them.
+    fn render(&self, text: &mut String, name: &str, help: &str) {
+        let _ = writeln!(text, "# HELP {} {}\n# TYPE {} histogram", name, help, name);
+        let mut cumulative = 0;
+        for (i, count) in self.counts.iter().enumerate() {
+            cumulative += count;
+            let bound = self
+                .bounds
+                .get(i)
+                .map_or_else(|| String::from("+Inf"), f64::to_string);
+            let _ = writeln!(text, "{}_bucket{{le=\"{}\"}} {}", name, bound, cumulative);
+        }
+        let _ = writeln!(text, "{}_sum {}", name, self.sum);
+        let _ = writeln!(text, "{}_count {}", name, cumulative);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_prometheus_text() {
+        let mut metrics = Metrics::default();
+        metrics.request("/generate", 200, Duration::from_millis(2));
+        metrics.request("/generate", 200, Duration::from_millis(30));
+        metrics.request("/wp-login.php", 404, Duration::from_micros(100));
+        metrics.password(77.5);
+        metrics.password(300.0);
+
+        let text = metrics.render();
+        assert!(text.contains("xkpass_http_requests_total{route=\"/generate\",status=\"200\"} 2\n"));
+        assert!(text.contains("xkpass_http_requests_total{route=\"other\",status=\"404\"} 1\n"));
+        assert!(!text.contains("wp-login"));
+        assert!(text.contains("xkpass_http_request_duration_seconds_bucket{le=\"0.0005\"} 1\n"));
+        assert!(text.contains("xkpass_http_request_duration_seconds_bucket{le=\"0.0025\"} 2\n"));
+        assert!(text.contains("xkpass_http_request_duration_seconds_bucket{le=\"+Inf\"} 3\n"));
+        assert!(text.contains("xkpass_http_request_duration_seconds_count 3\n"));
+        assert!(text.contains("xkpass_password_entropy_bits_bucket{le=\"64\"} 0\n"));
+        assert!(text.contains("xkpass_password_entropy_bits_bucket{le=\"80\"} 1\n"));
+        assert!(text.contains("xkpass_password_entropy_bits_bucket{le=\"+Inf\"} 2\n"));
+        assert!(text.contains("xkpass_password_entropy_bits_sum 377.5\n"));
+    }
+}
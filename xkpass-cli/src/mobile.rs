@@ -0,0 +1,56 @@
+//! Passwords that can be typed on a phone without leaving the letters layer.
+//!
+//! Phone keyboards show lowercase letters and the space bar first, while
+//! capitals need Shift and digits and punctuation a switch to another layer,
+//! which is where most typos on a touchscreen happen.
+
+use crate::{separators, Settings};
+use xkpass::Case;
+
+/// Keep `settings` on the letters layer: lowercase words, separated by spaces
+/// unless the separator is made of lowercase letters already and doesn't
+/// stand for random digits.
+pub fn settings(mut settings: Settings) -> Settings {
+    settings.config.case = Case::Lower;
+    let separator = &settings.config.separator;
+    if !separator.chars().all(typable) || separators::digits(separator).is_some() {
+        settings.config.separator = " ".to_string();
+    }
+    settings
+}
+
+/// Whether `word` can be typed on the letters layer.
+pub fn word(word: &str) -> bool {
+    word.chars().all(|c| c != ' ' && typable(c))
+}
+
+fn typable(c: char) -> bool {
+    c == ' ' || (c.is_alphabetic() && !c.is_uppercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::word_lists::ListName;
+    use xkpass::List;
+
+    #[test]
+    fn stays_on_the_letters_layer() {
+        assert!(word("drawbridge"));
+        assert!(word("éclair"));
+        assert!(!word("drop-down"));
+        assert!(!word("Paris"));
+        assert!(!word("r2d2"));
+
+        let list = ListName::Embedded(List::Long);
+        let adapted = settings(Settings::new(4, "-".to_string(), list, Case::Upper));
+        assert_eq!(adapted.config.separator, " ");
+        assert!(matches!(adapted.config.case, Case::Lower));
+        let list = ListName::Embedded(List::Long);
+        let adapted = settings(Settings::new(4, "x".to_string(), list, Case::Lower));
+        assert_eq!(adapted.config.separator, "x");
+        let list = ListName::Embedded(List::Long);
+        let adapted = settings(Settings::new(4, "digits".to_string(), list, Case::Lower));
+        assert_eq!(adapted.config.separator, " ");
+    }
+}
@@ -0,0 +1,110 @@
+//! A [native messaging](https://developer.chrome.com/docs/extensions/develop/concepts/native-messaging)
+//! host, so a browser extension can ask the locally installed `xkpass` for passwords.
+//!
+//! Every message, in both directions, is a JSON document preceded by its length
+//! as a 32-bit integer in native byte order. Requests use the same fields as the
+//! HTTP API and are answered with `{"password": ...}` or `{"error": ...}`.
+
+use crate::request::GenerateRequest;
+use serde_json::{json, Value};
+use std::io::{self, Read, Write};
+use xkpass::Config;
+
+/// Largest message accepted from the browser, in bytes.
+const MAX_MESSAGE_SIZE: usize = 1024 * 1024;
+
+/// Answer messages from the browser until it closes stdin.
+pub fn run(defaults: Config) -> io::Result<()> {
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    let mut input = stdin.lock();
+    let mut output = stdout.lock();
+
+    while let Some(message) = read_message(&mut input)? {
+        write_message(&mut output, &answer(&message, &defaults))?;
+    }
+
+    Ok(())
+}
+
+/// Read one message, returning `None` once the browser has hung up.
+fn read_message<R: Read>(input: &mut R) -> io::Result<Option<Vec<u8>>> {
+    let mut length = [0; 4];
+    match input.read_exact(&mut length) {
+        Ok(()) => {}
+        Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(err) => return Err(err),
+    }
+
+    let length = u32::from_ne_bytes(length) as usize;
+    if length > MAX_MESSAGE_SIZE {
+        // the rest of the stream can't be trusted to be in sync anymore
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("message of {} bytes is too large", length),
+        ));
+    }
+
+    let mut message = vec![0; length];
+    input.read_exact(&mut message)?;
+
+    Ok(Some(message))
+}
+
+/// Write one message, prefixed by its length.
+fn write_message<W: Write>(output: &mut W, message: &Value) -> io::Result<()> {
+    let message = message.to_string();
+    let length = u32::try_from(message.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "response is too large"))?;
+
+    output.write_all(&length.to_ne_bytes())?;
+    output.write_all(message.as_bytes())?;
+    output.flush()
+}
+
+/// Produce the response to a single message.
+fn answer(message: &[u8], defaults: &Config) -> Value {
+    match GenerateRequest::from_json(message).and_then(|request| request.generate(defaults)) {
+        Ok(password) => json!({ "password": password }),
+        Err(err) => json!({ "error": err }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn frame(message: &str) -> Vec<u8> {
+        let mut framed = (message.len() as u32).to_ne_bytes().to_vec();
+        framed.extend_from_slice(message.as_bytes());
+        framed
+    }
+
+    #[test]
+    fn answers_framed_messages() {
+        let defaults = Config::default();
+        let mut input = Cursor::new([frame(r#"{"number": 2}"#), frame("{")].concat());
+        let mut output = Vec::new();
+
+        while let Some(message) = read_message(&mut input).unwrap() {
+            write_message(&mut output, &answer(&message, &defaults)).unwrap();
+        }
+
+        let mut output = Cursor::new(output);
+        let first: Value =
+            serde_json::from_slice(&read_message(&mut output).unwrap().unwrap()).unwrap();
+        let second: Value =
+            serde_json::from_slice(&read_message(&mut output).unwrap().unwrap()).unwrap();
+
+        assert_eq!(first["password"].as_str().unwrap().split(' ').count(), 2);
+        assert!(second["error"].is_string());
+        assert!(read_message(&mut output).unwrap().is_none());
+    }
+
+    #[test]
+    fn rejects_oversized_messages() {
+        let length = (MAX_MESSAGE_SIZE as u32 + 1).to_ne_bytes();
+        assert!(read_message(&mut Cursor::new(length)).is_err());
+    }
+}
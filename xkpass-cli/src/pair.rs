@@ -0,0 +1,73 @@
+//! Generating a username and a password together, for signing up to an account.
+//!
+//! The username is made of short words and two digits, which sites accept and
+//! which are often still free. The password is generated with the usual
+//! settings, leaving out the words of the username so that knowing it tells
+//! nothing about the password.
+
+use rand::Rng;
+use serde::Serialize;
+use xkpass::{Config, List};
+
+/// Number of words of the username.
+const USERNAME_WORDS: usize = 2;
+
+#[derive(Serialize, Debug)]
+struct Pair {
+    username: String,
+    password: String,
+}
+
+/// Print a username and a password generated with `config`, on two lines or
+/// as a JSON object with `json`.
+pub fn run(config: Config, json: bool) -> Result<(), String> {
+    let pair = pair(&config, &mut rand::thread_rng())?;
+    if json {
+        let json = serde_json::to_string(&pair).map_err(|err| err.to_string())?;
+        println!("{}", json);
+    } else {
+        println!("{}\n{}", pair.username, pair.password);
+    }
+    Ok(())
+}
+
+fn pair(config: &Config, rng: &mut impl Rng) -> Result<Pair, String> {
+    let username_words = xkpass::sample_words_with(List::Short1.as_slice(), USERNAME_WORDS, rng)
+        .map_err(|err| err.to_string())?;
+    let username = format!("{}{:02}", username_words.join("_"), rng.gen_range(0..100));
+
+    let words: Vec<&str> = config
+        .list
+        .as_slice()
+        .iter()
+        .copied()
+        .filter(|word| !username_words.contains(word))
+        .collect();
+    let password = xkpass::generate_password_with(&words, config, rng)
+        .map_err(|err| err.to_string())?
+        .into_string();
+
+    Ok(Pair { username, password })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shares_no_words() {
+        // every word of the list but those of the username
+        let config = Config {
+            number: List::Short1.len() - USERNAME_WORDS,
+            list: List::Short1,
+            ..Config::default()
+        };
+        let pair = pair(&config, &mut rand::thread_rng()).unwrap();
+        let (words, digits) = pair.username.split_at(pair.username.len() - 2);
+        assert!(digits.chars().all(|c| c.is_ascii_digit()));
+        for word in words.split('_') {
+            assert!(List::Short1.contains(word));
+            assert!(!pair.password.split(' ').any(|other| other == word));
+        }
+    }
+}
@@ -0,0 +1,58 @@
+//! A page of plain text to print and keep a passphrase in cold storage.
+//!
+//! The page has the password laid out in blocks, as `--chunked` does, its QR
+//! code drawn with block characters, dark on the light paper, and its
+//! fingerprint, to tell the page apart from others without reading the
+//! password. The QR code alone can stand for the password, for pages that
+//! shouldn't show it to whoever glances at them. The page fits in 80 columns
+//! and 66 lines, the size of a US Letter or A4 page on a line printer.
+
+use crate::{chunked, fingerprint};
+use qrcode::{render::unicode::Dense1x2, QrCode};
+
+/// Characters in each block of the password, unless `--chunked` says otherwise.
+pub const BLOCK_SIZE: usize = 4;
+
+/// The page of `password`, generated on `date`, in blocks of `size`
+/// characters or only as a QR code with `qr_only`.
+pub fn render(password: &str, size: usize, qr_only: bool, date: &str) -> Result<String, String> {
+    let qr = QrCode::new(password.as_bytes()).map_err(|err| err.to_string())?;
+    let image = qr
+        .render::<Dense1x2>()
+        .dark_color(Dense1x2::Dark)
+        .light_color(Dense1x2::Light)
+        .build();
+
+    let mut page = format!("PASSWORD{:>72}\n\n", format!("generated {}", date));
+    if qr_only {
+        page += "Only in the QR code below: scan it to type the password back.\n";
+    } else {
+        page += &chunked::render(password, size);
+    }
+    page += "\n";
+    page += &image;
+    page += "\n\nFINGERPRINT\n\n";
+    page += &fingerprint::randomart(password);
+    Ok(page)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fits_on_a_page() {
+        let password = "correct horse battery staple tremor unruly kinsman sublime";
+        let page = render(password, BLOCK_SIZE, false, "2026-10-15").unwrap();
+        assert!(page.starts_with("PASSWORD"));
+        assert!(page.contains("generated 2026-10-15"));
+        assert!(page.contains("corr ect␣"));
+        assert!(page.contains(&fingerprint::randomart(password)));
+        assert!(page.lines().count() <= 66, "{}", page);
+        assert!(page.lines().all(|line| line.chars().count() <= 80));
+
+        let hidden = render(password, BLOCK_SIZE, true, "2026-10-15").unwrap();
+        assert!(!hidden.contains("corr"));
+        assert!(hidden.contains('▀'));
+    }
+}
@@ -0,0 +1,111 @@
+//! Spelling a password out in the NATO phonetic alphabet, for reading it to
+//! someone over the phone.
+//!
+//! Every character gets a line of its own, numbered so that both ends can
+//! tell where they are. Letters are marked "CAPITAL" or "small", since
+//! the code words say nothing of case, and symbols are named.
+
+const LETTERS: [&str; 26] = [
+    "alfa", "bravo", "charlie", "delta", "echo", "foxtrot", "golf", "hotel", "india", "juliett",
+    "kilo", "lima", "mike", "november", "oscar", "papa", "quebec", "romeo", "sierra", "tango",
+    "uniform", "victor", "whiskey", "x-ray", "yankee", "zulu",
+];
+
+const DIGITS: [&str; 10] = [
+    "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "niner",
+];
+
+/// `password` spelled out, one character per line.
+pub fn spell(password: &str) -> String {
+    let len = password.chars().count();
+    let width = len.to_string().len();
+    let lines: Vec<String> = password
+        .chars()
+        .enumerate()
+        .map(|(i, c)| format!("{:>width$}  {}  {}", i + 1, shown(c), name(c)))
+        .collect();
+    lines.join("\n")
+}
+
+/// `c` as it is printed on its line, where a space would go unseen.
+fn shown(c: char) -> char {
+    if c == ' ' {
+        '␣'
+    } else {
+        c
+    }
+}
+
+/// The code word for `c`, or its name for characters other than letters.
+pub fn name(c: char) -> String {
+    if c.is_ascii_alphabetic() {
+        let word = LETTERS[usize::from(c.to_ascii_lowercase() as u8 - b'a')];
+        return if c.is_ascii_uppercase() {
+            format!("CAPITAL {}", word.to_uppercase())
+        } else {
+            format!("small {}", word)
+        };
+    }
+    if let Some(digit) = c.to_digit(10) {
+        return format!("digit {}", DIGITS[digit as usize]);
+    }
+
+    let symbol = match c {
+        ' ' => "space",
+        '-' => "dash",
+        '_' => "underscore",
+        '.' => "period",
+        ',' => "comma",
+        ':' => "colon",
+        ';' => "semicolon",
+        '!' => "exclamation mark",
+        '?' => "question mark",
+        '#' => "hash",
+        '$' => "dollar sign",
+        '%' => "percent sign",
+        '&' => "ampersand",
+        '*' => "asterisk",
+        '+' => "plus sign",
+        '=' => "equals sign",
+        '@' => "at sign",
+        '^' => "caret",
+        '~' => "tilde",
+        '/' => "slash",
+        '\\' => "backslash",
+        '|' => "vertical bar",
+        '\'' => "apostrophe",
+        '"' => "double quote",
+        '`' => "backtick",
+        '(' => "opening parenthesis",
+        ')' => "closing parenthesis",
+        '[' => "opening bracket",
+        ']' => "closing bracket",
+        '{' => "opening brace",
+        '}' => "closing brace",
+        '<' => "less-than sign",
+        '>' => "greater-than sign",
+        _ if c.is_uppercase() => return format!("CAPITAL {} (U+{:04X})", c, u32::from(c)),
+        _ if c.is_lowercase() => return format!("small {} (U+{:04X})", c, u32::from(c)),
+        _ => return format!("U+{:04X}", u32::from(c)),
+    };
+    symbol.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spells_every_character() {
+        assert_eq!(
+            spell("Ab 9!é"),
+            "1  A  CAPITAL ALFA\n\
+             2  b  small bravo\n\
+             3  ␣  space\n\
+             4  9  digit niner\n\
+             5  !  exclamation mark\n\
+             6  é  small é (U+00E9)"
+        );
+        assert!(spell("correct horse battery").starts_with(" 1  c  small charlie\n"));
+    }
+}
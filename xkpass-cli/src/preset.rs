@@ -0,0 +1,53 @@
+//! Built-in sets of settings for passwords with a particular use, chosen with
+//! `--preset` in place of the configuration file.
+
+use crate::{word_lists::ListName, Settings};
+use clap::ValueEnum;
+use xkpass::{Case, List};
+
+/// Uses with settings of their own.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Preset {
+    /// Disk encryption with LUKS or VeraCrypt: 10 lowercase words of the long
+    /// list separated by spaces, at least 128 bits
+    Luks,
+}
+
+impl Preset {
+    /// The settings of the preset, which options on the command line still override.
+    pub fn settings(self) -> Settings {
+        match self {
+            // a disk passphrase is typed at boot, before any keyboard layout is
+            // loaded, hence lowercase letters and spaces only; spaces appear in
+            // no word of the long list, so its words can always be told apart
+            Self::Luks => Settings::new(
+                10,
+                String::from(" "),
+                ListName::Embedded(List::Long),
+                Case::Lower,
+            ),
+        }
+    }
+
+    /// The entropy passwords of the preset must have at least, in bits.
+    pub fn min_entropy(self) -> f64 {
+        match self {
+            // a volume can be attacked offline for as long as it exists
+            Self::Luks => 128.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn luks_has_enough_entropy() {
+        let settings = Preset::Luks.settings();
+        let words = List::Long.as_slice();
+        let bits = settings.config.number as f64 * (words.len() as f64).log2();
+        assert!(bits >= Preset::Luks.min_entropy(), "{}", bits);
+        assert!(crate::ambiguous_word(words.iter().copied(), &settings.config.separator).is_none());
+    }
+}
@@ -0,0 +1,336 @@
+//! Recipes: every setting a password was generated with on one line, to
+//! archive it and generate passwords the same way years later, whatever the
+//! defaults have become by then.
+//!
+//! A recipe is its version followed by comma-separated `key=value` pairs,
+//! with the values percent-encoded, and the options that are off left out:
+//!
+//! ```text
+//! xk1:list=long,number=6,case=lower,separator=%20,order=alphabetical,rhyme
+//! ```
+//!
+//! The list, the number of words, the case and the separator are always
+//! there. The user's own lists are followed by `@` and a digest of their
+//! words, so that a list changed since is caught rather than silently used.
+//! A recipe with a `seed` draws every random choice from a ChaCha20 generator
+//! seeded with it, and replays to the very same password: keep such recipes
+//! as secret as the password. `--seed-file` seeds the generator the same way,
+//! with the SHA-256 of a file of entropy gathered offline, such as dice rolls.
+
+use crate::{typing::Layout, word_lists::ListName, Output, Settings};
+use clap::ValueEnum;
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use sha2::{Digest, Sha256};
+use std::{fmt::Write, fs, io, path::Path};
+use xkpass::{Case, Order};
+
+/// Version of the recipes written, the only one read so far.
+pub const VERSION: &str = "xk1";
+
+/// Bytes of the seed of a recipe.
+pub const SEED_LEN: usize = 32;
+
+/// The recipe of passwords generated with `settings` and `output`, drawn
+/// from a generator seeded with `seed` if there is one.
+pub fn write(
+    settings: &Settings,
+    output: &Output,
+    seed: Option<&[u8; SEED_LEN]>,
+) -> Result<String, String> {
+    let mut pairs = vec![
+        ("list", list_id(&settings.list)?),
+        ("number", settings.config.number.to_string()),
+        ("case", settings.config.case.to_string()),
+        ("separator", encode(&settings.config.separator)),
+    ];
+    if let Some(order) = output.order {
+        pairs.push(("order", order.to_string()));
+    }
+    if let Some(mutate) = output.mutate {
+        pairs.push(("mutate", mutate.to_string()));
+    }
+    if let Some(symbols) = &output.embed_symbol {
+        pairs.push(("embed-symbol", encode(symbols)));
+    }
+    if output.force {
+        pairs.push(("force", String::new()));
+    }
+    if output.digits_as_words {
+        pairs.push(("digits-as-words", String::new()));
+    }
+    if let Some(regex) = &output.match_regex {
+        pairs.push(("match-regex", encode(regex)));
+    }
+    if !output.mix_languages.is_empty() {
+        let ids = output
+            .mix_languages
+            .iter()
+            .map(|list| list_id(list).map(|id| encode(&id)))
+            .collect::<Result<Vec<_>, _>>()?;
+        pairs.push(("mix-languages", ids.join("+")));
+    }
+    if output.rhyme {
+        pairs.push(("rhyme", String::new()));
+    }
+    if output.mobile {
+        pairs.push(("mobile", String::new()));
+    }
+    if let Some(layout) = output.easy_to_type {
+        let name = layout.to_possible_value().expect("layouts have names");
+        pairs.push(("easy-to-type", name.get_name().to_string()));
+    }
+    if let Some(seed) = seed {
+        pairs.push(("seed", seed.iter().map(|b| format!("{:02x}", b)).collect()));
+    }
+
+    let mut recipe = format!("{}:", VERSION);
+    for (i, (key, value)) in pairs.iter().enumerate() {
+        if i > 0 {
+            recipe.push(',');
+        }
+        recipe += key;
+        if !value.is_empty() {
+            let _ = write!(recipe, "={}", value);
+        }
+    }
+    Ok(recipe)
+}
+
+/// Read `recipe` into `output`, and return the settings it holds along with
+/// its seed, if it has one.
+pub fn read(
+    recipe: &str,
+    output: &mut Output,
+) -> Result<(Settings, Option<[u8; SEED_LEN]>), String> {
+    let Some(pairs) = recipe
+        .strip_prefix(VERSION)
+        .and_then(|r| r.strip_prefix(':'))
+    else {
+        return Err(match recipe.split_once(':') {
+            Some((version, _)) if version.starts_with("xk") => format!(
+                "recipes of version {} are newer than this xkpass, which reads {}",
+                version, VERSION
+            ),
+            _ => format!("{:?} isn't a recipe", recipe),
+        });
+    };
+
+    let (mut list, mut number, mut case, mut separator, mut seed) = (None, None, None, None, None);
+    for pair in pairs.split(',') {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let invalid = |err: String| format!("recipe: {}: {}", key, err);
+        match key {
+            "list" => list = Some(check_list(value).map_err(invalid)?),
+            "number" => number = Some(parse_number(value).map_err(invalid)?),
+            "case" => case = Some(value.parse::<Case>().map_err(|e| invalid(e.to_string()))?),
+            "separator" => separator = Some(decode(value).map_err(invalid)?),
+            "order" => {
+                output.order = Some(value.parse::<Order>().map_err(|e| invalid(e.to_string()))?)
+            }
+            "mutate" => output.mutate = Some(parse_number(value).map_err(invalid)?),
+            "embed-symbol" => output.embed_symbol = Some(decode(value).map_err(invalid)?),
+            "force" => output.force = true,
+            "digits-as-words" => output.digits_as_words = true,
+            "match-regex" => output.match_regex = Some(decode(value).map_err(invalid)?),
+            "mix-languages" => {
+                output.mix_languages = value
+                    .split('+')
+                    .map(|id| decode(id).and_then(|id| check_list(&id)))
+                    .collect::<Result<_, _>>()
+                    .map_err(invalid)?
+            }
+            "rhyme" => output.rhyme = true,
+            "mobile" => output.mobile = true,
+            "easy-to-type" => {
+                output.easy_to_type = Some(Layout::from_str(value, false).map_err(invalid)?)
+            }
+            "seed" => seed = Some(parse_seed(value).map_err(invalid)?),
+            _ => return Err(format!("recipe: unknown setting {:?}", key)),
+        }
+    }
+
+    let missing = |key| format!("recipe: {} is missing", key);
+    let settings = Settings::new(
+        number.ok_or_else(|| missing("number"))?,
+        separator.ok_or_else(|| missing("separator"))?,
+        list.ok_or_else(|| missing("list"))?,
+        case.ok_or_else(|| missing("case"))?,
+    );
+    Ok((settings, seed))
+}
+
+/// The seed of the file of entropy at `path`: the SHA-256 of its bytes,
+/// which must hold at least 256 bits of entropy, as 100 dice rolls do.
+pub fn read_seed_file(path: &Path) -> io::Result<[u8; SEED_LEN]> {
+    let bytes = fs::read(path)?;
+    if bytes.len() < SEED_LEN {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "{} bytes is too short to hold the {} bits of a seed",
+                bytes.len(),
+                8 * SEED_LEN
+            ),
+        ));
+    }
+    Ok(Sha256::digest(&bytes).into())
+}
+
+/// The generator of a recipe with a seed.
+pub fn rng(seed: [u8; SEED_LEN]) -> ChaCha20Rng {
+    ChaCha20Rng::from_seed(seed)
+}
+
+/// The name of `list`, followed by a digest of its words for the user's own lists.
+fn list_id(list: &ListName) -> Result<String, String> {
+    match list {
+        ListName::Embedded(_) => Ok(list.to_string()),
+        ListName::Custom(_) => Ok(format!("{}@{}", list, digest(list)?)),
+        ListName::Stdin => Ok(String::from("-")),
+    }
+}
+
+/// The list of `id`, as long as its words haven't changed since.
+fn check_list(id: &str) -> Result<ListName, String> {
+    let (name, expected) = match id.split_once('@') {
+        Some((name, digest)) => (name, Some(digest)),
+        None => (id, None),
+    };
+    let list: ListName = name.parse()?;
+    if let Some(expected) = expected {
+        if digest(&list)? != expected {
+            return Err(format!(
+                "the words of the {} list changed since the recipe was written",
+                list
+            ));
+        }
+    }
+    Ok(list)
+}
+
+/// The first 8 bytes, in hex, of the SHA-256 of the words of `list`, one per line.
+fn digest(list: &ListName) -> Result<String, String> {
+    let source = list.open().map_err(|err| format!("{}: {}", list, err))?;
+    let mut hasher = Sha256::new();
+    for word in source.iter() {
+        hasher.update(word.as_bytes());
+        hasher.update(b"\n");
+    }
+    Ok(hasher.finalize()[..8]
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect())
+}
+
+fn parse_number(value: &str) -> Result<usize, String> {
+    match value.parse() {
+        Ok(0) | Err(_) => Err(format!("{:?} isn't a positive number", value)),
+        Ok(number) => Ok(number),
+    }
+}
+
+fn parse_seed(value: &str) -> Result<[u8; SEED_LEN], String> {
+    let invalid = || format!("the seed must be {} hex digits", 2 * SEED_LEN);
+    if value.len() != 2 * SEED_LEN || !value.is_ascii() {
+        return Err(invalid());
+    }
+    let mut seed = [0; SEED_LEN];
+    for (byte, hex) in seed.iter_mut().zip(value.as_bytes().chunks(2)) {
+        let hex = std::str::from_utf8(hex).map_err(|_| invalid())?;
+        *byte = u8::from_str_radix(hex, 16).map_err(|_| invalid())?;
+    }
+    Ok(seed)
+}
+
+/// `value` with every byte but letters, digits and `-._~` percent-encoded,
+/// which leaves the commas, equal signs and plus signs of recipes to them.
+pub fn encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+            encoded.push(char::from(byte));
+        } else {
+            let _ = write!(encoded, "%{:02X}", byte);
+        }
+    }
+    encoded
+}
+
+fn decode(value: &str) -> Result<String, String> {
+    let invalid = || format!("invalid percent-encoding in {:?}", value);
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut rest = value.as_bytes();
+    while let Some((&byte, after)) = rest.split_first() {
+        if byte == b'%' {
+            let hex = after.get(..2).ok_or_else(invalid)?;
+            let hex = std::str::from_utf8(hex).map_err(|_| invalid())?;
+            bytes.push(u8::from_str_radix(hex, 16).map_err(|_| invalid())?);
+            rest = &after[2..];
+        } else {
+            bytes.push(byte);
+            rest = after;
+        }
+    }
+    String::from_utf8(bytes).map_err(|_| invalid())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Cli;
+    use clap::Parser;
+
+    #[test]
+    fn encodes_values() {
+        for value in [" ", "a,b=c+d", "%41", "été", "^.{12,28}$"] {
+            let encoded = encode(value);
+            assert!(!encoded.contains([',', '=', '+', ':']));
+            assert_eq!(decode(&encoded).unwrap(), value);
+        }
+        assert_eq!(encode(" -"), "%20-");
+        assert!(decode("%4").is_err());
+        assert!(decode("%zz").is_err());
+    }
+
+    #[test]
+    fn replays_settings_and_seed() {
+        let cli = Cli::parse_from(["xkpass", "--order", "length", "--mutate", "2", "--rhyme"]);
+        let settings = Settings::new(4, String::from("."), "short2".parse().unwrap(), Case::Mixed);
+        let recipe = write(&settings, &cli.output, Some(&[7; SEED_LEN])).unwrap();
+        assert!(recipe.starts_with("xk1:list=short2,number=4,case=mixed,separator=.,"));
+
+        let generate = || {
+            let mut output = Cli::parse_from(["xkpass"]).output;
+            let (settings, seed) = read(&recipe, &mut output).unwrap();
+            assert_eq!(write(&settings, &output, seed.as_ref()).unwrap(), recipe);
+            let (password, _, _) = settings.generate(&output, &mut rng(seed.unwrap())).unwrap();
+            password
+        };
+        assert_eq!(generate().expose(), generate().expose());
+    }
+
+    #[test]
+    fn reads_seed_files() {
+        let path = std::env::temp_dir().join(format!("xkpass-seed-{}", std::process::id()));
+        fs::write(&path, "6 3 1 4 ".repeat(25)).unwrap();
+        let seed = read_seed_file(&path).unwrap();
+        assert_eq!(read_seed_file(&path).unwrap(), seed);
+        fs::write(&path, "6 3 1 4").unwrap();
+        assert!(read_seed_file(&path).is_err());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn rejects_other_versions() {
+        let mut output = Cli::parse_from(["xkpass"]).output;
+        let err = read("xk2:list=long", &mut output).unwrap_err();
+        assert!(err.contains("version xk2"), "{}", err);
+        assert!(read("list=long", &mut output).is_err());
+        assert!(read("xk1:list=long,number=3,case=lower", &mut output)
+            .unwrap_err()
+            .contains("separator is missing"));
+        assert!(read("xk1:list=long,colour=red", &mut output).is_err());
+        assert!(read("xk1:seed=00", &mut output).is_err());
+    }
+}
@@ -0,0 +1,213 @@
+//! Writing the results of the modes on stdout in the format chosen with
+//! `--format`.
+//!
+//! A mode hands over its results as records, each with the line it stands
+//! for in plain text. Plain text also has lines of its own, such as table
+//! headings, which the other formats leave out. JSON is an array of the
+//! records, NDJSON has one record per line and CSV one row per record under
+//! a header of the field names. HTML is a printable page with a table like
+//! the CSV one, except for a single password, which gets a sheet of its own.
+//! A Kubernetes Secret, a `.env` line and a shell `export` only ever hold a
+//! single password, which `generate` writes itself.
+
+use crate::sheet;
+use clap::ValueEnum;
+use serde::Serialize;
+use std::{
+    fmt::Display,
+    io::{self, Write},
+};
+
+/// Formats the results can be written in.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Plain,
+    Json,
+    Csv,
+    Ndjson,
+    Html,
+    K8sSecret,
+    Dotenv,
+    Sh,
+}
+
+impl Format {
+    /// The name of the format, as given to `--format`.
+    pub fn name(self) -> &'static str {
+        self.to_possible_value()
+            .expect("formats have names")
+            .get_name()
+    }
+
+    /// Whether the format holds a single generated password rather than records.
+    pub fn single_password(self) -> bool {
+        matches!(self, Format::K8sSecret | Format::Dotenv | Format::Sh)
+    }
+}
+
+/// A generated password, the record of `generate`, with the recipe to
+/// generate passwords the same way again and the key of `--derive-key`.
+#[derive(Serialize, Debug)]
+pub struct Generated<'a> {
+    pub password: &'a str,
+    pub recipe: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub salt: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kdf: Option<&'a str>,
+}
+
+/// A word of a list, the record of `dump` and `words`.
+#[derive(Serialize, Debug)]
+pub struct Word<'a> {
+    pub word: &'a str,
+}
+
+/// Writes records to `W` in one format.
+pub struct Renderer<W: Write> {
+    sink: Sink<W>,
+}
+
+enum Sink<W: Write> {
+    Plain(W),
+    /// The number of records written so far, to open and separate the array.
+    Json(W, usize),
+    Csv(Box<csv::Writer<W>>),
+    Ndjson(W),
+    /// The number of records written so far, to write the header before the first.
+    Html(W, usize),
+}
+
+impl<W: Write> Renderer<W> {
+    pub fn new(format: Format, out: W) -> Self {
+        let sink = match format {
+            Format::Plain => Sink::Plain(out),
+            Format::Json => Sink::Json(out, 0),
+            Format::Csv => Sink::Csv(Box::new(csv::Writer::from_writer(out))),
+            Format::Ndjson => Sink::Ndjson(out),
+            Format::Html => Sink::Html(out, 0),
+            Format::K8sSecret | Format::Dotenv | Format::Sh => {
+                unreachable!("--format {:?} isn't for records", format)
+            }
+        };
+        Self { sink }
+    }
+
+    /// Write `text` as is in plain text, and nothing in the other formats.
+    pub fn plain(&mut self, text: impl Display) -> io::Result<()> {
+        match &mut self.sink {
+            Sink::Plain(out) => write!(out, "{}", text),
+            _ => Ok(()),
+        }
+    }
+
+    /// Write `record`, as the line `line` in plain text.
+    pub fn record<T: Serialize>(&mut self, record: &T, line: impl Display) -> io::Result<()> {
+        match &mut self.sink {
+            Sink::Plain(out) => writeln!(out, "{}", line),
+            Sink::Json(out, written) => {
+                out.write_all(if *written == 0 { b"[" } else { b"," })?;
+                serde_json::to_writer(&mut *out, record)?;
+                *written += 1;
+                Ok(())
+            }
+            Sink::Csv(out) => out.serialize(record).map_err(io::Error::other),
+            Sink::Ndjson(out) => {
+                serde_json::to_writer(&mut *out, record)?;
+                writeln!(out)
+            }
+            Sink::Html(out, written) => {
+                let serde_json::Value::Object(fields) = serde_json::to_value(record)? else {
+                    return Err(io::Error::other("records are objects"));
+                };
+                if *written == 0 {
+                    write!(out, "{}<table>\n<tr>", sheet::head("xkpass"))?;
+                    for name in fields.keys() {
+                        write!(out, "<th>{}</th>", sheet::escape(name))?;
+                    }
+                    writeln!(out, "</tr>")?;
+                }
+                write!(out, "<tr>")?;
+                for value in fields.values() {
+                    let value = match value {
+                        serde_json::Value::String(text) => sheet::escape(text),
+                        serde_json::Value::Null => String::new(),
+                        value => sheet::escape(&value.to_string()),
+                    };
+                    write!(out, "<td>{}</td>", value)?;
+                }
+                *written += 1;
+                writeln!(out, "</tr>")
+            }
+        }
+    }
+
+    /// Close what the format needs closed and flush the output.
+    pub fn finish(self) -> io::Result<()> {
+        match self.sink {
+            Sink::Plain(mut out) | Sink::Ndjson(mut out) => out.flush(),
+            Sink::Json(mut out, written) => {
+                out.write_all(if written == 0 { b"[]\n" } else { b"]\n" })?;
+                out.flush()
+            }
+            Sink::Csv(mut out) => out.flush(),
+            Sink::Html(mut out, written) => {
+                if written == 0 {
+                    write!(out, "{}", sheet::head("xkpass"))?;
+                } else {
+                    writeln!(out, "</table>")?;
+                }
+                write!(out, "{}", sheet::FOOT)?;
+                out.flush()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize)]
+    struct Scored<'a> {
+        word: &'a str,
+        bits: f64,
+    }
+
+    fn render(format: Format, words: &[&str]) -> String {
+        let mut out = Vec::new();
+        let mut renderer = Renderer::new(format, &mut out);
+        renderer.plain("WORDS\n").unwrap();
+        for &word in words {
+            let record = Scored { word, bits: 1.5 };
+            renderer.record(&record, word.to_uppercase()).unwrap();
+        }
+        renderer.finish().unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn writes_every_format() {
+        let words = ["acid", "a,b"];
+        assert_eq!(render(Format::Plain, &words), "WORDS\nACID\nA,B\n");
+        assert_eq!(
+            render(Format::Json, &words),
+            "[{\"word\":\"acid\",\"bits\":1.5},{\"word\":\"a,b\",\"bits\":1.5}]\n"
+        );
+        assert_eq!(render(Format::Json, &[]), "[]\n");
+        assert_eq!(
+            render(Format::Csv, &words),
+            "word,bits\nacid,1.5\n\"a,b\",1.5\n"
+        );
+        assert_eq!(
+            render(Format::Ndjson, &words),
+            "{\"word\":\"acid\",\"bits\":1.5}\n{\"word\":\"a,b\",\"bits\":1.5}\n"
+        );
+        let html = render(Format::Html, &["a<b"]);
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<tr><th>word</th><th>bits</th></tr>\n<tr><td>a&lt;b</td><td>1.5</td></tr>\n</table>\n</body>"));
+        assert!(render(Format::Html, &[]).ends_with(sheet::FOOT));
+    }
+}
@@ -0,0 +1,152 @@
+//! Generation requests shared by the modes that talk JSON to other programs.
+
+use serde::Deserialize;
+use xkpass::{Case, Config, List, Password};
+
+/// Largest number of words a single request may ask for.
+const MAX_WORDS: usize = 64;
+
+/// Longest separator a single request may ask for, in characters.
+const MAX_SEPARATOR_LEN: usize = 16;
+
+/// Settings for a single password, as sent by a client.
+///
+/// Missing fields fall back to the options `xkpass` was started with.
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct GenerateRequest {
+    number: Option<usize>,
+    separator: Option<String>,
+    list: Option<String>,
+    case: Option<String>,
+}
+
+impl GenerateRequest {
+    /// Parse a JSON request, treating a blank one as a request with no settings.
+    pub fn from_json(json: &[u8]) -> Result<Self, String> {
+        if json.iter().all(u8::is_ascii_whitespace) {
+            return Ok(Self::default());
+        }
+
+        serde_json::from_slice(json).map_err(|err| format!("invalid request: {}", err))
+    }
+
+    /// Parse a request written as `key=value` pairs separated by whitespace,
+    /// such as `number=4 list=short1`.
+    pub fn from_pairs(line: &str) -> Result<Self, String> {
+        let mut request = Self::default();
+        for pair in line.split_whitespace() {
+            let (key, value) = pair
+                .split_once('=')
+                .ok_or_else(|| format!("expected key=value, found {:?}", pair))?;
+            let value = value.to_string();
+            match key {
+                "number" => {
+                    request.number = Some(
+                        value
+                            .parse()
+                            .map_err(|_| format!("invalid number {:?}", value))?,
+                    )
+                }
+                "separator" => request.separator = Some(value),
+                "list" => request.list = Some(value),
+                "case" => request.case = Some(value),
+                _ => return Err(format!("unknown setting {:?}", key)),
+            }
+        }
+
+        Ok(request)
+    }
+
+    /// Validate the request, merge it with the defaults and generate the password.
+    pub fn generate(self, defaults: &Config) -> Result<String, String> {
+        let config = self.into_config(defaults)?;
+        xkpass::generate_password(config)
+            .map(Password::into_string)
+            .map_err(|err| err.to_string())
+    }
+
+    /// Validate the request and merge it with the defaults.
+    pub fn into_config(self, defaults: &Config) -> Result<Config, String> {
+        let number = self.number.unwrap_or(defaults.number);
+        if !(1..=MAX_WORDS).contains(&number) {
+            return Err(format!("number must be between 1 and {}", MAX_WORDS));
+        }
+
+        let separator = self.separator.unwrap_or_else(|| defaults.separator.clone());
+        if separator.chars().count() > MAX_SEPARATOR_LEN {
+            return Err(format!(
+                "separator must be at most {} characters long",
+                MAX_SEPARATOR_LEN
+            ));
+        }
+
+        let list = match self.list {
+            Some(list) => list.parse::<List>().map_err(|err| err.to_string())?,
+            None => defaults.list.clone(),
+        };
+
+        check_separator(&separator, &list)?;
+
+        let case = match self.case {
+            Some(case) => case.parse::<Case>().map_err(|err| err.to_string())?,
+            None => defaults.case.clone(),
+        };
+
+        Ok(Config {
+            number,
+            separator,
+            list,
+            case,
+        })
+    }
+}
+
+/// Refuse `separator` if it appears inside a word of `list`, since the words
+/// of the password couldn't be told apart. Requests can't force it.
+pub fn check_separator(separator: &str, list: &List) -> Result<(), String> {
+    match crate::ambiguous_word(list.as_slice().iter().copied(), separator) {
+        Some(word) => Err(format!(
+            "the separator {:?} appears in words of the {} list such as {:?}, \
+             so the words of the password can't be told apart",
+            separator, list, word
+        )),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_pairs() {
+        let request = GenerateRequest::from_pairs("number=4  list=short1 separator=.").unwrap();
+        let config = request.into_config(&Config::default()).unwrap();
+        assert_eq!(config.number, 4);
+        assert_eq!(config.separator, ".");
+        assert!(matches!(config.list, List::Short1));
+        assert!(matches!(config.case, Case::Lower));
+
+        assert!(GenerateRequest::from_pairs("number=four").is_err());
+        assert!(GenerateRequest::from_pairs("colour=red").is_err());
+        assert!(GenerateRequest::from_pairs("short1").is_err());
+    }
+
+    #[test]
+    fn refuses_ambiguous_separators() {
+        let request = GenerateRequest::from_pairs("separator=- list=long").unwrap();
+        let err = request.generate(&Config::default()).unwrap_err();
+        assert!(err.contains("can't be told apart"), "{}", err);
+
+        let defaults = Config {
+            separator: "-".to_string(),
+            ..Config::default()
+        };
+        assert!(GenerateRequest::default().generate(&defaults).is_err());
+        assert!(GenerateRequest::from_pairs("separator=.")
+            .unwrap()
+            .generate(&defaults)
+            .is_ok());
+    }
+}
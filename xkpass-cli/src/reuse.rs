@@ -0,0 +1,111 @@
+//! Making sure a new password isn't one already in use, by checking it
+//! against the passwords exported from a password manager.
+//!
+//! Bitwarden, 1Password, KeePassXC, LastPass and the browsers all export CSV
+//! with a header, the passwords in a column named `password`, or
+//! `login_password` for Bitwarden. Only the SHA-256 of each password is kept
+//! once read, and the new password is compared with those.
+
+use sha2::{Digest, Sha256};
+use std::{collections::HashSet, path::Path};
+use xkpass::{Config, Password};
+
+/// Passwords drawn before giving up on one that isn't in the export, which
+/// only happens with settings that leave very few passwords to draw from.
+const TRIES: usize = 10;
+
+/// Read the passwords of the export at `path` and generate a password with
+/// `config` that isn't one of them.
+pub fn run(config: Config, path: &Path) -> Result<(), String> {
+    let hashes = read(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+    let password = generate(&config, &hashes)?;
+    eprintln!(
+        "checked against the passwords of {}, {} in all",
+        path.display(),
+        hashes.len()
+    );
+    println!("{}", password.expose());
+    Ok(())
+}
+
+/// A password generated with `config` whose hash isn't in `hashes`.
+fn generate(config: &Config, hashes: &HashSet<[u8; 32]>) -> Result<Password, String> {
+    for _ in 0..TRIES {
+        let password = xkpass::generate_password(config.clone()).map_err(|err| err.to_string())?;
+        if !hashes.contains(&hash(password.expose())) {
+            return Ok(password);
+        }
+    }
+    Err(format!(
+        "the {} passwords generated were all in use already, the settings leave too few passwords",
+        TRIES
+    ))
+}
+
+/// The hashes of the passwords of the CSV export at `path`.
+fn read(path: &Path) -> Result<HashSet<[u8; 32]>, String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_path(path)
+        .map_err(|err| err.to_string())?;
+    let headers = reader.headers().map_err(|err| err.to_string())?;
+    let column = headers
+        .iter()
+        .position(|name| {
+            let name = name.trim().to_ascii_lowercase();
+            name == "password" || name.ends_with("_password")
+        })
+        .ok_or("no password column, the export should have a header naming it password")?;
+
+    let mut hashes = HashSet::new();
+    for record in reader.records() {
+        let record = record.map_err(|err| err.to_string())?;
+        if let Some(password) = record.get(column).filter(|password| !password.is_empty()) {
+            hashes.insert(hash(password));
+        }
+    }
+    Ok(hashes)
+}
+
+fn hash(password: &str) -> [u8; 32] {
+    Sha256::digest(password.as_bytes()).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use xkpass::List;
+
+    #[test]
+    fn reads_exports() {
+        let path = std::env::temp_dir().join(format!("xkpass-reuse-{}.csv", std::process::id()));
+        fs::write(
+            &path,
+            "folder,type,name,login_uri,login_username,login_password\n\
+             ,login,Example,https://example.com,me,\"correct, horse\"\n\
+             ,note,Wifi,,,\n",
+        )
+        .unwrap();
+        let hashes = read(&path).unwrap();
+        assert_eq!(hashes.len(), 1);
+        assert!(hashes.contains(&hash("correct, horse")));
+
+        fs::write(&path, "name,secret\nExample,hunter2\n").unwrap();
+        assert!(read(&path).unwrap_err().contains("no password column"));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn refuses_passwords_in_use() {
+        // every password of a single word of the long list is in use
+        let config = Config {
+            number: 1,
+            separator: String::new(),
+            ..Config::default()
+        };
+        let taken: HashSet<[u8; 32]> = List::Long.as_slice().iter().map(|w| hash(w)).collect();
+        assert!(generate(&config, &taken).is_err());
+        assert!(generate(&config, &HashSet::new()).is_ok());
+    }
+}
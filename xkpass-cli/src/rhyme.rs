@@ -0,0 +1,121 @@
+//! Passwords whose words all rhyme.
+//!
+//! Words rhyme when they share a rime: their last group of vowels and the
+//! letters after it, a final silent "e" aside. The table of rimes is built
+//! from the spelling of the words, which gets most English rhymes right
+//! without shipping a pronouncing dictionary.
+//!
+//! A rime is picked with a weight of the number of passwords its words make,
+//! so that every rhyming password is as likely as any other and the entropy
+//! is that of all of them together.
+
+use crate::explain::Row;
+use rand::{distributions::WeightedIndex, prelude::Distribution, Rng};
+use std::collections::BTreeMap;
+
+/// The words of a list grouped by rime, keeping the groups large enough for a password.
+pub struct Rhymes<'a> {
+    groups: Vec<Vec<&'a str>>,
+    weights: WeightedIndex<f64>,
+    bits: f64,
+}
+
+impl<'a> Rhymes<'a> {
+    /// Group `words` by rime, for passwords of `number` words.
+    pub fn new(words: &[&'a str], number: usize) -> Result<Self, String> {
+        let mut by_rime: BTreeMap<String, Vec<&'a str>> = BTreeMap::new();
+        for &word in words {
+            by_rime.entry(rime(word)).or_default().push(word);
+        }
+        let groups: Vec<Vec<&str>> = by_rime
+            .into_values()
+            .filter(|group| group.len() >= number)
+            .collect();
+
+        let entropies: Vec<f64> = groups
+            .iter()
+            .map(|group| xkpass::word_entropy(group.len(), number))
+            .collect();
+        let max = entropies.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let weights: Vec<f64> = entropies.iter().map(|bits| (bits - max).exp2()).collect();
+        let bits = max + weights.iter().sum::<f64>().log2();
+        let weights = WeightedIndex::new(&weights)
+            .map_err(|_| format!("no {} words of the list rhyme", number))?;
+
+        Ok(Self {
+            groups,
+            weights,
+            bits,
+        })
+    }
+
+    /// The words of a rime picked at random.
+    pub fn pick(&self, rng: &mut impl Rng) -> &[&'a str] {
+        &self.groups[self.weights.sample(rng)]
+    }
+
+    /// The bits lost to rhyming, compared to `number` words out of the whole list of `pool`.
+    pub fn row(&self, pool: usize, number: usize) -> Row {
+        (
+            "rhyme",
+            format!(
+                "every word ends alike, one of {} endings",
+                self.groups.len()
+            ),
+            self.bits - xkpass::word_entropy(pool, number),
+        )
+    }
+}
+
+/// The last group of vowels of `word` and the letters after it.
+fn rime(word: &str) -> String {
+    let chars: Vec<char> = word
+        .chars()
+        .filter(|c| c.is_alphabetic())
+        .flat_map(char::to_lowercase)
+        .collect();
+    let vowel = |c: &char| "aeiouy".contains(*c);
+
+    // a final "e" after a consonant is silent, as long as another vowel is sounded
+    let mut searched = &chars[..];
+    if let [rest @ .., consonant, 'e'] = searched {
+        if !vowel(consonant) && rest.iter().any(vowel) {
+            searched = &chars[..chars.len() - 1];
+        }
+    }
+    let Some(last) = searched.iter().rposition(vowel) else {
+        return chars.into_iter().collect();
+    };
+    let start = searched[..last]
+        .iter()
+        .rposition(|c| !vowel(c))
+        .map_or(0, |i| i + 1);
+
+    chars[start..].iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_rimes() {
+        let rimes: Vec<String> = ["cat", "table", "crane", "free", "rain", "Drop-Down", "hmm"]
+            .into_iter()
+            .map(rime)
+            .collect();
+        assert_eq!(rimes, ["at", "able", "ane", "ee", "ain", "own", "hmm"]);
+    }
+
+    #[test]
+    fn picks_rhyming_words() {
+        let words = ["cat", "hat", "bat", "dog", "log", "fish"];
+        let rhymes = Rhymes::new(&words, 2).unwrap();
+        // 3 * 2 orders of two words ending in "at", 2 * 1 ending in "og"
+        assert_eq!(rhymes.bits, 3.0);
+        let picked = rhymes.pick(&mut rand::thread_rng());
+        assert!(picked == ["cat", "hat", "bat"] || picked == ["dog", "log"]);
+
+        assert!(Rhymes::new(&words, 4).is_err());
+    }
+}
@@ -0,0 +1,263 @@
+//! Line-delimited [JSON-RPC 2.0](https://www.jsonrpc.org/specification) over
+//! stdin and stdout, for editors and GUI wrappers running `xkpass` as a child process.
+//!
+//! Methods:
+//!
+//! - `generate`: takes the usual generation settings and returns `{"password": ...}`.
+//! - `generate_many`: takes the same settings plus `count`, sends every password
+//!   as a `password` notification as soon as it is generated and finally
+//!   returns `{"count": ...}`.
+
+use crate::request::GenerateRequest;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::io::{self, BufRead, Write};
+use xkpass::{Config, Password};
+
+/// Largest number of passwords a single `generate_many` call may ask for.
+const MAX_COUNT: usize = 10_000;
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// A JSON-RPC request; requests without an `id` are notifications.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct RpcRequest {
+    jsonrpc: String,
+    method: String,
+    #[serde(default)]
+    params: Value,
+    #[serde(default)]
+    id: Option<Value>,
+}
+
+/// Answer requests read from stdin until it is closed.
+pub fn run(defaults: Config) -> io::Result<()> {
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+
+    for line in stdin.lock().lines() {
+        let line = line?;
+        if !line.trim().is_empty() {
+            answer(&line, &defaults, &mut out)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle a single request line, writing every resulting message to `out`.
+fn answer<W: Write>(line: &str, defaults: &Config, out: &mut W) -> io::Result<()> {
+    let request = match serde_json::from_str::<Value>(line) {
+        Ok(request) => request,
+        Err(err) => return send(out, error(Value::Null, PARSE_ERROR, err.to_string())),
+    };
+
+    // the id is echoed back even when the rest of the request is invalid
+    let id = request.get("id").cloned().unwrap_or(Value::Null);
+    let request = match serde_json::from_value::<RpcRequest>(request) {
+        Ok(request) if request.jsonrpc == "2.0" => request,
+        Ok(_) => return send(out, error(id, INVALID_REQUEST, "jsonrpc must be \"2.0\"")),
+        Err(err) => return send(out, error(id, INVALID_REQUEST, err.to_string())),
+    };
+
+    // notifications get no answer, and the passwords they would generate
+    // could never be matched to a request
+    let id = match request.id {
+        Some(id) => id,
+        None => return Ok(()),
+    };
+
+    let result = match request.method.as_str() {
+        "generate" => generate(request.params, defaults),
+        "generate_many" => generate_many(request.params, defaults, &id, out),
+        _ => Err(RpcError::new(METHOD_NOT_FOUND, "method not found")),
+    };
+
+    match result {
+        Ok(result) => send(out, json!({ "jsonrpc": "2.0", "id": id, "result": result })),
+        Err(RpcError::Rpc(code, message)) => send(out, error(id, code, message)),
+        Err(RpcError::Io(err)) => Err(err),
+    }
+}
+
+/// Generate a single password.
+fn generate(params: Value, defaults: &Config) -> Result<Value, RpcError> {
+    let config = parse_settings(params, defaults)?;
+    Ok(json!({ "password": generate_password(config)? }))
+}
+
+/// Generate `count` passwords, sending each one as a notification.
+fn generate_many<W: Write>(
+    mut params: Value,
+    defaults: &Config,
+    id: &Value,
+    out: &mut W,
+) -> Result<Value, RpcError> {
+    let count = match params
+        .as_object_mut()
+        .and_then(|params| params.remove("count"))
+    {
+        Some(count) => count
+            .as_u64()
+            .filter(|count| (1..=MAX_COUNT as u64).contains(count))
+            .ok_or_else(|| {
+                RpcError::new(
+                    INVALID_PARAMS,
+                    format!("count must be between 1 and {}", MAX_COUNT),
+                )
+            })?,
+        None => return Err(RpcError::new(INVALID_PARAMS, "count is required")),
+    };
+    let config = parse_settings(params, defaults)?;
+    let passwords = xkpass::iter_passwords(&config)
+        .map_err(|err| RpcError::new(INVALID_PARAMS, err.to_string()))?;
+
+    for (index, password) in passwords.take(count as usize).enumerate() {
+        let password = password.into_string();
+        let notification = json!({
+            "jsonrpc": "2.0",
+            "method": "password",
+            "params": { "id": id, "index": index, "password": password },
+        });
+        send(out, notification).map_err(RpcError::Io)?;
+    }
+
+    Ok(json!({ "count": count }))
+}
+
+/// Turn the params of a request into generation settings.
+fn parse_settings(params: Value, defaults: &Config) -> Result<Config, RpcError> {
+    let request = match params {
+        Value::Null => GenerateRequest::default(),
+        params => serde_json::from_value::<GenerateRequest>(params)
+            .map_err(|err| RpcError::new(INVALID_PARAMS, err.to_string()))?,
+    };
+
+    request
+        .into_config(defaults)
+        .map_err(|err| RpcError::new(INVALID_PARAMS, err))
+}
+
+/// Generate a password, reporting failures as invalid params.
+fn generate_password(config: Config) -> Result<String, RpcError> {
+    xkpass::generate_password(config)
+        .map(Password::into_string)
+        .map_err(|err| RpcError::new(INVALID_PARAMS, err.to_string()))
+}
+
+/// Write a single message on its own line.
+fn send<W: Write>(out: &mut W, message: Value) -> io::Result<()> {
+    writeln!(out, "{}", message)?;
+    out.flush()
+}
+
+/// Build an error response.
+fn error(id: Value, code: i64, message: impl Into<String>) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message.into() },
+    })
+}
+
+/// Ways a method call can fail.
+enum RpcError {
+    /// Reported back to the client as an error object.
+    Rpc(i64, String),
+    /// Writing to stdout failed, there is nobody left to report to.
+    Io(io::Error),
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        RpcError::Rpc(code, message.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(line: &str) -> Vec<Value> {
+        let defaults = Config::default();
+        let mut out = Vec::new();
+        answer(line, &defaults, &mut out).unwrap();
+
+        String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn generates_password() {
+        let messages =
+            call(r#"{"jsonrpc": "2.0", "id": 7, "method": "generate", "params": {"number": 3}}"#);
+
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0]["id"], 7);
+        let password = messages[0]["result"]["password"].as_str().unwrap();
+        assert_eq!(password.split(' ').count(), 3);
+    }
+
+    #[test]
+    fn streams_many_passwords() {
+        let messages = call(
+            r#"{"jsonrpc": "2.0", "id": "a", "method": "generate_many", "params": {"count": 3, "separator": "+"}}"#,
+        );
+
+        assert_eq!(messages.len(), 4);
+        for (index, message) in messages[..3].iter().enumerate() {
+            assert_eq!(message["method"], "password");
+            assert_eq!(message["params"]["id"], "a");
+            assert_eq!(message["params"]["index"], index);
+            assert_eq!(
+                message["params"]["password"]
+                    .as_str()
+                    .unwrap()
+                    .split('+')
+                    .count(),
+                6
+            );
+        }
+        assert_eq!(messages[3]["result"]["count"], 3);
+    }
+
+    #[test]
+    fn reports_errors() {
+        let code = |line: &str| call(line)[0]["error"]["code"].clone();
+
+        assert_eq!(code("{"), PARSE_ERROR);
+        assert_eq!(
+            code(r#"{"jsonrpc": "1.0", "id": 1, "method": "generate"}"#),
+            INVALID_REQUEST
+        );
+        assert_eq!(
+            code(r#"{"jsonrpc": "2.0", "id": 1, "method": "guess"}"#),
+            METHOD_NOT_FOUND
+        );
+        assert_eq!(
+            code(r#"{"jsonrpc": "2.0", "id": 1, "method": "generate", "params": {"number": 0}}"#),
+            INVALID_PARAMS
+        );
+        assert_eq!(
+            code(r#"{"jsonrpc": "2.0", "id": 1, "method": "generate_many", "params": {}}"#),
+            INVALID_PARAMS
+        );
+    }
+
+    #[test]
+    fn stays_silent_for_notifications() {
+        assert!(call(r#"{"jsonrpc": "2.0", "method": "generate"}"#).is_empty());
+        assert!(
+            call(r#"{"jsonrpc": "2.0", "method": "generate_many", "params": {"count": 3}}"#)
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,151 @@
+//! Random digits between the words, in place of a fixed separator.
+//!
+//! `--separator digits` puts a fresh random digit between every pair of words
+//! and `--separator digits:2` a group of two, which satisfies rules asking for
+//! a numeral without tacking one onto the end of the password. Every digit is
+//! drawn on its own, so each boundary adds its bits to the entropy.
+//!
+//! The digits can be spelled out as words between spaces instead, such as
+//! `pouch forty-two grape`, for a password that stays alphabetic and can be
+//! read aloud. Each number is spelled one way only, so nothing is lost.
+
+use rand::Rng;
+use std::f64::consts::LOG2_10;
+use xkpass::Password;
+
+/// Separator standing for random digits.
+const DIGITS: &str = "digits";
+
+/// Most digits between two words.
+const MAX_DIGITS: usize = 2;
+
+/// The number of random digits to put between the words if `separator` asks for them.
+pub fn digits(separator: &str) -> Option<usize> {
+    match separator.strip_prefix(DIGITS)? {
+        "" => Some(1),
+        len => match len.strip_prefix(':')?.parse() {
+            Ok(len @ 1..=MAX_DIGITS) => Some(len),
+            _ => None,
+        },
+    }
+}
+
+/// Bits of entropy added by `len` random digits between every pair of `number` words.
+pub fn entropy(number: usize, len: usize) -> f64 {
+    LOG2_10 * (number.saturating_sub(1) * len) as f64
+}
+
+/// `password` with fresh random digits, drawn from `rng`, between every
+/// pair of its words if `separator` asks for them, spelled out between
+/// spaces with `spelled`, as it is otherwise.
+pub fn apply(password: Password, separator: &str, spelled: bool, rng: &mut impl Rng) -> Password {
+    let Some(len) = digits(separator) else {
+        return password;
+    };
+    let bits = LOG2_10 * len as f64;
+    password.separate_with(bits, rng, |rng| {
+        let number = rng.gen_range(0..10usize.pow(len as u32));
+        if spelled {
+            format!(" {} ", spell(number, len))
+        } else {
+            format!("{:0len$}", number)
+        }
+    })
+}
+
+/// `number` of `len` digits in words, such as "forty-two", or "zero-seven"
+/// for the leading zero of 07 so that it isn't taken for 7.
+fn spell(number: usize, len: usize) -> String {
+    const ONES: [&str; 20] = [
+        "zero",
+        "one",
+        "two",
+        "three",
+        "four",
+        "five",
+        "six",
+        "seven",
+        "eight",
+        "nine",
+        "ten",
+        "eleven",
+        "twelve",
+        "thirteen",
+        "fourteen",
+        "fifteen",
+        "sixteen",
+        "seventeen",
+        "eighteen",
+        "nineteen",
+    ];
+    const TENS: [&str; 10] = [
+        "", "", "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+    ];
+
+    match (number / 10, number % 10) {
+        (0, ones) if len == 2 => format!("zero-{}", ONES[ones]),
+        (0 | 1, _) => ONES[number].to_string(),
+        (tens, 0) => TENS[tens].to_string(),
+        (tens, ones) => format!("{}-{}", TENS[tens], ONES[ones]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use xkpass::Config;
+
+    #[test]
+    fn parses_digits() {
+        assert_eq!(digits("digits"), Some(1));
+        assert_eq!(digits("digits:2"), Some(2));
+        assert_eq!(digits("digits:3"), None);
+        assert_eq!(digits("digits:"), None);
+        assert_eq!(digits("-"), None);
+    }
+
+    #[test]
+    fn puts_digits_between_words() {
+        let config = Config {
+            number: 3,
+            separator: String::from("digits:2"),
+            ..Config::default()
+        };
+        let password = xkpass::generate_password(config).unwrap();
+        let before = password.entropy();
+        let password = apply(password, "digits:2", false, &mut rand::thread_rng());
+
+        assert_eq!(password.separators().len(), 2);
+        for separator in password.separators() {
+            assert!(separator.len() == 2 && separator.chars().all(|c| c.is_ascii_digit()));
+        }
+        assert!((password.entropy() - before - entropy(3, 2)).abs() < 1e-9);
+        assert_eq!(
+            apply(password.clone(), " ", false, &mut rand::thread_rng()).expose(),
+            password.expose()
+        );
+
+        let spelled = apply(password, "digits", true, &mut rand::thread_rng());
+        assert!(spelled.expose().chars().all(|c| !c.is_ascii_digit()));
+        assert_eq!(spelled.expose().split(' ').count(), 5);
+    }
+
+    #[test]
+    fn spells_numbers() {
+        let spelled: Vec<String> = [(0, 1), (7, 2), (13, 2), (40, 2), (42, 2), (99, 2)]
+            .into_iter()
+            .map(|(number, len)| spell(number, len))
+            .collect();
+        assert_eq!(
+            spelled,
+            [
+                "zero",
+                "zero-seven",
+                "thirteen",
+                "forty",
+                "forty-two",
+                "ninety-nine"
+            ]
+        );
+    }
+}
@@ -0,0 +1,286 @@
+//! A small JSON API over HTTP, so other programs can request passwords
+//! without shelling out to `xkpass`.
+//!
+//! Besides `POST /generate`, `GET /healthz` answers as long as the server is
+//! up and `GET /metrics` has the metrics of [`crate::metrics`] for Prometheus.
+//! Only `/generate` is behind the token and the rate limit of
+//! [`crate::access`], so that probes and scrapers need neither.
+//!
+//! On a loopback address, requests naming any other host than the loopback
+//! address itself are refused, so that a web page can't reach the API by
+//! pointing a DNS name of its own at 127.0.0.1.
+
+use crate::{
+    access::{Access, Denied},
+    metrics::Metrics,
+    request::GenerateRequest,
+};
+use serde_json::json;
+use std::{error::Error, io::Read, net::SocketAddr, time::Instant};
+use tiny_http::{Header, Method, Request, Response, Server};
+use xkpass::{Config, PasswordGenerator};
+
+/// Largest request body accepted, in bytes.
+const MAX_BODY_SIZE: usize = 16 * 1024;
+
+/// Listen on `addr` and answer requests allowed by `access` until the process is killed.
+pub fn serve(
+    addr: SocketAddr,
+    defaults: Config,
+    mut access: Access,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    if !addr.ip().is_loopback() && !access.has_token() {
+        eprintln!(
+            "xkpass: warning: {} is not a loopback address, passwords will be reachable from the network without a token",
+            addr.ip()
+        );
+    }
+
+    let server = Server::http(addr)?;
+    eprintln!("xkpass: listening on http://{}", server.server_addr());
+
+    // the port actually bound, when the OS picked it
+    let port = server
+        .server_addr()
+        .to_ip()
+        .map_or(addr.port(), |addr| addr.port());
+    let local_only = addr.ip().is_loopback().then_some(port);
+
+    let mut metrics = Metrics::default();
+    for request in server.incoming_requests() {
+        respond(request, local_only, &defaults, &mut access, &mut metrics);
+    }
+
+    Ok(())
+}
+
+/// Whether `host`, the Host header of a request, names the loopback address
+/// listening on `port`.
+fn is_local_host(host: Option<&str>, port: u16) -> bool {
+    let host = match host {
+        Some(host) => host,
+        None => return false,
+    };
+    ["127.0.0.1", "localhost", "[::1]"]
+        .iter()
+        .any(|name| host.eq_ignore_ascii_case(&format!("{}:{}", name, port)))
+}
+
+/// Read the body of a request, pass it to [`handle`] and send back the result.
+///
+/// With `local_only` set to the port listened on, requests for any other
+/// host are refused with 403.
+fn respond(
+    mut request: Request,
+    local_only: Option<u16>,
+    defaults: &Config,
+    access: &mut Access,
+    metrics: &mut Metrics,
+) {
+    let start = Instant::now();
+    let header = |name| {
+        request
+            .headers()
+            .iter()
+            .find(|header| header.field.equiv(name))
+            .map(|header| header.value.as_str())
+    };
+    if let Some(port) = local_only {
+        if !is_local_host(header("Host"), port) {
+            let response = Response::from_string(json!({ "error": "unexpected Host" }).to_string())
+                .with_status_code(403)
+                .with_header(json_content_type());
+            metrics.request(request.url(), 403, start.elapsed());
+            let _ = request.respond(response);
+            return;
+        }
+    }
+    if request.url() == "/generate" {
+        let authorization = header("Authorization");
+        let client = request.remote_addr().map(|addr| addr.ip());
+        if let Err(denied) = access.check(client, authorization, start) {
+            let (status, payload, header) = match denied {
+                Denied::Unauthorized => (
+                    401,
+                    json!({ "error": "missing or wrong bearer token" }),
+                    Header::from_bytes("WWW-Authenticate", "Bearer"),
+                ),
+                Denied::TooManyRequests(seconds) => (
+                    429,
+                    json!({ "error": "too many requests" }),
+                    Header::from_bytes("Retry-After", seconds.to_string()),
+                ),
+            };
+            let response = Response::from_string(payload.to_string())
+                .with_status_code(status)
+                .with_header(json_content_type())
+                .with_header(header.expect("header is valid"));
+            metrics.request(request.url(), status, start.elapsed());
+            let _ = request.respond(response);
+            return;
+        }
+    }
+    let mut body = Vec::new();
+    let read = request
+        .as_reader()
+        .take(MAX_BODY_SIZE as u64 + 1)
+        .read_to_end(&mut body);
+
+    let (status, payload) = match read {
+        Err(_) => (400, json!({ "error": "could not read request body" })),
+        Ok(_) if body.len() > MAX_BODY_SIZE => (413, json!({ "error": "request body too large" })),
+        Ok(_) if request.url() == "/metrics" && *request.method() == Method::Get => {
+            let content_type = Header::from_bytes("Content-Type", "text/plain; version=0.0.4")
+                .expect("static header is valid");
+            let response = Response::from_string(metrics.render()).with_header(content_type);
+            metrics.request(request.url(), 200, start.elapsed());
+            let _ = request.respond(response);
+            return;
+        }
+        Ok(_) => handle(request.method(), request.url(), &body, defaults, metrics),
+    };
+
+    let response = Response::from_string(payload.to_string())
+        .with_status_code(status)
+        .with_header(json_content_type());
+    metrics.request(request.url(), status, start.elapsed());
+
+    // the client may have gone away already, nothing left to do then
+    let _ = request.respond(response);
+}
+
+fn json_content_type() -> Header {
+    Header::from_bytes("Content-Type", "application/json").expect("static header is valid")
+}
+
+/// Route a request and produce the status code and JSON payload to answer with.
+fn handle(
+    method: &Method,
+    url: &str,
+    body: &[u8],
+    defaults: &Config,
+    metrics: &mut Metrics,
+) -> (u16, serde_json::Value) {
+    match (url, method) {
+        ("/generate", Method::Post) => {}
+        ("/healthz", Method::Get) => return (200, json!({ "status": "ok" })),
+        // GET /metrics is answered by `respond`, in text rather than JSON
+        ("/generate" | "/healthz" | "/metrics", _) => {
+            return (405, json!({ "error": "method not allowed" }))
+        }
+        _ => return (404, json!({ "error": "not found" })),
+    }
+
+    let generator = GenerateRequest::from_json(body)
+        .and_then(|request| request.into_config(defaults))
+        .and_then(|config| {
+            PasswordGenerator::builder()
+                .config(config)
+                .build()
+                .map_err(|err| err.to_string())
+        });
+    match generator {
+        Ok(generator) => {
+            metrics.password(generator.entropy());
+            (200, json!({ "password": generator.generate().expose() }))
+        }
+        Err(err) => (400, json!({ "error": err })),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn post(body: &str) -> (u16, serde_json::Value) {
+        handle(
+            &Method::Post,
+            "/generate",
+            body.as_bytes(),
+            &Config::default(),
+            &mut Metrics::default(),
+        )
+    }
+
+    #[test]
+    fn generates_with_defaults_on_empty_body() {
+        let (status, payload) = post("");
+        let password = payload["password"].as_str().unwrap();
+
+        assert_eq!(status, 200);
+        assert_eq!(password.split(' ').count(), 6);
+    }
+
+    #[test]
+    fn applies_request_settings() {
+        let (status, payload) = post(r#"{"number": 3, "separator": "+", "case": "upper"}"#);
+        let password = payload["password"].as_str().unwrap();
+
+        assert_eq!(status, 200);
+        assert_eq!(password.split('+').count(), 3);
+        assert_eq!(password, password.to_uppercase());
+    }
+
+    #[test]
+    fn rejects_invalid_settings() {
+        assert_eq!(post(r#"{"number": 0}"#).0, 400);
+        assert_eq!(post(r#"{"number": 1000}"#).0, 400);
+        assert_eq!(post(r#"{"list": "huge"}"#).0, 400);
+        assert_eq!(post(r#"{"case": "sideways"}"#).0, 400);
+        assert_eq!(post(r#"{"separator": "------------------"}"#).0, 400);
+        assert_eq!(post(r#"{"words": 4}"#).0, 400);
+        assert_eq!(post("not json").0, 400);
+    }
+
+    #[test]
+    fn rejects_unknown_routes_and_methods() {
+        let get = |method, url| {
+            handle(
+                &method,
+                url,
+                b"",
+                &Config::default(),
+                &mut Metrics::default(),
+            )
+            .0
+        };
+        assert_eq!(get(Method::Post, "/"), 404);
+        assert_eq!(get(Method::Get, "/generate"), 405);
+        assert_eq!(get(Method::Post, "/healthz"), 405);
+        assert_eq!(get(Method::Get, "/healthz"), 200);
+    }
+
+    #[test]
+    fn accepts_only_loopback_hosts() {
+        assert!(is_local_host(Some("127.0.0.1:8732"), 8732));
+        assert!(is_local_host(Some("localhost:8732"), 8732));
+        assert!(is_local_host(Some("LOCALHOST:8732"), 8732));
+        assert!(is_local_host(Some("[::1]:8732"), 8732));
+
+        assert!(!is_local_host(None, 8732));
+        assert!(!is_local_host(Some("localhost"), 8732));
+        assert!(!is_local_host(Some("localhost:8080"), 8732));
+        assert!(!is_local_host(Some("attacker.example:8732"), 8732));
+        assert!(!is_local_host(
+            Some("127.0.0.1.attacker.example:8732"),
+            8732
+        ));
+    }
+
+    #[test]
+    fn counts_the_entropy_of_passwords() {
+        let mut metrics = Metrics::default();
+        let body = br#"{"number": 4, "list": "long"}"#;
+        handle(
+            &Method::Post,
+            "/generate",
+            body,
+            &Config::default(),
+            &mut metrics,
+        );
+        // 4 words out of the 7776 of the long list
+        assert!(metrics
+            .render()
+            .contains("xkpass_password_entropy_bits_bucket{le=\"48\"} 0\nxkpass_password_entropy_bits_bucket{le=\"64\"} 1\n"));
+    }
+}
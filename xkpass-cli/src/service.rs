@@ -0,0 +1,551 @@
+//! Running the daemon from the service manager of the system: as a launchd
+//! agent on macOS or a service on Windows, so that desktop integrations find
+//! it listening from login or boot on.
+//!
+//! `daemon --install` registers the daemon and starts it, `daemon --uninstall`
+//! stops it and unregisters it. What is registered runs `daemon --service`,
+//! which logs to the log of the system rather than to a terminal nobody
+//! reads: the unified log on macOS, through syslog, and the Application event
+//! log on Windows.
+
+use crate::daemon;
+use std::{
+    io,
+    path::Path,
+    sync::atomic::{AtomicBool, Ordering},
+};
+use xkpass::Config;
+
+/// Label of the launchd agent.
+#[cfg(any(target_os = "macos", test))]
+const LABEL: &str = "io.github.dkmlv.xkpass";
+
+/// Whether messages go to the log of the system rather than to stderr.
+static SYSTEM_LOG: AtomicBool = AtomicBool::new(false);
+
+/// Report what the daemon is doing.
+pub fn log(message: &str) {
+    write(false, message);
+}
+
+/// Report something that went wrong without stopping the daemon.
+pub fn log_error(message: &str) {
+    write(true, message);
+}
+
+fn write(error: bool, message: &str) {
+    if SYSTEM_LOG.load(Ordering::Relaxed) {
+        system_log(error, message);
+    } else {
+        eprintln!("xkpass: {}", message);
+    }
+}
+
+/// Run the daemon as started by launchd or another service manager, logging
+/// to syslog.
+#[cfg(unix)]
+pub fn run(socket: &Path, defaults: Config) -> io::Result<()> {
+    // SAFETY: the identifier is a static string, as openlog keeps a pointer to it
+    unsafe { libc::openlog(c"xkpass".as_ptr(), libc::LOG_PID, libc::LOG_DAEMON) };
+    SYSTEM_LOG.store(true, Ordering::Relaxed);
+    daemon::run(socket, defaults)
+}
+
+/// Run the daemon as a Windows service, until the service manager stops it.
+#[cfg(windows)]
+pub fn run(socket: &Path, defaults: Config) -> io::Result<()> {
+    SYSTEM_LOG.store(true, Ordering::Relaxed);
+    windows::run(socket, defaults)
+}
+
+#[cfg(unix)]
+fn system_log(error: bool, message: &str) {
+    let Ok(message) = std::ffi::CString::new(message) else {
+        return;
+    };
+    // messages below LOG_NOTICE aren't kept by the unified log of macOS
+    let priority = if error {
+        libc::LOG_ERR
+    } else {
+        libc::LOG_NOTICE
+    };
+    // SAFETY: the format takes a single string, given as a nul-terminated one
+    unsafe { libc::syslog(priority, c"%s".as_ptr(), message.as_ptr()) };
+}
+
+#[cfg(windows)]
+fn system_log(error: bool, message: &str) {
+    windows::report_event(error, message);
+}
+
+/// Register the daemon listening on `socket` with the service manager, and start it.
+#[cfg(target_os = "macos")]
+pub fn install(socket: &Path) -> io::Result<()> {
+    launchd::install(socket)
+}
+
+/// Register the daemon listening on `socket` with the service manager, and start it.
+#[cfg(windows)]
+pub fn install(socket: &Path) -> io::Result<()> {
+    windows::install(socket)
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+pub fn install(_socket: &Path) -> io::Result<()> {
+    Err(unsupported())
+}
+
+/// Stop the daemon and unregister it from the service manager.
+#[cfg(target_os = "macos")]
+pub fn uninstall() -> io::Result<()> {
+    launchd::uninstall()
+}
+
+/// Stop the daemon and unregister it from the service manager.
+#[cfg(windows)]
+pub fn uninstall() -> io::Result<()> {
+    windows::uninstall()
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+pub fn uninstall() -> io::Result<()> {
+    Err(unsupported())
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+fn unsupported() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        "the daemon is only installed with launchd on macOS and as a service on Windows, \
+         run `xkpass daemon --service` from a unit of systemd or the like instead",
+    )
+}
+
+/// The property list of a launchd agent running `exe` as a daemon listening
+/// on `socket`, from login on, and again should it ever crash.
+#[cfg(any(target_os = "macos", test))]
+fn plist(exe: &Path, socket: &Path) -> String {
+    use crate::sheet::escape;
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+	<key>Label</key>
+	<string>{}</string>
+	<key>ProgramArguments</key>
+	<array>
+		<string>{}</string>
+		<string>daemon</string>
+		<string>--service</string>
+		<string>--socket</string>
+		<string>{}</string>
+	</array>
+	<key>RunAtLoad</key>
+	<true/>
+	<key>KeepAlive</key>
+	<dict>
+		<key>SuccessfulExit</key>
+		<false/>
+	</dict>
+</dict>
+</plist>
+"#,
+        LABEL,
+        escape(&exe.to_string_lossy()),
+        escape(&socket.to_string_lossy())
+    )
+}
+
+#[cfg(target_os = "macos")]
+mod launchd {
+    use super::LABEL;
+    use std::{
+        fs, io,
+        path::{Path, PathBuf},
+        process::{Command, Stdio},
+    };
+
+    /// Where launchd finds the agents of the user.
+    fn plist_path() -> io::Result<PathBuf> {
+        let home = std::env::var_os("HOME")
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "$HOME is not set"))?;
+        Ok(Path::new(&home)
+            .join("Library/LaunchAgents")
+            .join(format!("{}.plist", LABEL)))
+    }
+
+    /// The launchd domain of the user's login session.
+    fn domain() -> String {
+        // SAFETY: getuid has no preconditions and can't fail
+        format!("gui/{}", unsafe { libc::getuid() })
+    }
+
+    pub(super) fn install(socket: &Path) -> io::Result<()> {
+        let exe = std::env::current_exe()?;
+        let path = plist_path()?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        // an agent installed before is replaced, with the binary it runs
+        let _ = launchctl(&["bootout", &format!("{}/{}", domain(), LABEL)], true);
+        fs::write(&path, super::plist(&exe, socket))?;
+        launchctl(&["bootstrap", &domain(), &path.to_string_lossy()], false)?;
+        eprintln!(
+            "xkpass: installed {}, listening on {}",
+            path.display(),
+            socket.display()
+        );
+        Ok(())
+    }
+
+    pub(super) fn uninstall() -> io::Result<()> {
+        let path = plist_path()?;
+        if !path.exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!(
+                    "{} doesn't exist, the daemon isn't installed",
+                    path.display()
+                ),
+            ));
+        }
+
+        // the agent may have been stopped already
+        let _ = launchctl(&["bootout", &format!("{}/{}", domain(), LABEL)], true);
+        fs::remove_file(&path)?;
+        eprintln!("xkpass: removed {}", path.display());
+        Ok(())
+    }
+
+    fn launchctl(args: &[&str], quiet: bool) -> io::Result<()> {
+        let mut command = Command::new("launchctl");
+        command.args(args);
+        if quiet {
+            command.stderr(Stdio::null());
+        }
+        let status = command.status()?;
+        if !status.success() {
+            return Err(io::Error::other(format!(
+                "launchctl {} exited with {}",
+                args[0], status
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(windows)]
+mod windows {
+    use super::daemon;
+    use std::{
+        ffi::c_void,
+        io,
+        path::{Path, PathBuf},
+        ptr,
+        sync::{
+            atomic::{AtomicPtr, Ordering},
+            Condvar, Mutex, OnceLock,
+        },
+        thread,
+    };
+    use windows_sys::{
+        core::PWSTR,
+        Win32::{
+            Foundation::{ERROR_CALL_NOT_IMPLEMENTED, ERROR_SERVICE_SPECIFIC_ERROR, NO_ERROR},
+            Storage::FileSystem::DELETE,
+            System::{
+                EventLog::{
+                    DeregisterEventSource, RegisterEventSourceW, ReportEventW, EVENTLOG_ERROR_TYPE,
+                    EVENTLOG_INFORMATION_TYPE,
+                },
+                Services::{
+                    ChangeServiceConfig2W, CloseServiceHandle, ControlService, CreateServiceW,
+                    DeleteService, OpenSCManagerW, OpenServiceW, RegisterServiceCtrlHandlerExW,
+                    SetServiceStatus, StartServiceCtrlDispatcherW, StartServiceW, SC_HANDLE,
+                    SC_MANAGER_CONNECT, SC_MANAGER_CREATE_SERVICE, SERVICE_ACCEPT_SHUTDOWN,
+                    SERVICE_ACCEPT_STOP, SERVICE_AUTO_START, SERVICE_CHANGE_CONFIG,
+                    SERVICE_CONFIG_DESCRIPTION, SERVICE_CONTROL_INTERROGATE,
+                    SERVICE_CONTROL_SHUTDOWN, SERVICE_CONTROL_STOP, SERVICE_DESCRIPTIONW,
+                    SERVICE_ERROR_NORMAL, SERVICE_RUNNING, SERVICE_START, SERVICE_STATUS,
+                    SERVICE_STOP, SERVICE_STOPPED, SERVICE_STOP_PENDING, SERVICE_TABLE_ENTRYW,
+                    SERVICE_WIN32_OWN_PROCESS,
+                },
+            },
+        },
+    };
+    use xkpass::Config;
+
+    /// Name of the service, and of the source of its events.
+    const NAME: &str = "xkpass";
+
+    /// The account the service runs as, with no more rights than a user's.
+    const ACCOUNT: &str = r"NT AUTHORITY\LocalService";
+
+    const DESCRIPTION: &str =
+        r"Answers password generation requests on a named pipe, \\.\pipe\xkpass by default.";
+
+    /// What the daemon listens on and generates with, for `service_main`,
+    /// which the service manager calls without any.
+    static SERVICE: OnceLock<(PathBuf, Config)> = OnceLock::new();
+
+    /// The handle to report the status of the service with.
+    static STATUS: AtomicPtr<c_void> = AtomicPtr::new(ptr::null_mut());
+
+    /// The exit code of the service, set once it is told to stop or fails.
+    static EXIT_CODE: Mutex<Option<u32>> = Mutex::new(None);
+    static STOPPED: Condvar = Condvar::new();
+
+    fn wide(text: &str) -> Vec<u16> {
+        text.encode_utf16().chain(Some(0)).collect()
+    }
+
+    /// A handle to the service manager or a service, closed when dropped.
+    struct Handle(SC_HANDLE);
+
+    impl Handle {
+        fn new(handle: SC_HANDLE) -> io::Result<Self> {
+            if handle.is_null() {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(Self(handle))
+        }
+
+        fn manager(access: u32) -> io::Result<Self> {
+            // SAFETY: null names stand for the local computer and its active database
+            Self::new(unsafe { OpenSCManagerW(ptr::null(), ptr::null(), access) })
+        }
+    }
+
+    impl Drop for Handle {
+        fn drop(&mut self) {
+            // SAFETY: the handle was opened by the service manager and is closed once
+            unsafe { CloseServiceHandle(self.0) };
+        }
+    }
+
+    pub(super) fn install(socket: &Path) -> io::Result<()> {
+        let exe = std::env::current_exe()?;
+        let command = format!(
+            "\"{}\" daemon --service --socket \"{}\"",
+            exe.display(),
+            socket.display()
+        );
+        let (name, command, account) = (wide(NAME), wide(&command), wide(ACCOUNT));
+        let mut description = wide(DESCRIPTION);
+
+        let manager = Handle::manager(SC_MANAGER_CREATE_SERVICE)?;
+        // SAFETY: every string is nul-terminated and outlives the call, the
+        // account has no password
+        let service = Handle::new(unsafe {
+            CreateServiceW(
+                manager.0,
+                name.as_ptr(),
+                name.as_ptr(),
+                SERVICE_CHANGE_CONFIG | SERVICE_START,
+                SERVICE_WIN32_OWN_PROCESS,
+                SERVICE_AUTO_START,
+                SERVICE_ERROR_NORMAL,
+                command.as_ptr(),
+                ptr::null(),
+                ptr::null_mut(),
+                ptr::null(),
+                account.as_ptr(),
+                ptr::null(),
+            )
+        })?;
+
+        let info = SERVICE_DESCRIPTIONW {
+            lpDescription: description.as_mut_ptr(),
+        };
+        // SAFETY: `info` points to a nul-terminated description outliving the
+        // call; the service works without one, so failing to set it is ignored
+        unsafe {
+            ChangeServiceConfig2W(
+                service.0,
+                SERVICE_CONFIG_DESCRIPTION,
+                (&info as *const SERVICE_DESCRIPTIONW).cast(),
+            )
+        };
+
+        // SAFETY: the service takes no arguments but those of its command line
+        if unsafe { StartServiceW(service.0, 0, ptr::null()) } == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        eprintln!(
+            "xkpass: installed the {} service, listening on {}",
+            NAME,
+            socket.display()
+        );
+        Ok(())
+    }
+
+    pub(super) fn uninstall() -> io::Result<()> {
+        let name = wide(NAME);
+        let manager = Handle::manager(SC_MANAGER_CONNECT)?;
+        // SAFETY: `name` is nul-terminated
+        let service =
+            Handle::new(unsafe { OpenServiceW(manager.0, name.as_ptr(), SERVICE_STOP | DELETE) })?;
+
+        let mut status = SERVICE_STATUS::default();
+        // SAFETY: `status` is valid for the call; the service may have been
+        // stopped already, which changes nothing to deleting it
+        unsafe { ControlService(service.0, SERVICE_CONTROL_STOP, &mut status) };
+        // SAFETY: the handle was opened with DELETE access
+        if unsafe { DeleteService(service.0) } == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        eprintln!("xkpass: removed the {} service", NAME);
+        Ok(())
+    }
+
+    /// Hand the thread over to the service manager, which runs `service_main`
+    /// in another and returns once the service has stopped.
+    pub(super) fn run(socket: &Path, defaults: Config) -> io::Result<()> {
+        let _ = SERVICE.set((socket.to_owned(), defaults));
+        let mut name = wide(NAME);
+        let table = [
+            SERVICE_TABLE_ENTRYW {
+                lpServiceName: name.as_mut_ptr(),
+                lpServiceProc: Some(service_main),
+            },
+            SERVICE_TABLE_ENTRYW::default(),
+        ];
+        // SAFETY: the table ends with a null entry, and `name` outlives the dispatcher
+        if unsafe { StartServiceCtrlDispatcherW(table.as_ptr()) } == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    unsafe extern "system" fn service_main(_argc: u32, _argv: *mut PWSTR) {
+        let name = wide(NAME);
+        // SAFETY: `name` is nul-terminated and `control` takes no context
+        let status =
+            unsafe { RegisterServiceCtrlHandlerExW(name.as_ptr(), Some(control), ptr::null()) };
+        if status.is_null() {
+            super::log_error(&format!(
+                "could not register with the service manager: {}",
+                io::Error::last_os_error()
+            ));
+            return;
+        }
+        STATUS.store(status, Ordering::SeqCst);
+        set_status(SERVICE_RUNNING, NO_ERROR);
+
+        let (socket, defaults) = SERVICE.get().expect("set before starting the dispatcher");
+        thread::spawn(move || {
+            if let Err(err) = daemon::run(socket, defaults.clone()) {
+                super::log_error(&err.to_string());
+                stop(ERROR_SERVICE_SPECIFIC_ERROR);
+            }
+        });
+
+        let mut exit_code = EXIT_CODE.lock().unwrap_or_else(|err| err.into_inner());
+        while exit_code.is_none() {
+            exit_code = STOPPED
+                .wait(exit_code)
+                .unwrap_or_else(|err| err.into_inner());
+        }
+        super::log("stopped");
+        set_status(SERVICE_STOPPED, exit_code.unwrap_or(NO_ERROR));
+    }
+
+    unsafe extern "system" fn control(
+        control: u32,
+        _event_type: u32,
+        _event_data: *mut c_void,
+        _context: *mut c_void,
+    ) -> u32 {
+        match control {
+            SERVICE_CONTROL_STOP | SERVICE_CONTROL_SHUTDOWN => {
+                set_status(SERVICE_STOP_PENDING, NO_ERROR);
+                stop(NO_ERROR);
+                NO_ERROR
+            }
+            SERVICE_CONTROL_INTERROGATE => NO_ERROR,
+            _ => ERROR_CALL_NOT_IMPLEMENTED,
+        }
+    }
+
+    /// Have `service_main` report the service as stopped with `exit_code`.
+    fn stop(exit_code: u32) {
+        let mut current = EXIT_CODE.lock().unwrap_or_else(|err| err.into_inner());
+        current.get_or_insert(exit_code);
+        STOPPED.notify_all();
+    }
+
+    fn set_status(state: u32, exit_code: u32) {
+        let status = SERVICE_STATUS {
+            dwServiceType: SERVICE_WIN32_OWN_PROCESS,
+            dwCurrentState: state,
+            dwControlsAccepted: if state == SERVICE_RUNNING {
+                SERVICE_ACCEPT_STOP | SERVICE_ACCEPT_SHUTDOWN
+            } else {
+                0
+            },
+            dwWin32ExitCode: exit_code,
+            dwServiceSpecificExitCode: u32::from(exit_code == ERROR_SERVICE_SPECIFIC_ERROR),
+            ..SERVICE_STATUS::default()
+        };
+        // SAFETY: the handle was registered by `service_main` before any status is set
+        unsafe { SetServiceStatus(STATUS.load(Ordering::SeqCst), &status) };
+    }
+
+    /// Write `message` to the Application event log.
+    ///
+    /// xkpass has no message file of its own, so Event Viewer shows the
+    /// message after a note that the description of the event is missing.
+    pub(super) fn report_event(error: bool, message: &str) {
+        let (name, message) = (wide(NAME), wide(message));
+        let kind = if error {
+            EVENTLOG_ERROR_TYPE
+        } else {
+            EVENTLOG_INFORMATION_TYPE
+        };
+        // SAFETY: the strings are nul-terminated and outlive the calls, and
+        // the event source is deregistered once
+        unsafe {
+            let log = RegisterEventSourceW(ptr::null(), name.as_ptr());
+            if log.is_null() {
+                return;
+            }
+            let strings = [message.as_ptr()];
+            ReportEventW(
+                log,
+                kind,
+                0,
+                0,
+                ptr::null_mut(),
+                1,
+                0,
+                strings.as_ptr(),
+                ptr::null(),
+            );
+            DeregisterEventSource(log);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_launchd_agents() {
+        let plist = plist(
+            Path::new("/Applications/xkpass & co/xkpass"),
+            Path::new("/tmp/xkpass.sock"),
+        );
+        assert!(plist.contains("<string>io.github.dkmlv.xkpass</string>"));
+        assert!(plist.contains(
+            "<string>/Applications/xkpass &amp; co/xkpass</string>\n\
+             \t\t<string>daemon</string>\n\
+             \t\t<string>--service</string>\n\
+             \t\t<string>--socket</string>\n\
+             \t\t<string>/tmp/xkpass.sock</string>"
+        ));
+    }
+}
@@ -0,0 +1,187 @@
+//! Splitting a password into shares, any `threshold` of which put it back together.
+//!
+//! This is Shamir's secret sharing over GF(256): every byte of the password is
+//! the constant term of its own random polynomial of degree `threshold - 1`,
+//! and each share holds the value of all the polynomials at one point. Fewer
+//! than `threshold` shares tell nothing about the password.
+//!
+//! A share is its point followed by the values, written as words with
+//! [`xkpass::encode_bytes`]. A checksum is split along with the password, so
+//! that putting together too few shares, or shares of different passwords, is
+//! an error rather than a wrong password.
+
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use std::{
+    collections::HashSet,
+    io::{self, BufRead},
+};
+use xkpass::Config;
+
+/// Bytes of the SHA-256 hash of the password split along with it.
+const CHECKSUM_LEN: usize = 4;
+
+/// Print `shares` shares of a password generated with `config` and the
+/// password itself first, or without a `config` of a password read on stdin.
+pub fn run_split(config: Option<Config>, shares: u8, threshold: u8) -> Result<(), String> {
+    let password = match config {
+        Some(config) => {
+            let password = xkpass::generate_password(config)
+                .map_err(|err| err.to_string())?
+                .into_string();
+            println!("{}", password);
+            password
+        }
+        None => {
+            let mut password = String::new();
+            io::stdin()
+                .lock()
+                .read_line(&mut password)
+                .map_err(|err| format!("stdin: {}", err))?;
+            password.trim_end_matches(&['\r', '\n'][..]).to_string()
+        }
+    };
+    for share in split(&password, shares, threshold, &mut rand::thread_rng()) {
+        println!("{}", share);
+    }
+    Ok(())
+}
+
+/// Read shares on stdin, one per line, and print the password they put together.
+pub fn run_combine() -> Result<(), String> {
+    let lines = io::stdin()
+        .lock()
+        .lines()
+        .collect::<io::Result<Vec<_>>>()
+        .map_err(|err| format!("stdin: {}", err))?;
+    let shares: Vec<&str> = lines
+        .iter()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .collect();
+    println!("{}", combine(&shares)?);
+    Ok(())
+}
+
+/// Split `password` into `shares` shares, any `threshold` of which put it back together.
+fn split(password: &str, shares: u8, threshold: u8, rng: &mut impl RngCore) -> Vec<String> {
+    let mut secret = password.as_bytes().to_vec();
+    secret.extend_from_slice(&Sha256::digest(password.as_bytes())[..CHECKSUM_LEN]);
+
+    let mut points: Vec<Vec<u8>> = (1..=shares).map(|x| vec![x]).collect();
+    let mut coefficients = vec![0; usize::from(threshold)];
+    for byte in secret {
+        coefficients[0] = byte;
+        rng.fill_bytes(&mut coefficients[1..]);
+        for point in &mut points {
+            let x = point[0];
+            let y = coefficients
+                .iter()
+                .rev()
+                .fold(0, |y, &coefficient| mul(y, x) ^ coefficient);
+            point.push(y);
+        }
+    }
+
+    points
+        .iter()
+        .map(|point| xkpass::encode_bytes(point).join(" "))
+        .collect()
+}
+
+/// Put the password split into `shares` back together.
+fn combine(shares: &[&str]) -> Result<String, String> {
+    let points = shares
+        .iter()
+        .map(|share| xkpass::decode_words(share.split_whitespace()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| err.to_string())?;
+    let Some(len) = points.first().map(Vec::len) else {
+        return Err(String::from("no shares were given"));
+    };
+    if points.iter().any(|point| point.len() != len) {
+        return Err(String::from("the shares are of different passwords"));
+    }
+    let xs: Vec<u8> = points.iter().map(|point| point[0]).collect();
+    if xs.iter().collect::<HashSet<_>>().len() != xs.len() {
+        return Err(String::from("the same share was given twice"));
+    }
+
+    // the value at 0 of the polynomial going through the points, by Lagrange
+    let weights: Vec<u8> = xs
+        .iter()
+        .map(|&xi| {
+            xs.iter()
+                .filter(|&&xj| xj != xi)
+                .fold(1, |weight, &xj| mul(weight, mul(xj, inverse(xj ^ xi))))
+        })
+        .collect();
+    let secret: Vec<u8> = (1..len)
+        .map(|i| {
+            points
+                .iter()
+                .zip(&weights)
+                .fold(0, |byte, (point, &weight)| byte ^ mul(point[i], weight))
+        })
+        .collect();
+
+    let checksum_start = secret.len().saturating_sub(CHECKSUM_LEN);
+    let (password, checksum) = secret.split_at(checksum_start);
+    if Sha256::digest(password)[..CHECKSUM_LEN] != *checksum {
+        return Err(String::from(
+            "too few shares were given, or shares of different passwords",
+        ));
+    }
+    String::from_utf8(password.to_vec())
+        .map_err(|_| String::from("the shares don't make up a password"))
+}
+
+/// Product in GF(256), modulo the polynomial of AES.
+fn mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0;
+    while b != 0 {
+        if b & 1 == 1 {
+            product ^= a;
+        }
+        let carry = a & 0x80 != 0;
+        a <<= 1;
+        if carry {
+            a ^= 0x1b;
+        }
+        b >>= 1;
+    }
+    product
+}
+
+/// Inverse in GF(256) of a non-zero `a`, which is `a` to the power 254.
+fn inverse(a: u8) -> u8 {
+    let (mut result, mut base, mut exponent) = (1, a, 254u8);
+    while exponent != 0 {
+        if exponent & 1 == 1 {
+            result = mul(result, base);
+        }
+        base = mul(base, base);
+        exponent >>= 1;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn any_threshold_shares_combine() {
+        let password = "correct horse battery staple";
+        let shares = split(password, 5, 3, &mut rand::thread_rng());
+
+        for picked in [[0, 1, 2], [4, 2, 0], [1, 3, 4]] {
+            let picked: Vec<&str> = picked.iter().map(|&i| shares[i].as_str()).collect();
+            assert_eq!(combine(&picked).as_deref(), Ok(password));
+        }
+        let all: Vec<&str> = shares.iter().map(String::as_str).collect();
+        assert_eq!(combine(&all).as_deref(), Ok(password));
+        assert!(combine(&all[..2]).is_err());
+        assert!(combine(&[all[0], all[0], all[1]]).is_err());
+    }
+}
@@ -0,0 +1,167 @@
+//! Printable sheets: the HTML of `--format html`, for keeping a passphrase
+//! on paper.
+//!
+//! The page stands alone, without scripts or anything fetched from elsewhere,
+//! so that it can be opened offline and printed, or saved as a PDF, from any
+//! browser. The sheet of a password has the password itself, its QR code to
+//! type it back with a phone, its fingerprint to tell it apart from others,
+//! the date it was generated and its settings, along with the recipe to
+//! generate passwords the same way again.
+
+use qrcode::{render::svg, QrCode};
+use std::{
+    fmt::Write,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// What the sheet of a password shows.
+pub struct Sheet<'a> {
+    pub password: &'a str,
+    /// Lines of the password as it is shown, chunked or spelled out.
+    pub shown: &'a str,
+    pub fingerprint: &'a str,
+    /// The settings, as names and values.
+    pub settings: &'a [(&'a str, String)],
+    pub recipe: &'a str,
+}
+
+/// The start of a page titled `title`, up to its body.
+pub fn head(title: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n\
+         <html>\n\
+         <head>\n\
+         <meta charset=\"utf-8\">\n\
+         <title>{}</title>\n\
+         <style>\n\
+         body {{ font-family: sans-serif; max-width: 44em; margin: 2em auto; }}\n\
+         table {{ border-collapse: collapse; }}\n\
+         th, td {{ border: 1px solid #888; padding: 0.3em 0.6em; text-align: left; }}\n\
+         .password {{ font-family: monospace; font-size: 1.6em; white-space: pre-wrap; word-break: break-all; }}\n\
+         .fingerprint {{ font-family: monospace; line-height: 1.1; }}\n\
+         .qr svg {{ width: 14em; height: 14em; }}\n\
+         @media print {{ body {{ margin: 0; }} }}\n\
+         </style>\n\
+         </head>\n\
+         <body>\n",
+        escape(title)
+    )
+}
+
+/// The end of a page.
+pub const FOOT: &str = "</body>\n</html>\n";
+
+/// The page of `sheet`, generated on `date`.
+pub fn password(sheet: &Sheet, date: &str) -> Result<String, String> {
+    let qr = QrCode::new(sheet.password.as_bytes()).map_err(|err| err.to_string())?;
+    let image = qr.render::<svg::Color>().min_dimensions(200, 200).build();
+    // the XML declaration has no place inside HTML
+    let image = image
+        .split_once("?>")
+        .map_or(image.as_str(), |(_, svg)| svg.trim_start());
+
+    let mut page = head("Password");
+    page += "<h1>Password</h1>\n";
+    let _ = writeln!(page, "<p class=\"password\">{}</p>", escape(sheet.shown));
+    let _ = writeln!(page, "<div class=\"qr\">{}</div>", image);
+    page += "<h2>Fingerprint</h2>\n";
+    let _ = writeln!(
+        page,
+        "<pre class=\"fingerprint\">{}</pre>",
+        escape(sheet.fingerprint)
+    );
+    page += "<h2>Settings</h2>\n<table>\n";
+    let _ = writeln!(page, "<tr><th>Generated</th><td>{}</td></tr>", date);
+    for (name, value) in sheet.settings {
+        let _ = writeln!(
+            page,
+            "<tr><th>{}</th><td>{}</td></tr>",
+            escape(name),
+            escape(value)
+        );
+    }
+    let _ = writeln!(
+        page,
+        "<tr><th>Recipe</th><td><code>{}</code></td></tr>",
+        escape(sheet.recipe)
+    );
+    page += "</table>\n";
+    page += FOOT;
+    Ok(page)
+}
+
+/// `text` with the characters that mean something in HTML escaped.
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Today's date in UTC, as YYYY-MM-DD.
+pub fn today() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs());
+    date(secs / 86400)
+}
+
+/// The date `days` days after 1970-01-01, in the proleptic Gregorian calendar.
+fn date(days: u64) -> String {
+    // from Howard Hinnant's civil_from_days, with eras of 400 years starting in March
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_from_march + 2) / 5 + 1;
+    let month = if month_from_march < 10 {
+        month_from_march + 3
+    } else {
+        month_from_march - 9
+    };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_dates() {
+        assert_eq!(date(0), "1970-01-01");
+        assert_eq!(date(59), "1970-03-01");
+        assert_eq!(date(11_016), "2000-02-29");
+        assert_eq!(date(20_741), "2026-10-15");
+    }
+
+    #[test]
+    fn escapes_passwords() {
+        let settings = [("Separator", String::from("<&>"))];
+        let sheet = Sheet {
+            password: "a<b>&\"c'",
+            shown: "a<b>&\"c'",
+            fingerprint: "+--+\n|  |\n+--+\n",
+            settings: &settings,
+            recipe: "xk1:list=long",
+        };
+        let page = password(&sheet, "2026-10-15").unwrap();
+        assert!(page.contains("a&lt;b&gt;&amp;&quot;c&#39;"));
+        assert!(page.contains("&lt;&amp;&gt;"));
+        assert!(!page.contains("a<b>"));
+        assert!(page.contains("<svg"));
+        assert!(!page.contains("<?xml"));
+        assert!(page.ends_with(FOOT));
+    }
+}
@@ -0,0 +1,92 @@
+//! Running `ssh-keygen` with a generated passphrase.
+//!
+//! ssh-keygen reads passphrases from the terminal, or from the program named
+//! by `SSH_ASKPASS` when `SSH_ASKPASS_REQUIRE=force`. xkpass names itself,
+//! and the copy started by ssh-keygen fetches the passphrase from a Unix
+//! socket in a directory only the user can enter, so that it never shows up
+//! in argv or in the environment. The passphrase is printed once ssh-keygen
+//! has succeeded.
+
+use crate::clipboard::{self, Selection};
+use rand::Rng;
+use std::{
+    env,
+    ffi::OsStr,
+    fs,
+    io::{self, Write},
+    os::unix::{
+        fs::DirBuilderExt,
+        net::{UnixListener, UnixStream},
+    },
+    process::{Command, Stdio},
+    thread,
+};
+use xkpass::Config;
+
+/// Variable telling xkpass it was started by ssh-keygen as its askpass
+/// program, holding the path of the socket to fetch the passphrase from.
+pub const SOCKET: &str = "XKPASS_ASKPASS_SOCKET";
+
+/// Run `ssh-keygen` with `args` and a passphrase generated with `config`,
+/// then print the passphrase, or copy it to `copy`.
+pub fn run(config: Config, args: &[String], copy: Option<Selection>) -> io::Result<()> {
+    let password = xkpass::generate_password(config)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+
+    let dir = env::temp_dir().join(format!(
+        "xkpass-askpass-{:016x}",
+        rand::thread_rng().gen::<u64>()
+    ));
+    // creating the directory fails if it already exists, so nobody else can own it
+    fs::DirBuilder::new().mode(0o700).create(&dir)?;
+    let socket = dir.join("socket");
+    let status = UnixListener::bind(&socket).and_then(|listener| {
+        let passphrase = password.expose().to_string();
+        // ssh-keygen asks for the passphrase twice, and more often for some operations
+        thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let _ = writeln!(stream, "{}", passphrase);
+            }
+        });
+        Command::new("ssh-keygen")
+            .args(args)
+            .env("SSH_ASKPASS", env::current_exe()?)
+            .env("SSH_ASKPASS_REQUIRE", "force")
+            .env(SOCKET, &socket)
+            // stdout is for the passphrase alone
+            .stdout(Stdio::from(io::stderr()))
+            .status()
+    });
+    let _ = fs::remove_dir_all(&dir);
+
+    let status = status.map_err(|err| match err.kind() {
+        io::ErrorKind::NotFound => io::Error::new(
+            io::ErrorKind::NotFound,
+            "ssh-keygen isn't installed, it comes with OpenSSH",
+        ),
+        _ => err,
+    })?;
+    if !status.success() {
+        return Err(io::Error::other(format!(
+            "ssh-keygen exited with {}",
+            status
+        )));
+    }
+
+    match copy {
+        Some(selection) => {
+            clipboard::copy(password.expose(), selection)?;
+            eprintln!("copied the passphrase to the clipboard");
+        }
+        None => println!("{}", password.expose()),
+    }
+    Ok(())
+}
+
+/// Act as the askpass program of ssh-keygen: print the passphrase served on
+/// `socket`, whatever the prompt.
+pub fn askpass(socket: &OsStr) -> io::Result<()> {
+    let mut stream = UnixStream::connect(socket)?;
+    io::copy(&mut stream, &mut io::stdout().lock())?;
+    Ok(())
+}
@@ -0,0 +1,69 @@
+//! Exit statuses telling scripts why xkpass failed, so that they can branch on
+//! the reason rather than parse the message:
+//!
+//! | Status | Meaning                                                      |
+//! |--------|--------------------------------------------------------------|
+//! | 0      | Success                                                      |
+//! | 1      | Any other error, such as a file that can't be read           |
+//! | 2      | The options are invalid or can't all be satisfied            |
+//! | 3      | The password has less entropy than `--min-entropy` asks for  |
+//! | 4      | Reserved for a breach check finding the password             |
+//! | 5      | A batch had an invalid line, which stops it unless in NDJSON |
+//!
+//! Status 2 is also the one clap exits with on usage errors.
+
+use std::{fmt, io, process};
+
+/// Why xkpass failed, as its exit status.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Status {
+    Failure = 1,
+    Unsatisfiable = 2,
+    Weak = 3,
+    PartialBatch = 5,
+}
+
+/// An error along with the status to exit with.
+#[derive(Debug)]
+pub struct Failure {
+    pub status: Status,
+    pub message: String,
+}
+
+impl Failure {
+    pub fn new(status: Status, message: impl fmt::Display) -> Self {
+        Self {
+            status,
+            message: message.to_string(),
+        }
+    }
+
+    /// An error of options that can't all be satisfied.
+    pub fn unsatisfiable(message: impl fmt::Display) -> Self {
+        Self::new(Status::Unsatisfiable, message)
+    }
+
+    /// Report the error and exit with its status.
+    pub fn exit(self) -> ! {
+        eprintln!("xkpass: {}", self.message);
+        process::exit(self.status as i32);
+    }
+}
+
+impl From<String> for Failure {
+    fn from(message: String) -> Self {
+        Self::new(Status::Failure, message)
+    }
+}
+
+impl From<io::Error> for Failure {
+    fn from(err: io::Error) -> Self {
+        Self::new(Status::Failure, err)
+    }
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
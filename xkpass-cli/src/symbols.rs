@@ -0,0 +1,65 @@
+//! A symbol inside one of the words, for rules that want a special character
+//! but refuse one at the start or the end of the password.
+//!
+//! The symbol and its place are both random, so they add their bits to the
+//! entropy: those of the symbols to pick from, and those of the places between
+//! two characters of a word, of which a password of long words has many.
+
+use crate::{explain::Row, separators};
+use xkpass::Password;
+
+/// Symbols embedded by default, found on every keyboard layout.
+pub const SYMBOLS: &str = "!#$%&*+=?@^~";
+
+/// The distinct symbols of `given`, leaving out those that could be taken for
+/// the `separator` and the digits standing in for one.
+pub fn set(given: &str, separator: &str) -> Result<Vec<char>, String> {
+    let digits = separators::digits(separator).is_some();
+    let mut symbols: Vec<char> = given
+        .chars()
+        .filter(|&c| !c.is_whitespace() && !separator.contains(c))
+        .filter(|c| !(digits && c.is_ascii_digit()))
+        .collect();
+    symbols.sort_unstable();
+    symbols.dedup();
+
+    if symbols.is_empty() {
+        return Err(format!(
+            "--embed-symbol needs symbols other than whitespace and the separator {:?}",
+            separator
+        ));
+    }
+    Ok(symbols)
+}
+
+/// The bits the symbol embedded in `password` adds, picked out of `symbols`.
+pub fn row(password: &Password, symbols: usize) -> Row {
+    let places: usize = password
+        .words()
+        .iter()
+        .map(|word| word.chars().count().saturating_sub(1))
+        .sum();
+    (
+        "symbol",
+        match symbols {
+            1 => format!("one symbol at one of {} places in the words", places),
+            _ => format!(
+                "one of {} symbols at {} places in the words",
+                symbols, places
+            ),
+        },
+        ((places * symbols) as f64).log2().max(0.0),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn leaves_out_separators() {
+        assert_eq!(set("!!-?", "-"), Ok(vec!['!', '?']));
+        assert_eq!(set("!7", "digits"), Ok(vec!['!']));
+        assert!(set("- ", "-").is_err());
+    }
+}
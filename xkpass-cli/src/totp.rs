@@ -0,0 +1,152 @@
+//! Secrets for TOTP, the six-digit codes of authenticator apps (RFC 6238),
+//! for setting up two-factor authentication on a new account along with its
+//! password.
+//!
+//! The secret is 160 random bits, the size RFC 4226 recommends for HMAC-SHA1,
+//! handed to the app as an `otpauth://` URI in a QR code, with the defaults
+//! every app supports: SHA-1, six digits and a new code every 30 seconds.
+
+use crate::recipe;
+use hmac::{Hmac, KeyInit, Mac};
+use qrcode::{render::unicode::Dense1x2, QrCode};
+use rand::RngCore;
+use sha1::Sha1;
+use std::{
+    io::{self, BufRead, Write},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Bytes of a secret.
+const SECRET_LEN: usize = 20;
+/// Seconds each code is valid for.
+const PERIOD: u64 = 30;
+const DIGITS: u32 = 6;
+
+/// Generate a secret for `account` at `issuer` and print its URI and QR code,
+/// then ask for the first code if `verify` is set, to check the app has it.
+pub fn run(issuer: &str, account: &str, verify: bool) -> io::Result<()> {
+    let mut secret = [0; SECRET_LEN];
+    rand::rngs::OsRng.fill_bytes(&mut secret);
+    let uri = uri(issuer, account, &secret);
+    let qr = QrCode::new(&uri).map_err(io::Error::other)?;
+
+    let mut out = io::stdout().lock();
+    // inverted, for terminals with a dark background, as most are
+    let image = qr
+        .render::<Dense1x2>()
+        .dark_color(Dense1x2::Light)
+        .light_color(Dense1x2::Dark)
+        .build();
+    writeln!(out, "{}", image)?;
+    writeln!(out, "{}", uri)?;
+    writeln!(out, "secret: {}", base32(&secret))?;
+    out.flush()?;
+    if !verify {
+        return Ok(());
+    }
+
+    eprint!("Code shown by the app: ");
+    let mut line = String::new();
+    io::stdin().lock().read_line(&mut line)?;
+    let given = line.trim().replace(' ', "");
+    if check(&secret, &given, now()) {
+        eprintln!("The code matches, the app is set up.");
+        Ok(())
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "the code doesn't match, check the clock of the device and scan the code again",
+        ))
+    }
+}
+
+/// The `otpauth://` URI of `secret`, as authenticator apps read it.
+fn uri(issuer: &str, account: &str, secret: &[u8]) -> String {
+    format!(
+        "otpauth://totp/{}:{}?secret={}&issuer={}&algorithm=SHA1&digits={}&period={}",
+        recipe::encode(issuer),
+        recipe::encode(account),
+        base32(secret),
+        recipe::encode(issuer),
+        DIGITS,
+        PERIOD
+    )
+}
+
+/// Whether `given` is the code of `secret` at `time`, or of the period before
+/// or after it, to allow for the clocks being a little apart.
+fn check(secret: &[u8], given: &str, time: u64) -> bool {
+    let counter = time / PERIOD;
+    [counter.saturating_sub(1), counter, counter + 1]
+        .iter()
+        .any(|&counter| {
+            format!("{:0width$}", code(secret, counter), width = DIGITS as usize) == given
+        })
+}
+
+/// The code of `secret` for `counter`, as HOTP computes it (RFC 4226).
+fn code(secret: &[u8], counter: u64) -> u32 {
+    let mut mac = Hmac::<Sha1>::new_from_slice(secret).expect("HMAC takes keys of any size");
+    mac.update(&counter.to_be_bytes());
+    let hash = mac.finalize().into_bytes();
+    let offset = usize::from(hash[hash.len() - 1] & 0xf);
+    let truncated = u32::from_be_bytes(hash[offset..offset + 4].try_into().expect("4 bytes"));
+    (truncated & 0x7fff_ffff) % 10u32.pow(DIGITS)
+}
+
+/// `bytes` in base32 without padding (RFC 4648), the encoding of the secret.
+fn base32(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(5) * 8);
+    let (mut buffer, mut bits) = (0u32, 0);
+    for &byte in bytes {
+        buffer = (buffer << 8) | u32::from(byte);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            encoded.push(char::from(ALPHABET[(buffer >> bits) as usize & 31]));
+        }
+    }
+    if bits > 0 {
+        encoded.push(char::from(ALPHABET[(buffer << (5 - bits)) as usize & 31]));
+    }
+    encoded
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The SHA-1 secret of the test vectors of RFC 6238.
+    const SECRET: &[u8] = b"12345678901234567890";
+
+    #[test]
+    fn computes_codes_of_the_rfc() {
+        // the last six of the eight digits of the RFC
+        assert_eq!(code(SECRET, 59 / PERIOD), 287082);
+        assert_eq!(code(SECRET, 1111111109 / PERIOD), 81804);
+        assert_eq!(code(SECRET, 1234567890 / PERIOD), 5924);
+        assert!(check(SECRET, "081804", 1111111109));
+        assert!(check(SECRET, "081804", 1111111109 + PERIOD));
+        assert!(!check(SECRET, "081804", 1111111109 + 2 * PERIOD));
+        assert!(!check(SECRET, "81804", 1111111109));
+    }
+
+    #[test]
+    fn encodes_uris() {
+        assert_eq!(base32(b"foobar"), "MZXW6YTBOI");
+        assert_eq!(base32(b"fooba"), "MZXW6YTB");
+        assert_eq!(base32(SECRET).len(), 32);
+        assert_eq!(
+            uri("Example Co", "alice@example.com", b"fooba"),
+            "otpauth://totp/Example%20Co:alice%40example.com?secret=MZXW6YTB\
+             &issuer=Example%20Co&algorithm=SHA1&digits=6&period=30"
+        );
+    }
+}
@@ -0,0 +1,305 @@
+//! A full-screen interactive front-end in the terminal.
+//!
+//! The settings are changed with single keys and the password is regenerated
+//! on every change, with its entropy and the time it would take to crack it.
+//! Single words can be rerolled without touching the others.
+
+use crate::{
+    clipboard::{self, Selection},
+    explain::{crack_time, GUESSES_PER_SECOND},
+};
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind},
+    layout::{Constraint, Layout},
+    style::{Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Paragraph, Wrap},
+    DefaultTerminal, Frame,
+};
+use std::io;
+use xkpass::{Case, Config, List, VariantNames};
+
+const MAX_WORDS: usize = 20;
+
+const HELP: &str = "↑/↓ words  l list  c case  s separator  ←/→ pick a word  r reroll it  \
+                    space regenerate  y copy  q quit";
+
+/// Run the interface until the user quits, starting from `config` and
+/// copying to `selection`.
+pub fn run(config: Config, selection: Selection) -> io::Result<()> {
+    let mut app = App::new(config, selection);
+    let mut terminal = ratatui::try_init()?;
+    let result = app.event_loop(&mut terminal);
+    ratatui::restore();
+
+    result
+}
+
+/// Settings chosen so far and the password they produced.
+struct App {
+    config: Config,
+    pool: Vec<&'static str>,
+    words: Vec<String>,
+    entropy: f64,
+    /// Index of the word `r` rerolls.
+    selected: usize,
+    /// The separator being typed, while it is edited.
+    editing: Option<String>,
+    /// Last error or confirmation, shown under the password.
+    status: Option<String>,
+    selection: Selection,
+    quit: bool,
+}
+
+impl App {
+    fn new(config: Config, selection: Selection) -> Self {
+        let mut app = Self {
+            pool: xkpass::parse_word_list(&config.list),
+            config,
+            words: Vec::new(),
+            entropy: 0.0,
+            selected: 0,
+            editing: None,
+            status: None,
+            selection,
+            quit: false,
+        };
+        app.regenerate();
+
+        app
+    }
+
+    fn event_loop(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        while !self.quit {
+            terminal.draw(|frame| self.draw(frame))?;
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press {
+                    self.handle_key(key);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) {
+        if let Some(separator) = &mut self.editing {
+            match key.code {
+                KeyCode::Char(c) => separator.push(c),
+                KeyCode::Backspace => {
+                    separator.pop();
+                }
+                KeyCode::Enter => {
+                    self.config.separator = self.editing.take().unwrap_or_default();
+                    self.regenerate();
+                }
+                KeyCode::Esc => self.editing = None,
+                _ => {}
+            }
+            return;
+        }
+
+        self.status = None;
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Up | KeyCode::Char('+') if self.config.number < MAX_WORDS => {
+                self.config.number += 1;
+                self.regenerate();
+            }
+            KeyCode::Down | KeyCode::Char('-') if self.config.number > 1 => {
+                self.config.number -= 1;
+                self.regenerate();
+            }
+            KeyCode::Char('l') => {
+                self.config.list = next(&self.config.list, List::name);
+                self.pool = xkpass::parse_word_list(&self.config.list);
+                self.regenerate();
+            }
+            KeyCode::Char('c') => {
+                self.config.case = next(&self.config.case, Case::name);
+                self.regenerate();
+            }
+            KeyCode::Char('s') => self.editing = Some(String::new()),
+            KeyCode::Left => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Right if self.selected + 1 < self.words.len() => self.selected += 1,
+            KeyCode::Char('r') => self.reroll(),
+            KeyCode::Char(' ') | KeyCode::Enter => self.regenerate(),
+            KeyCode::Char('y') if !self.words.is_empty() => {
+                self.status = Some(match clipboard::copy(&self.password(), self.selection) {
+                    Ok(()) => String::from("copied to the clipboard"),
+                    Err(err) => err.to_string(),
+                });
+            }
+            _ => {}
+        }
+    }
+
+    /// Replace every word.
+    fn regenerate(&mut self) {
+        match xkpass::generate_password_from(&self.pool, self.config.clone()) {
+            Ok(password) => {
+                self.words = password.words().to_vec();
+                self.entropy = password.entropy();
+            }
+            Err(err) => {
+                self.words.clear();
+                self.status = Some(err.to_string());
+            }
+        }
+        self.selected = self.selected.min(self.words.len().saturating_sub(1));
+    }
+
+    /// Replace the selected word by one that isn't in the password yet.
+    ///
+    /// The entropy stays the same as long as words aren't rerolled until the
+    /// password looks a certain way.
+    fn reroll(&mut self) {
+        if self.words.is_empty() || self.pool.len() <= self.words.len() {
+            return;
+        }
+
+        let config = Config {
+            number: 1,
+            ..self.config.clone()
+        };
+        loop {
+            let word = match xkpass::generate_password_from(&self.pool, config.clone()) {
+                Ok(password) => password.into_string(),
+                Err(err) => {
+                    self.status = Some(err.to_string());
+                    return;
+                }
+            };
+            if !self
+                .words
+                .iter()
+                .any(|other| other.eq_ignore_ascii_case(&word))
+            {
+                self.words[self.selected] = word;
+                return;
+            }
+        }
+    }
+
+    fn password(&self) -> String {
+        self.words.join(&self.config.separator)
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [settings, password, strength, status, help] = Layout::vertical([
+            Constraint::Length(6),
+            Constraint::Min(3),
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        let separator = match &self.editing {
+            Some(separator) => Span::from(format!("{:?}▏", separator)).reversed(),
+            None => Span::from(format!("{:?}", self.config.separator)),
+        };
+        let lines = vec![
+            setting("Words", Span::from(self.config.number.to_string())),
+            setting("List", Span::from(self.config.list.name())),
+            setting("Case", Span::from(self.config.case.name())),
+            setting("Separator", separator),
+        ];
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::bordered().title(" xkpass ")),
+            settings,
+        );
+
+        let mut spans = Vec::new();
+        for (i, word) in self.words.iter().enumerate() {
+            if i > 0 {
+                spans.push(Span::from(self.config.separator.as_str()).dim());
+            }
+            let style = if i == self.selected {
+                Style::new().bold().underlined()
+            } else {
+                Style::new().bold()
+            };
+            spans.push(Span::styled(word.as_str(), style));
+        }
+        frame.render_widget(
+            Paragraph::new(Line::from(spans))
+                .wrap(Wrap { trim: false })
+                .block(Block::bordered().title(" password ")),
+            password,
+        );
+
+        if !self.words.is_empty() {
+            frame.render_widget(
+                Line::from(format!(
+                    " {:.1} bits of entropy, {} to crack at {:.0e} guesses per second",
+                    self.entropy,
+                    crack_time(self.entropy),
+                    GUESSES_PER_SECOND
+                )),
+                strength,
+            );
+        }
+        if let Some(message) = &self.status {
+            frame.render_widget(Line::from(format!(" {}", message)).italic(), status);
+        }
+        frame.render_widget(Line::from(format!(" {}", HELP)).dim(), help);
+    }
+}
+
+fn setting<'a>(name: &'a str, value: Span<'a>) -> Line<'a> {
+    Line::from(vec![Span::from(format!(" {:<10}", name)).dim(), value])
+}
+
+/// The variant of `T` after `value`, wrapping around.
+fn next<T: VariantNames + std::str::FromStr>(value: &T, name: fn(&T) -> &'static str) -> T
+where
+    T::Err: std::fmt::Debug,
+{
+    let i = T::VARIANTS.iter().position(|v| *v == name(value));
+    let i = i.map_or(0, |i| (i + 1) % T::VARIANTS.len());
+
+    T::VARIANTS[i].parse().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::crossterm::event::KeyModifiers;
+
+    fn press(app: &mut App, code: KeyCode) {
+        app.handle_key(KeyEvent::new(code, KeyModifiers::NONE));
+    }
+
+    #[test]
+    fn changes_settings_with_keys() {
+        let mut app = App::new(Config::default(), Selection::Clipboard);
+        press(&mut app, KeyCode::Up);
+        press(&mut app, KeyCode::Char('l'));
+        press(&mut app, KeyCode::Char('c'));
+        assert_eq!(app.words.len(), 7);
+        assert!(matches!(app.config.list, List::Short1));
+        assert!(matches!(app.config.case, Case::Capitalized));
+
+        press(&mut app, KeyCode::Char('s'));
+        press(&mut app, KeyCode::Char('+'));
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.config.separator, "+");
+        assert_eq!(app.password().split('+').count(), 7);
+
+        press(&mut app, KeyCode::Char('q'));
+        assert!(app.quit);
+    }
+
+    #[test]
+    fn rerolls_one_word() {
+        let mut app = App::new(Config::default(), Selection::Clipboard);
+        let before = app.words.clone();
+        press(&mut app, KeyCode::Right);
+        press(&mut app, KeyCode::Char('r'));
+
+        assert_ne!(app.words[1], before[1]);
+        assert_eq!(app.words[0], before[0]);
+        assert_eq!(app.words[2..], before[2..]);
+    }
+}
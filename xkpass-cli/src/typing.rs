@@ -0,0 +1,163 @@
+//! Scoring passwords by how much effort they take to type.
+//!
+//! Each character costs more the further its key is from the home row, and
+//! more again when it needs Shift. Each pair of consecutive characters costs
+//! nothing when it alternates hands, a little when it stays on the same hand
+//! and a lot when the same finger has to move to another key. Spaces are
+//! typed with the thumbs and break up pairs.
+
+use crate::explain::Row;
+use clap::ValueEnum;
+use std::fmt::{self, Display};
+use xkpass::Password;
+
+/// Number of candidates generated to pick the easiest to type from.
+pub const CANDIDATES: usize = 16;
+
+/// Keyboard layouts passwords can be scored on.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Layout {
+    Qwerty,
+    Dvorak,
+}
+
+/// Keys of each row, from the number row down, unshifted then shifted.
+const QWERTY: [(&str, &str); 4] = [
+    ("1234567890-=", "!@#$%^&*()_+"),
+    ("qwertyuiop[]", "QWERTYUIOP{}"),
+    ("asdfghjkl;'", "ASDFGHJKL:\""),
+    ("zxcvbnm,./", "ZXCVBNM<>?"),
+];
+
+const DVORAK: [(&str, &str); 4] = [
+    ("1234567890[]", "!@#$%^&*(){}"),
+    ("',.pyfgcrl/=", "\"<>PYFGCRL?+"),
+    ("aoeuidhtns-", "AOEUIDHTNS_"),
+    (";qjkxbmwvz", ":QJKXBMWVZ"),
+];
+
+/// Finger typing the key of each column, from the left pinky (0) to the
+/// right pinky (7).
+const FINGERS: [u8; 12] = [0, 1, 2, 3, 3, 4, 4, 5, 6, 7, 7, 7];
+
+/// Cost of reaching each row, from the number row down.
+const ROW_COST: [f64; 4] = [2.0, 0.5, 0.0, 1.0];
+
+const SHIFT_COST: f64 = 1.0;
+/// Cost of a character that isn't on the layout, such as an accented letter.
+const UNKNOWN_COST: f64 = 3.0;
+const SAME_HAND_COST: f64 = 0.5;
+const SAME_FINGER_COST: f64 = 2.0;
+
+#[derive(Clone, Copy)]
+struct Key {
+    row: usize,
+    column: usize,
+    shift: bool,
+}
+
+impl Key {
+    fn finger(self) -> u8 {
+        FINGERS[self.column]
+    }
+
+    fn left_hand(self) -> bool {
+        self.finger() < 4
+    }
+}
+
+impl Layout {
+    fn key(self, c: char) -> Option<Key> {
+        let rows = match self {
+            Layout::Qwerty => &QWERTY,
+            Layout::Dvorak => &DVORAK,
+        };
+        rows.iter().enumerate().find_map(|(row, (plain, shifted))| {
+            let find = |keys: &str| keys.chars().position(|key| key == c);
+            find(plain)
+                .map(|column| (column, false))
+                .or_else(|| find(shifted).map(|column| (column, true)))
+                .map(|(column, shift)| Key { row, column, shift })
+        })
+    }
+}
+
+impl Display for Layout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Layout::Qwerty => "QWERTY",
+            Layout::Dvorak => "Dvorak",
+        })
+    }
+}
+
+/// Effort it takes to type `text` on `layout`, lower is easier.
+pub fn effort(text: &str, layout: Layout) -> f64 {
+    let mut total = 0.0;
+    let mut previous: Option<Key> = None;
+    for c in text.chars() {
+        let key = layout.key(c);
+        total += match key {
+            Some(key) => ROW_COST[key.row] + if key.shift { SHIFT_COST } else { 0.0 },
+            None if c == ' ' => 0.0,
+            None => UNKNOWN_COST,
+        };
+        if let (Some(previous), Some(key)) = (previous, key) {
+            if previous.finger() == key.finger() {
+                if (previous.row, previous.column) != (key.row, key.column) {
+                    total += SAME_FINGER_COST;
+                }
+            } else if previous.left_hand() == key.left_hand() {
+                total += SAME_HAND_COST;
+            }
+        }
+        previous = key;
+    }
+    total
+}
+
+/// The candidate password that takes the least effort to type on `layout`,
+/// along with what came with it.
+///
+/// Picking one of `n` candidates costs at most log2(`n`) bits of entropy, an
+/// attacker who knows the selection only has to try the easiest passwords.
+pub fn easiest<T>(candidates: Vec<(Password, T)>, layout: Layout) -> (Password, T) {
+    candidates
+        .into_iter()
+        .map(|candidate| (effort(candidate.0.expose(), layout), candidate))
+        .min_by(|(a, _), (b, _)| a.total_cmp(b))
+        .map(|(_, candidate)| candidate)
+        .expect("at least one candidate")
+}
+
+/// The row of `--explain` for picking the easiest of [`CANDIDATES`] to type,
+/// counted at the most it can cost.
+pub fn row(layout: Layout) -> Row {
+    (
+        "typing",
+        format!(
+            "the easiest to type on {} of {} candidates",
+            layout, CANDIDATES
+        ),
+        -(CANDIDATES as f64).log2(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scores_typing_effort() {
+        // alternating hands beats rolling on one hand, which beats one finger
+        assert!(effort("fj", Layout::Qwerty) < effort("fd", Layout::Qwerty));
+        assert!(effort("fd", Layout::Qwerty) < effort("fr", Layout::Qwerty));
+        // home row beats the others, and Shift costs extra
+        assert_eq!(effort("asdf", Layout::Dvorak), 3.0);
+        assert_eq!(effort("aoeu", Layout::Dvorak), 1.5);
+        assert!(effort("Aoeu", Layout::Dvorak) > effort("aoeu", Layout::Dvorak));
+        // spaces break pairs up
+        assert_eq!(effort("f r", Layout::Qwerty), 0.5);
+        assert_eq!(effort("é", Layout::Qwerty), UNKNOWN_COST);
+    }
+}
@@ -0,0 +1,556 @@
+//! A vault of generated passwords, for keeping them without a password manager:
+//! `xkpass vault add LABEL` generates a password and keeps it under LABEL,
+//! `xkpass vault show LABEL` prints it back. `list` and `find` show the
+//! labels, the latter only those that fuzzily match a query, and `show` takes
+//! any query that matches a single entry.
+//!
+//! The vault is a single file, `vault` in the configuration directory unless
+//! `$XKPASS_VAULT` points elsewhere. Its entries are encrypted together with
+//! XChaCha20-Poly1305, under a key derived from a passphrase with Argon2id:
+//!
+//! | Bytes | Contents                                             |
+//! |-------|------------------------------------------------------|
+//! | 4     | `xkpv`                                               |
+//! | 1     | Version of the format, 1                             |
+//! | 12    | Memory in KiB, passes and lanes of Argon2id, LE u32s |
+//! | 16    | Salt of Argon2id                                     |
+//! | 24    | Nonce, a new one every time the vault is written     |
+//! | rest  | The entries as JSON, encrypted                       |
+//!
+//! The header is authenticated along with the entries, which takes deriving
+//! the key first, so costs above [`Cost::MAX`] are refused. The whole file is
+//! written again on every change, to a temporary file then renamed over the
+//! vault, so that a failed write never leaves it half written.
+//!
+//! After `xkpass vault biometric`, the key of the vault is kept by
+//! [`keyring`], which only gives it back once Touch ID or Windows Hello
+//! recognizes the user, and the passphrase is only asked for when it doesn't.
+//! The key, the decrypted entries and the plaintext they are read from and
+//! written to are wiped from memory once done with.
+
+use crate::{
+    clipboard::{self, Selection},
+    config_file,
+    kdf::{self, Cost, SALT_LEN},
+    keyring,
+};
+use chacha20poly1305::{
+    aead::{Aead, Payload},
+    KeyInit, XChaCha20Poly1305, XNonce,
+};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    io::{self, BufRead, IsTerminal},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+use xkpass::Config;
+use zeroize::{Zeroize, Zeroizing};
+
+const MAGIC: &[u8; 4] = b"xkpv";
+const VERSION: u8 = 1;
+const NONCE_LEN: usize = 24;
+const HEADER_LEN: usize = MAGIC.len() + 1 + 12 + SALT_LEN + NONCE_LEN;
+
+/// A password kept in the vault.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Entry {
+    pub password: String,
+    /// When it was added, in seconds since the Unix epoch.
+    pub created: u64,
+    /// Address of the site the password is for.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+/// The decrypted vault, with the key to encrypt it again.
+pub struct Vault {
+    pub entries: BTreeMap<String, Entry>,
+    cost: Cost,
+    salt: [u8; SALT_LEN],
+    key: Zeroizing<[u8; 32]>,
+}
+
+impl Drop for Vault {
+    fn drop(&mut self) {
+        for entry in self.entries.values_mut() {
+            entry.password.zeroize();
+        }
+    }
+}
+
+impl Vault {
+    /// An empty vault, locked with `passphrase`.
+    pub fn create(passphrase: &str, cost: Cost) -> io::Result<Self> {
+        let salt = kdf::salt();
+        Ok(Self {
+            entries: BTreeMap::new(),
+            cost,
+            salt,
+            key: derive(passphrase, &salt, cost)?,
+        })
+    }
+
+    /// Decrypt the vault in `bytes` with `passphrase`.
+    pub fn open(bytes: &[u8], passphrase: &str) -> io::Result<Self> {
+        let (cost, salt) = header(bytes)?;
+        Self::open_with_key(bytes, derive(passphrase, &salt, cost)?)
+    }
+
+    /// Decrypt the vault in `bytes` with the `key` derived from its passphrase.
+    pub fn open_with_key(bytes: &[u8], key: Zeroizing<[u8; 32]>) -> io::Result<Self> {
+        let invalid = |message| io::Error::new(io::ErrorKind::InvalidData, message);
+        let (cost, salt) = header(bytes)?;
+
+        let (header, ciphertext) = bytes.split_at(HEADER_LEN);
+        let nonce = XNonce::try_from(&header[HEADER_LEN - NONCE_LEN..]).expect("nonce length");
+        let payload = Payload {
+            msg: ciphertext,
+            aad: header,
+        };
+        let plaintext = XChaCha20Poly1305::new((&*key).into())
+            .decrypt(&nonce, payload)
+            .map(Zeroizing::new)
+            .map_err(|_| invalid("wrong passphrase, or the vault was tampered with"))?;
+        let entries = serde_json::from_slice(&plaintext).map_err(io::Error::from)?;
+
+        Ok(Self {
+            entries,
+            cost,
+            salt,
+            key,
+        })
+    }
+
+    /// The vault encrypted under a new nonce.
+    pub fn seal(&self) -> io::Result<Vec<u8>> {
+        let mut nonce = [0; NONCE_LEN];
+        rand::rngs::OsRng.fill_bytes(&mut nonce);
+        let mut bytes = Vec::with_capacity(HEADER_LEN);
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        for field in [self.cost.memory, self.cost.passes, self.cost.lanes] {
+            bytes.extend_from_slice(&field.to_le_bytes());
+        }
+        bytes.extend_from_slice(&self.salt);
+        bytes.extend_from_slice(&nonce);
+
+        let plaintext = Zeroizing::new(serde_json::to_vec(&self.entries)?);
+        let payload = Payload {
+            msg: &plaintext,
+            aad: &bytes,
+        };
+        let ciphertext = XChaCha20Poly1305::new((&*self.key).into())
+            .encrypt(&XNonce::from(nonce), payload)
+            .map_err(|_| io::Error::other("the vault couldn't be encrypted"))?;
+        bytes.extend_from_slice(&ciphertext);
+        Ok(bytes)
+    }
+
+    /// Keep `password` under `label`, which mustn't be taken yet.
+    pub fn add(&mut self, label: &str, password: &str, url: Option<&str>) -> io::Result<()> {
+        if self.entries.contains_key(label) {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{:?} is already in the vault", label),
+            ));
+        }
+        let created = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_secs());
+        let entry = Entry {
+            password: password.to_string(),
+            created,
+            url: url.map(str::to_string),
+        };
+        self.entries.insert(label.to_string(), entry);
+        Ok(())
+    }
+
+    /// The entries whose label or address fuzzily match `query`, best first.
+    pub fn find(&self, query: &str) -> Vec<(&str, &Entry)> {
+        let mut found: Vec<_> = self
+            .entries
+            .iter()
+            .filter_map(|(label, entry)| {
+                let by_url = entry.url.as_deref().and_then(|url| score(query, url));
+                let best = score(query, label).into_iter().chain(by_url).min()?;
+                Some((best, label.as_str(), entry))
+            })
+            .collect();
+        found.sort_by_key(|&(score, label, _)| (score, label));
+        found
+            .into_iter()
+            .map(|(_, label, entry)| (label, entry))
+            .collect()
+    }
+
+    /// The entry kept under `query`, or the only one that matches it.
+    pub fn get(&self, query: &str) -> io::Result<(&str, &Entry)> {
+        if let Some((label, entry)) = self.entries.get_key_value(query) {
+            return Ok((label, entry));
+        }
+        match self.find(query)[..] {
+            [found] => Ok(found),
+            [] => Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("nothing in the vault matches {:?}", query),
+            )),
+            ref found => {
+                let labels: Vec<&str> = found.iter().map(|&(label, _)| label).collect();
+                Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("{:?} matches {}", query, labels.join(", ")),
+                ))
+            }
+        }
+    }
+}
+
+/// How well `text` matches `query`, lower being better, if it has all the
+/// characters of the query in order, whatever their case.
+///
+/// The characters of `text` skipped between the first and last ones matched
+/// count first, so that `gh` matches `github` better than `graph`, then where
+/// the match starts, so that prefixes come before the rest.
+fn score(query: &str, text: &str) -> Option<(usize, usize)> {
+    let text: Vec<char> = text.chars().flat_map(char::to_lowercase).collect();
+    let mut matched = Vec::new();
+    let mut from = 0;
+    for c in query.chars().flat_map(char::to_lowercase) {
+        let at = from + text[from..].iter().position(|&t| t == c)?;
+        matched.push(at);
+        from = at + 1;
+    }
+    match (matched.first(), matched.last()) {
+        (Some(&first), Some(&last)) => Some((last - first + 1 - matched.len(), first)),
+        _ => Some((0, 0)),
+    }
+}
+
+/// The line of an entry in lists, its label followed by its address.
+fn line(label: &str, entry: &Entry) -> String {
+    match &entry.url {
+        Some(url) => format!("{}  {}", label, url),
+        None => label.to_string(),
+    }
+}
+
+/// The cost and salt of Argon2id in the header of the vault in `bytes`.
+fn header(bytes: &[u8]) -> io::Result<(Cost, [u8; SALT_LEN])> {
+    let invalid = |message| io::Error::new(io::ErrorKind::InvalidData, message);
+    if bytes.len() < HEADER_LEN || &bytes[..MAGIC.len()] != MAGIC {
+        return Err(invalid("not an xkpass vault"));
+    }
+    if bytes[MAGIC.len()] != VERSION {
+        return Err(invalid("the vault was written by a newer xkpass"));
+    }
+    let mut fields = bytes[MAGIC.len() + 1..]
+        .chunks_exact(4)
+        .map(|b| u32::from_le_bytes(b.try_into().expect("chunks of 4 bytes")));
+    let cost = Cost {
+        memory: fields.next().expect("the header has the cost"),
+        passes: fields.next().expect("the header has the cost"),
+        lanes: fields.next().expect("the header has the cost"),
+    };
+    // the header is only authenticated once the key is derived
+    if !cost.within(Cost::MAX) {
+        return Err(invalid(
+            "the vault asks for more memory or passes of Argon2id than xkpass allows, \
+             it may have been tampered with",
+        ));
+    }
+    let salt_at = MAGIC.len() + 1 + 12;
+    let salt = bytes[salt_at..salt_at + SALT_LEN]
+        .try_into()
+        .expect("the header has the salt");
+    Ok((cost, salt))
+}
+
+/// The key of `passphrase` and `salt`.
+fn derive(passphrase: &str, salt: &[u8], cost: Cost) -> io::Result<Zeroizing<[u8; 32]>> {
+    let mut key = Zeroizing::new([0; 32]);
+    kdf::argon2id(passphrase.as_bytes(), salt, cost, &mut *key)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    Ok(key)
+}
+
+/// Generate a password with `config`, keep it under `label` along with `url`
+/// and print it.
+pub fn run_add(config: Config, label: &str, url: Option<&str>) -> io::Result<()> {
+    let password = xkpass::generate_password(config)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+    let path = path()?;
+    let mut vault = load(&path, true)?;
+    vault.add(label, password.expose(), url)?;
+    save(&vault, &path)?;
+    println!("{}", password.expose());
+    Ok(())
+}
+
+/// Print the password kept under `query`, or copy it to `copy`.
+pub fn run_show(query: &str, copy: Option<Selection>) -> io::Result<()> {
+    let vault = load(&path()?, false)?;
+    let (label, entry) = vault.get(query)?;
+    match copy {
+        Some(selection) => {
+            clipboard::copy(&entry.password, selection)?;
+            eprintln!("copied the password of {} to the clipboard", label);
+        }
+        None => println!("{}", entry.password),
+    }
+    Ok(())
+}
+
+/// Print the label of every entry, and its address if it has one.
+pub fn run_list() -> io::Result<()> {
+    let vault = load(&path()?, false)?;
+    for (label, entry) in &vault.entries {
+        println!("{}", line(label, entry));
+    }
+    Ok(())
+}
+
+/// Print the entries matching `query`, best first.
+pub fn run_find(query: &str) -> io::Result<()> {
+    let vault = load(&path()?, false)?;
+    let found = vault.find(query);
+    if found.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("nothing in the vault matches {:?}", query),
+        ));
+    }
+    for (label, entry) in found {
+        println!("{}", line(label, entry));
+    }
+    Ok(())
+}
+
+/// Keep the key of the vault for Touch ID or Windows Hello to unlock it from
+/// now on, once the passphrase is given, or forget it with `off`.
+pub fn run_biometric(off: bool) -> io::Result<()> {
+    let path = path()?;
+    let bytes = fs::read(&path).map_err(|err| match err.kind() {
+        io::ErrorKind::NotFound => io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("there is no vault at {} yet", path.display()),
+        ),
+        _ => err,
+    })?;
+    let account = account(&path)?;
+    if off {
+        keyring::remove(&account)?;
+        eprintln!("the vault only opens with its passphrase again");
+        return Ok(());
+    }
+
+    let vault = Vault::open(&bytes, &passphrase("Vault passphrase: ")?)?;
+    keyring::store(&account, &*vault.key)?;
+    eprintln!("the vault opens with {} from now on", keyring::NAME);
+    Ok(())
+}
+
+/// The name the key of the vault at `path` is kept under in [`keyring`],
+/// apart from the labels of `xkpass keyring`.
+fn account(path: &Path) -> io::Result<String> {
+    Ok(format!("vault:{}", path.canonicalize()?.display()))
+}
+
+/// The vault in `bytes` at `path`, opened with the key kept by
+/// [`run_biometric`] once the user is recognized, if there is one.
+///
+/// Anything going wrong is only reported, the passphrase can still open the vault.
+fn unlock(path: &Path, bytes: &[u8]) -> Option<Vault> {
+    let unlocked = account(path)
+        .and_then(|account| keyring::load(&account))
+        .and_then(|key| match key {
+            Some(key) => {
+                let key: [u8; 32] = key.as_slice().try_into().map_err(|_| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("the key kept for {} isn't 32 bytes long", keyring::NAME),
+                    )
+                })?;
+                Vault::open_with_key(bytes, Zeroizing::new(key)).map(Some)
+            }
+            None => Ok(None),
+        });
+    match unlocked {
+        Ok(vault) => vault,
+        Err(err) => {
+            eprintln!("xkpass: {}, asking for the passphrase instead", err);
+            None
+        }
+    }
+}
+
+/// Path of the vault: `$XKPASS_VAULT` if it is set, otherwise `vault` in
+/// [`config_file::config_dir`].
+fn path() -> io::Result<PathBuf> {
+    match env::var_os("XKPASS_VAULT") {
+        Some(path) => Ok(PathBuf::from(path)),
+        None => config_file::config_dir()
+            .map(|dir| dir.join("vault"))
+            .ok_or_else(|| io::Error::other("there is no configuration directory for the vault")),
+    }
+}
+
+/// Open the vault at `path`, with Touch ID or Windows Hello if it was set up
+/// with `vault biometric`, otherwise asking for its passphrase, or create it
+/// if it doesn't exist yet and `create` is set.
+fn load(path: &Path, create: bool) -> io::Result<Vault> {
+    match fs::read(path) {
+        Ok(bytes) => match unlock(path, &bytes) {
+            Some(vault) => Ok(vault),
+            None => Vault::open(&bytes, &passphrase("Vault passphrase: ")?),
+        },
+        Err(err) if err.kind() == io::ErrorKind::NotFound && create => {
+            let passphrase = passphrase("New vault passphrase: ")?;
+            if io::stdin().is_terminal() && self::passphrase("Once more: ")? != passphrase {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "the passphrases don't match",
+                ));
+            }
+            Vault::create(&passphrase, Cost::DEFAULT)
+        }
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("there is no vault at {} yet", path.display()),
+        )),
+        Err(err) => Err(err),
+    }
+}
+
+/// Write `vault` to `path`, readable by the user only.
+fn save(vault: &Vault, path: &Path) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let temporary = path.with_extension("tmp");
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    io::Write::write_all(&mut options.open(&temporary)?, &vault.seal()?)?;
+    fs::rename(&temporary, path)
+}
+
+/// Ask for the passphrase on the terminal without echoing it, or read it
+/// from the first line of stdin when it isn't a terminal, for scripts.
+fn passphrase(prompt: &str) -> io::Result<String> {
+    let passphrase = if io::stdin().is_terminal() {
+        rpassword::prompt_password(prompt)?
+    } else {
+        let mut line = String::new();
+        io::stdin().lock().read_line(&mut line)?;
+        line.trim_end_matches(&['\r', '\n'][..]).to_string()
+    };
+    if passphrase.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "the vault passphrase can't be empty",
+        ));
+    }
+    Ok(passphrase)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Cheap enough for tests.
+    const COST: Cost = Cost {
+        memory: 64,
+        passes: 1,
+        lanes: 1,
+    };
+
+    #[test]
+    fn seals_and_opens() {
+        let mut vault = Vault::create("hunter2", COST).unwrap();
+        vault.add("mail", "correct horse", None).unwrap();
+        assert!(vault.add("mail", "battery staple", None).is_err());
+        let bytes = vault.seal().unwrap();
+        assert!(!bytes
+            .windows(b"correct".len())
+            .any(|window| window == b"correct"));
+
+        let opened = Vault::open(&bytes, "hunter2").unwrap();
+        assert_eq!(opened.get("mail").unwrap().1.password, "correct horse");
+        assert_eq!(opened.cost, COST);
+        assert!(opened.get("bank").is_err());
+        // a new nonce every time
+        assert_ne!(opened.seal().unwrap(), bytes);
+    }
+
+    #[test]
+    fn opens_with_the_key() {
+        let mut vault = Vault::create("hunter2", COST).unwrap();
+        vault.add("mail", "correct horse", None).unwrap();
+        let bytes = vault.seal().unwrap();
+
+        let opened = Vault::open_with_key(&bytes, vault.key.clone()).unwrap();
+        assert_eq!(opened.get("mail").unwrap().1.password, "correct horse");
+        assert!(Vault::open_with_key(&bytes, Zeroizing::new([0; 32])).is_err());
+    }
+
+    #[test]
+    fn rejects_wrong_passphrases_and_tampering() {
+        let bytes = Vault::create("hunter2", COST).unwrap().seal().unwrap();
+        assert!(Vault::open(&bytes, "hunter3").is_err());
+
+        let mut tampered = bytes.clone();
+        // the number of passes, which is authenticated too
+        tampered[MAGIC.len() + 5] = 2;
+        assert!(Vault::open(&tampered, "hunter2").is_err());
+        assert!(Vault::open(b"xkpv", "hunter2").is_err());
+        assert!(Vault::open(&bytes[..HEADER_LEN + 3], "hunter2").is_err());
+    }
+
+    #[test]
+    fn rejects_inflated_costs() {
+        let bytes = Vault::create("hunter2", COST).unwrap().seal().unwrap();
+        // 4 TiB of memory, which would be allocated before the tag is checked
+        let mut inflated = bytes.clone();
+        inflated[MAGIC.len() + 1..MAGIC.len() + 5].copy_from_slice(&u32::MAX.to_le_bytes());
+        let err = Vault::open(&inflated, "hunter2").err().unwrap();
+        assert!(err.to_string().contains("more memory or passes"));
+
+        let mut inflated = bytes;
+        inflated[MAGIC.len() + 5..MAGIC.len() + 9].copy_from_slice(&1_000_000u32.to_le_bytes());
+        let err = Vault::open(&inflated, "hunter2").err().unwrap();
+        assert!(err.to_string().contains("more memory or passes"));
+    }
+
+    #[test]
+    fn finds_entries_fuzzily() {
+        let mut vault = Vault::create("hunter2", COST).unwrap();
+        vault.add("github", "a", None).unwrap();
+        vault.add("graph", "b", None).unwrap();
+        vault
+            .add("bank", "c", Some("https://online.example.com"))
+            .unwrap();
+
+        let labels = |query| -> Vec<&str> {
+            vault
+                .find(query)
+                .into_iter()
+                .map(|(label, _)| label)
+                .collect()
+        };
+        assert_eq!(labels("gh"), ["github", "graph"]);
+        assert_eq!(labels("GH"), ["github", "graph"]);
+        assert_eq!(labels("example"), ["bank"]);
+        assert!(labels("hg").is_empty());
+
+        assert_eq!(vault.get("github").unwrap().1.password, "a");
+        assert_eq!(vault.get("exa").unwrap().0, "bank");
+        assert!(vault.get("gh").is_err());
+        assert!(vault.get("zzz").is_err());
+    }
+}
@@ -0,0 +1,565 @@
+//! Word lists chosen by name: the embedded ones, or the user's own lists kept as
+//! `<name>.txt` files in the `wordlists` directory of the configuration directory.
+//!
+//! A word list file has one word per line. Lines starting with `#` are comments,
+//! and for lists in diceware format, such as EFF's, only the last column is used.
+//!
+//! Lists of [`MAP_THRESHOLD`] bytes or more are memory-mapped instead of read.
+//! Either way, a list is indexed by the offset of each of its distinct words,
+//! and words are only looked up when used, so huge lists are never copied.
+//!
+//! The words of each list are cached after its first use, in a binary file of
+//! the cache directory named after a hash of the list's path, size and
+//! modification time. Later runs map the cache, check it once, and skip
+//! parsing entirely.
+//!
+//! The list called `-` is read from stdin. To generate a single password its
+//! words are streamed, so that a pipe can provide more words than fit in memory.
+//!
+//! Lists named after a locale, such as `fr.txt` or `pt_BR.txt`, become the
+//! default list when the locale of the environment matches.
+
+use crate::config_file;
+use memmap2::Mmap;
+use rand::RngCore;
+use serde::Deserialize;
+use std::{
+    collections::hash_map::DefaultHasher,
+    env, fmt, fs,
+    hash::{Hash, Hasher},
+    io::{self, BufRead, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+    process,
+    str::{self, FromStr},
+};
+use xkpass::{Config, List, Password};
+
+/// Name of a word list.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(try_from = "String")]
+pub enum ListName {
+    Embedded(List),
+    Custom(String),
+    Stdin,
+}
+
+impl ListName {
+    /// Every word list: the embedded ones followed by the user's.
+    pub fn all() -> Vec<ListName> {
+        let embedded = List::ALL.into_iter().map(ListName::Embedded);
+        let custom = custom_names().into_iter().map(ListName::Custom);
+        embedded.chain(custom).collect()
+    }
+
+    /// Open the list, its words can then be borrowed from the [`Source`].
+    pub fn open(&self) -> io::Result<Source> {
+        match self {
+            ListName::Embedded(list) => Ok(Source::Embedded(list.clone())),
+            ListName::Custom(name) => open(name),
+            ListName::Stdin => {
+                let mut contents = String::new();
+                io::stdin().lock().read_to_string(&mut contents)?;
+                Ok(Source::Read(Indexed::new(contents)?))
+            }
+        }
+    }
+}
+
+/// Size from which the user's lists are memory-mapped rather than read.
+pub const MAP_THRESHOLD: u64 = 16 << 20;
+
+/// The contents of an open word list.
+pub enum Source {
+    Embedded(List),
+    Read(Indexed<String>),
+    Mapped(Indexed<Mmap>),
+    Cached(Cache),
+}
+
+impl Source {
+    /// The number of distinct words of the list.
+    pub fn len(&self) -> usize {
+        match self {
+            Source::Embedded(list) => list.as_slice().len(),
+            Source::Read(indexed) => indexed.starts.len(),
+            Source::Mapped(indexed) => indexed.starts.len(),
+            Source::Cached(cache) => cache.len,
+        }
+    }
+
+    /// The word at `index`, pointing into the contents of the list.
+    ///
+    /// Panics if `index` is not less than [`Source::len`].
+    pub fn word(&self, index: usize) -> &str {
+        match self {
+            Source::Embedded(list) => list.as_slice()[index],
+            Source::Read(indexed) => indexed.word(index),
+            Source::Mapped(indexed) => indexed.word(index),
+            Source::Cached(cache) => cache_word(&cache.map, cache.len, index),
+        }
+    }
+
+    /// The words of the list in order, each looked up as it comes.
+    pub fn iter(&self) -> impl Iterator<Item = &str> + Clone {
+        (0..self.len()).map(move |index| self.word(index))
+    }
+
+    /// The words of the list, pointing into its contents.
+    pub fn words(&self) -> Vec<&str> {
+        self.iter().collect()
+    }
+}
+
+/// The contents of a list with the offset of each of its distinct words, in
+/// the order they first appear.
+pub struct Indexed<T> {
+    contents: T,
+    starts: Vec<usize>,
+}
+
+impl<T: AsRef<[u8]>> Indexed<T> {
+    /// Check that `contents` is UTF-8 and index its words, like
+    /// [`xkpass::parse_words`] but without gathering them.
+    fn new(contents: T) -> io::Result<Self> {
+        let text = str::from_utf8(contents.as_ref())
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        let offset = |word: &str| word.as_ptr() as usize - text.as_ptr() as usize;
+        let word = |start: usize| text[start..].split(char::is_whitespace).next();
+
+        let mut starts: Vec<usize> = text
+            .lines()
+            .filter_map(xkpass::parse_line)
+            .map(offset)
+            .collect();
+        // sorted by word, then by offset, so that the first of duplicates is kept
+        starts.sort_unstable_by(|&a, &b| word(a).cmp(&word(b)).then(a.cmp(&b)));
+        starts.dedup_by(|later, first| word(*later) == word(*first));
+        starts.sort_unstable();
+
+        Ok(Indexed { contents, starts })
+    }
+
+    fn word(&self, index: usize) -> &str {
+        // SAFETY: the contents were checked to be UTF-8 when indexed, and
+        // like any file xkpass reads, mapped lists must not change while it runs
+        let text = unsafe { str::from_utf8_unchecked(self.contents.as_ref()) };
+        let start = self.starts[index];
+        text[start..]
+            .split(char::is_whitespace)
+            .next()
+            .unwrap_or_default()
+    }
+}
+
+/// A mapped cache file written by [`write_cache`], checked once when mapped.
+pub struct Cache {
+    map: Mmap,
+    len: usize,
+}
+
+impl FromStr for ListName {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        if name == "-" {
+            return Ok(ListName::Stdin);
+        }
+        if let Ok(list) = name.parse() {
+            return Ok(ListName::Embedded(list));
+        }
+
+        // names end up in a path, so they can't point outside of the directory
+        let valid = |c: char| c.is_alphanumeric() || c == '-' || c == '_';
+        if name.is_empty() || !name.chars().all(valid) {
+            return Err(format!("invalid word list name {:?}", name));
+        }
+
+        Ok(ListName::Custom(name.to_string()))
+    }
+}
+
+impl TryFrom<String> for ListName {
+    type Error = String;
+
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        name.parse()
+    }
+}
+
+impl fmt::Display for ListName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ListName::Embedded(list) => list.fmt(f),
+            ListName::Custom(name) => f.write_str(name),
+            ListName::Stdin => f.write_str("stdin"),
+        }
+    }
+}
+
+/// Directory holding the user's word lists.
+pub fn dir() -> Option<PathBuf> {
+    config_file::config_dir().map(|dir| dir.join("wordlists"))
+}
+
+/// Names of the user's word lists, sorted.
+pub fn custom_names() -> Vec<String> {
+    let entries = match dir().map(fs::read_dir) {
+        Some(Ok(entries)) => entries,
+        _ => return Vec::new(),
+    };
+
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            if path.extension()? != "txt" {
+                return None;
+            }
+            let name = path.file_stem()?.to_str()?;
+            match name.parse() {
+                Ok(ListName::Custom(name)) => Some(name),
+                _ => None,
+            }
+        })
+        .collect();
+    names.sort_unstable();
+
+    names
+}
+
+/// The user's list named after the locale of the environment, if there is one.
+pub fn for_locale() -> Option<ListName> {
+    // the same precedence as for messages, with LANG as the fallback
+    let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .into_iter()
+        .filter_map(|var| env::var(var).ok())
+        .find(|locale| !locale.is_empty())?;
+
+    let names = custom_names();
+    locale_names(&locale)
+        .into_iter()
+        .find(|name| names.contains(name))
+        .map(ListName::Custom)
+}
+
+/// Names a list for `locale` could have, from the most to the least specific:
+/// `pt_BR` and `pt` for `pt_BR.UTF-8`.
+fn locale_names(locale: &str) -> Vec<String> {
+    let locale = locale.split(&['.', '@'][..]).next().unwrap_or_default();
+    if locale == "C" || locale == "POSIX" {
+        return Vec::new();
+    }
+
+    let mut names = vec![locale.to_string()];
+    if let Some((language, _)) = locale.split_once('_') {
+        names.push(language.to_string());
+    }
+    names.retain(|name| matches!(name.parse(), Ok(ListName::Custom(_))));
+
+    names
+}
+
+/// Open the user's list called `name`.
+pub fn open(name: &str) -> io::Result<Source> {
+    let dir = dir().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            "can't find the configuration directory",
+        )
+    })?;
+
+    let path = dir.join(format!("{}.txt", name));
+    match config_file::cache_dir() {
+        Some(cache) => cached(&path, &cache.join("wordlists")),
+        None => read(&path),
+    }
+}
+
+fn with_path(path: &Path) -> impl Fn(io::Error) -> io::Error + '_ {
+    move |err| io::Error::new(err.kind(), format!("{}: {}", path.display(), err))
+}
+
+fn read(path: &Path) -> io::Result<Source> {
+    let mut file = fs::File::open(path).map_err(with_path(path))?;
+    if file.metadata().map_err(with_path(path))?.len() < MAP_THRESHOLD {
+        let mut contents = String::new();
+        file.read_to_string(&mut contents)
+            .map_err(with_path(path))?;
+        return Ok(Source::Read(
+            Indexed::new(contents).map_err(with_path(path))?,
+        ));
+    }
+
+    // SAFETY: like any file xkpass reads, the list must not change while it runs
+    let map = unsafe { Mmap::map(&file) }.map_err(with_path(path))?;
+    Ok(Source::Mapped(Indexed::new(map).map_err(with_path(path))?))
+}
+
+/// Marks the cache files, the last byte is the version of their format.
+const CACHE_MAGIC: &[u8; 8] = b"xkpass\0\x01";
+
+/// Open the list at `path` from its cache in the `cache` directory, reading
+/// the list and writing the cache if it isn't there yet.
+fn cached(path: &Path, cache: &Path) -> io::Result<Source> {
+    let metadata = fs::metadata(path).map_err(with_path(path))?;
+    let mut hasher = DefaultHasher::new();
+    path.hash(&mut hasher);
+    metadata.len().hash(&mut hasher);
+    metadata.modified().ok().hash(&mut hasher);
+    let cache = cache.join(format!("{:016x}.bin", hasher.finish()));
+
+    if let Ok(source) = map_cache(&cache) {
+        return Ok(source);
+    }
+    let source = read(path)?;
+    // the cache only saves time, without it the list is used as it was read
+    match write_cache(&cache, source.iter()).and_then(|()| map_cache(&cache)) {
+        Ok(cached) => Ok(cached),
+        Err(_) => Ok(source),
+    }
+}
+
+fn map_cache(path: &Path) -> io::Result<Source> {
+    let file = fs::File::open(path)?;
+    // SAFETY: caches are only replaced by renaming a new file over them
+    let map = unsafe { Mmap::map(&file) }?;
+    if !map.starts_with(CACHE_MAGIC) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "not a word list cache",
+        ));
+    }
+    let len = cache_len(&map)?;
+
+    Ok(Source::Cached(Cache { map, len }))
+}
+
+/// Write `words` to a cache file: [`CACHE_MAGIC`], the number of words, the
+/// offset at which each word ends, and the words themselves, all in a row.
+fn write_cache<'a>(path: &Path, words: impl Iterator<Item = &'a str> + Clone) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    // written aside and renamed, so that other runs never see half a cache
+    let partial = path.with_extension(format!("{}.partial", process::id()));
+    let mut out = BufWriter::new(fs::File::create(&partial)?);
+
+    out.write_all(CACHE_MAGIC)?;
+    out.write_all(&(words.clone().count() as u64).to_le_bytes())?;
+    let mut end = 0u64;
+    for word in words.clone() {
+        end += word.len() as u64;
+        out.write_all(&end.to_le_bytes())?;
+    }
+    for word in words {
+        out.write_all(word.as_bytes())?;
+    }
+    out.into_inner().map_err(io::IntoInnerError::into_error)?;
+
+    fs::rename(&partial, path)
+}
+
+/// The offset table and the words of a cache file written by [`write_cache`],
+/// if it has room for `count` words.
+fn cache_parts(cache: &[u8], count: usize) -> Option<(&[u8], &[u8])> {
+    let rest = cache.strip_prefix(CACHE_MAGIC)?;
+    let table = count.checked_add(1)?.checked_mul(8)?;
+    rest.get(8..table).zip(rest.get(table..))
+}
+
+/// The end offset at `index` of the offset table of a cache file.
+fn cache_end(ends: &[u8], index: usize) -> usize {
+    u64::from_le_bytes(ends[index * 8..index * 8 + 8].try_into().unwrap()) as usize
+}
+
+/// The number of words of a cache file written by [`write_cache`], after
+/// checking that every word can be looked up with [`cache_word`].
+fn cache_len(cache: &[u8]) -> io::Result<usize> {
+    let corrupt = || io::Error::new(io::ErrorKind::InvalidData, "corrupt word list cache");
+
+    let count = cache
+        .strip_prefix(CACHE_MAGIC)
+        .and_then(|rest| rest.get(..8))
+        .and_then(|count| usize::try_from(u64::from_le_bytes(count.try_into().ok()?)).ok())
+        .ok_or_else(corrupt)?;
+    let (ends, data) = cache_parts(cache, count).ok_or_else(corrupt)?;
+    let data = str::from_utf8(data).map_err(|_| corrupt())?;
+
+    let mut start = 0;
+    for i in 0..count {
+        let end = cache_end(ends, i);
+        if end < start || !data.is_char_boundary(end) {
+            return Err(corrupt());
+        }
+        start = end;
+    }
+
+    Ok(count)
+}
+
+/// The word at `index` of a cache file of `len` words checked by [`cache_len`].
+fn cache_word(cache: &[u8], len: usize, index: usize) -> &str {
+    assert!(index < len, "no word {} in a cache of {}", index, len);
+    let (ends, data) = cache_parts(cache, len).expect("the cache was checked");
+    let start = match index {
+        0 => 0,
+        _ => cache_end(ends, index - 1),
+    };
+    str::from_utf8(&data[start..cache_end(ends, index)]).expect("the cache was checked")
+}
+
+/// Generate a password out of the words piped to stdin with `rng`, keeping
+/// only as many as the password needs, and return it with the number of
+/// words read.
+///
+/// With `mobile`, words that can't be typed on a phone's letters layer are skipped.
+pub fn generate_from_stdin(
+    config: &Config,
+    mobile: bool,
+    rng: &mut dyn RngCore,
+) -> Result<(Password, usize), String> {
+    generate_from_reader(io::stdin().lock(), config, mobile, rng)
+}
+
+fn generate_from_reader(
+    reader: impl BufRead,
+    config: &Config,
+    mobile: bool,
+    mut rng: &mut dyn RngCore,
+) -> Result<(Password, usize), String> {
+    let mut error = None;
+    let lines = reader.lines().map_while(|line| match line {
+        Ok(line) => Some(line),
+        Err(err) => {
+            error = Some(err);
+            None
+        }
+    });
+    let words = lines
+        .filter_map(|line| xkpass::parse_line(&line).map(String::from))
+        .filter(|word| !mobile || crate::mobile::word(word));
+    let generated = xkpass::generate_password_from_stream_with(words, config, &mut rng);
+
+    match error {
+        Some(err) => Err(format!("stdin: {}", err)),
+        None => generated.map_err(|err| err.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_names() {
+        assert!(matches!(
+            "short1".parse(),
+            Ok(ListName::Embedded(List::Short1))
+        ));
+        assert!(matches!(
+            "german-2".parse(),
+            Ok(ListName::Custom(name)) if name == "german-2"
+        ));
+        assert!(matches!("-".parse(), Ok(ListName::Stdin)));
+        assert!("../secrets".parse::<ListName>().is_err());
+        assert!("".parse::<ListName>().is_err());
+    }
+
+    #[test]
+    fn seeds_words_read_from_stdin() {
+        let words = "apple\nbanana\ncherry\ndate\nelder\nfig\ngrape\n";
+        let config = Config {
+            number: 3,
+            ..Config::default()
+        };
+        let generate = |seed| {
+            let mut rng = crate::recipe::rng([seed; 32]);
+            generate_from_reader(words.as_bytes(), &config, false, &mut rng)
+                .unwrap()
+                .0
+                .expose()
+                .to_string()
+        };
+        assert_eq!(generate(1), generate(1));
+        // other seeds pick other words
+        assert!((1..8).any(|seed| generate(seed) != generate(0)));
+    }
+
+    #[test]
+    fn names_lists_after_locales() {
+        assert_eq!(locale_names("pt_BR.UTF-8"), ["pt_BR", "pt"]);
+        assert_eq!(locale_names("de_DE@euro"), ["de_DE", "de"]);
+        assert_eq!(locale_names("fr"), ["fr"]);
+        assert!(locale_names("C.UTF-8").is_empty());
+        assert!(locale_names("").is_empty());
+    }
+
+    #[test]
+    fn maps_huge_lists() {
+        let path = std::env::temp_dir().join(format!("xkpass-test-{}.txt", std::process::id()));
+        // long words keep the number of lines, and the test, small
+        let padding = "w".repeat(4000);
+        let mut contents = String::from("# huge\n");
+        let mut i = 0;
+        while (contents.len() as u64) < MAP_THRESHOLD {
+            contents += &format!("{}\t{}{}\n", i, padding, i);
+            i += 1;
+        }
+        fs::write(&path, &contents).unwrap();
+
+        let source = read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(source, Source::Mapped(_)));
+        assert_eq!(source.len(), i);
+        assert_eq!(source.word(0), format!("{}0", padding));
+        assert_eq!(source.word(i - 1), format!("{}{}", padding, i - 1));
+    }
+
+    #[test]
+    fn caches_lists() {
+        let dir = std::env::temp_dir().join(format!("xkpass-cache-test-{}", std::process::id()));
+        let path = dir.join("fruits.txt");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(&path, "# fruits\napple\nbanana\napple\nçerise\n").unwrap();
+
+        for _ in 0..2 {
+            let source = cached(&path, &dir.join("cache")).unwrap();
+            assert!(matches!(source, Source::Cached(_)));
+            assert_eq!(source.words(), ["apple", "banana", "çerise"]);
+        }
+        let entries = fs::read_dir(dir.join("cache")).unwrap().count();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(entries, 1);
+    }
+
+    #[test]
+    fn indexes_distinct_words() {
+        let source = Source::Read(
+            Indexed::new("# list\n11 pear\nfig\n\n12\tpear \nkiwi\r\nfig".to_string()).unwrap(),
+        );
+        assert_eq!(source.words(), ["pear", "fig", "kiwi"]);
+        assert!(Indexed::new(&b"fig\n\xff\n"[..]).is_err());
+    }
+
+    #[test]
+    fn rejects_corrupt_caches() {
+        let mut cache = CACHE_MAGIC.to_vec();
+        assert!(cache_len(&cache).is_err());
+        cache.extend(2u64.to_le_bytes());
+        cache.extend(3u64.to_le_bytes());
+        assert!(cache_len(&cache).is_err());
+        cache.extend(9u64.to_le_bytes());
+        cache.extend(b"onetwo");
+        assert!(cache_len(&cache).is_err());
+        cache.truncate(cache.len() - 6 - 8);
+        cache.extend(2u64.to_le_bytes());
+        cache.extend(b"onetwo");
+        assert!(cache_len(&cache).is_err());
+        cache.truncate(cache.len() - 6 - 8);
+        cache.extend(6u64.to_le_bytes());
+        cache.extend(b"onetwo");
+        let words: Vec<&str> = (0..cache_len(&cache).unwrap())
+            .map(|i| cache_word(&cache, 2, i))
+            .collect();
+        assert_eq!(words, ["one", "two"]);
+    }
+}
@@ -0,0 +1,51 @@
+//! Options that only apply along with others are refused without them,
+//! rather than silently ignored.
+
+use std::process::Command;
+
+const XKPASS: &str = env!("CARGO_BIN_EXE_xkpass");
+
+/// The usage error `xkpass` exits with when given `args`.
+fn refused(args: &[&str]) -> String {
+    let output = Command::new(XKPASS)
+        .arg("--no-config")
+        .args(args)
+        .env_remove("XKPASS_FORMAT")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2), "{:?} wasn't refused", args);
+    String::from_utf8(output.stderr).unwrap()
+}
+
+#[test]
+fn refuses_salts_of_keys_not_printed() {
+    let salt = "00".repeat(16);
+    refused(&["--salt", &salt]);
+    for other in ["--paper", "--copy", "--count=2", "--encrypt-to=age1me"] {
+        refused(&["--derive-key", "32", "--salt", &salt, other]);
+    }
+    for format in ["k8s-secret", "dotenv", "sh"] {
+        let err = refused(&["--derive-key", "32", "--salt", &salt, "--format", format]);
+        assert!(err.contains("--derive-key"), "{}", err);
+    }
+}
+
+#[test]
+fn refuses_secret_names_without_k8s_secret() {
+    for format in ["plain", "json", "dotenv"] {
+        let err = refused(&["--format", format, "--name", "db"]);
+        assert!(err.contains("k8s-secret"), "{}", err);
+        refused(&["--format", format, "--key", "password"]);
+    }
+}
+
+#[test]
+fn refuses_variable_names_without_dotenv_or_sh() {
+    for args in [
+        &["--format", "plain"][..],
+        &["--format", "k8s-secret", "--name", "db"],
+    ] {
+        let err = refused(&[args, &["--var-name", "DB"]].concat());
+        assert!(err.contains("dotenv"), "{}", err);
+    }
+}
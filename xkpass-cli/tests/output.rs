@@ -0,0 +1,64 @@
+//! Passwords with separators outside of ASCII come out intact, whether stdout
+//! is a pipe or the Windows console.
+
+use std::process::{Command, Stdio};
+
+const XKPASS: &str = env!("CARGO_BIN_EXE_xkpass");
+
+#[test]
+fn prints_unicode_separators_to_a_pipe() {
+    let output = Command::new(XKPASS)
+        .args(["--no-config", "-n", "3", "-s", "🔑"])
+        .stdout(Stdio::piped())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let password = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(password.trim_end().split('🔑').count(), 3);
+}
+
+#[cfg(windows)]
+#[test]
+fn prints_unicode_separators_to_the_console() {
+    use std::{fs::OpenOptions, os::windows::io::AsRawHandle};
+    use windows_sys::Win32::System::Console::{
+        AllocConsole, FreeConsole, ReadConsoleOutputCharacterW, COORD,
+    };
+
+    // a console of the test's own, with nothing on it yet
+    // SAFETY: neither call takes arguments, and no handle of the old console is kept
+    unsafe {
+        FreeConsole();
+        assert_ne!(AllocConsole(), 0);
+    }
+    let console = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open("CONOUT$")
+        .unwrap();
+
+    let status = Command::new(XKPASS)
+        .args(["--no-config", "-n", "3", "-s", "→"])
+        .stdout(Stdio::from(console.try_clone().unwrap()))
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let mut text = [0u16; 256];
+    let mut read = 0;
+    // SAFETY: `text` holds as many characters as asked for, the handle is open
+    let ok = unsafe {
+        ReadConsoleOutputCharacterW(
+            console.as_raw_handle(),
+            text.as_mut_ptr(),
+            text.len() as u32,
+            COORD { X: 0, Y: 0 },
+            &mut read,
+        )
+    };
+    assert_ne!(ok, 0);
+
+    let screen = String::from_utf16(&text[..read as usize]).unwrap();
+    assert_eq!(screen.trim_end().split('→').count(), 3);
+}
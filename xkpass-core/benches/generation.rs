@@ -0,0 +1,109 @@
+//! Benchmarks for every step of generating a password, run with `cargo bench`.
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use rand::{rngs::StdRng, SeedableRng};
+use std::hint::black_box;
+use xkpass::{Case, Config, List, PasswordGenerator, VariantNames, WordTransform};
+
+fn lists() -> impl Iterator<Item = List> {
+    List::VARIANTS.iter().map(|name| name.parse().unwrap())
+}
+
+fn parsing(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+    for list in lists() {
+        group.bench_function(list.name(), |b| {
+            b.iter(|| xkpass::parse_word_list(black_box(&list)))
+        });
+    }
+    group.finish();
+}
+
+fn lookup(c: &mut Criterion) {
+    let mut group = c.benchmark_group("lookup");
+    for list in lists() {
+        group.bench_function(list.name(), |b| b.iter(|| list.contains(black_box("zoom"))));
+    }
+    group.finish();
+}
+
+fn sampling(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sample");
+    let mut rng = StdRng::seed_from_u64(0);
+    for list in lists() {
+        let words = list.as_slice();
+        let config = Config {
+            list: list.clone(),
+            ..Config::default()
+        };
+        group.bench_function(list.name(), |b| {
+            b.iter(|| xkpass::generate_password_with(words, &config, &mut rng))
+        });
+    }
+
+    let stream: Vec<&str> = List::Long
+        .as_slice()
+        .iter()
+        .copied()
+        .cycle()
+        .take(1 << 20)
+        .collect();
+    group.bench_function("stream", |b| {
+        b.iter(|| xkpass::generate_password_from_stream_with(&stream, &Config::default(), &mut rng))
+    });
+    group.finish();
+}
+
+fn casing(c: &mut Criterion) {
+    let mut group = c.benchmark_group("case");
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut out = String::new();
+    for case in Case::VARIANTS
+        .iter()
+        .map(|name| name.parse::<Case>().unwrap())
+    {
+        group.bench_function(case.name(), |b| {
+            b.iter(|| {
+                out.clear();
+                case.apply_into(black_box("trombone"), &mut out, &mut rng);
+            })
+        });
+    }
+    group.bench_function("unicode", |b| {
+        b.iter_batched(
+            || String::from("straße"),
+            |word| Case::Upper.apply(word, &mut rng),
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+fn generation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("generate");
+    for list in lists() {
+        let config = Config {
+            list: list.clone(),
+            ..Config::default()
+        };
+        group.bench_function(list.name(), |b| {
+            b.iter(|| xkpass::generate_password(config.clone()))
+        });
+
+        let generator = PasswordGenerator::builder()
+            .list(list.clone())
+            .build()
+            .unwrap();
+        let mut out = String::new();
+        group.bench_function(format!("{}/into", list.name()), |b| {
+            b.iter(|| {
+                out.clear();
+                generator.generate_into(&mut out);
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, parsing, lookup, sampling, casing, generation);
+criterion_main!(benches);
@@ -0,0 +1,45 @@
+//! Splits the embedded word lists into arrays of words at build time, so that
+//! they don't have to be parsed every time a password is generated, along with
+//! an index of their words in sorted order to look words up.
+
+use std::{env, fmt::Write, fs, path::Path};
+
+/// Name of the generated array and file of each embedded word list.
+const LISTS: [(&str, &str); 3] = [
+    ("WORDS_LONG", "eff_large_wordlist.txt"),
+    ("WORDS_SHORT1", "eff_short_wordlist_1.txt"),
+    ("WORDS_SHORT2", "eff_short_wordlist_2_0.txt"),
+];
+
+fn main() {
+    let mut code = String::new();
+    for (name, file) in LISTS {
+        let path = Path::new("src/words").join(file);
+        println!("cargo:rerun-if-changed={}", path.display());
+
+        let contents = fs::read_to_string(&path).expect("can't read the word list");
+        let words: Vec<&str> = contents.split_whitespace().collect();
+        writeln!(
+            code,
+            "static {}: [&str; {}] = {:?};",
+            name,
+            words.len(),
+            words
+        )
+        .unwrap();
+
+        let mut index: Vec<u16> = (0..words.len() as u16).collect();
+        index.sort_unstable_by_key(|&i| words[i as usize]);
+        writeln!(
+            code,
+            "static {}_INDEX: [u16; {}] = {:?};",
+            name,
+            index.len(),
+            index
+        )
+        .unwrap();
+    }
+
+    let out = Path::new(&env::var_os("OUT_DIR").unwrap()).join("word_lists.rs");
+    fs::write(out, code).expect("can't write the parsed word lists");
+}
@@ -0,0 +1,20 @@
+//! Deserialize arbitrary bytes as JSON settings, as sent to the WebAssembly and
+//! server APIs, and generate a password with them.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use xkpass::Config;
+
+fuzz_target!(|data: &[u8]| {
+    let config: Config = match serde_json::from_slice(data) {
+        Ok(config) => config,
+        Err(_) => return,
+    };
+
+    // huge numbers are refused before any word is picked
+    let number = config.number;
+    if let Ok(password) = xkpass::generate_password(config) {
+        assert_eq!(password.words().len(), number);
+    }
+});
@@ -0,0 +1,29 @@
+//! Parse arbitrary bytes as a word list file and generate a password out of it.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use xkpass::{Case, Config};
+
+fuzz_target!(|data: &[u8]| {
+    let contents = match std::str::from_utf8(data) {
+        Ok(contents) => contents,
+        Err(_) => return,
+    };
+
+    let words = xkpass::parse_words(contents);
+    for word in &words {
+        assert!(!word.is_empty() && !word.contains(char::is_whitespace));
+    }
+
+    for case in [Case::Upper, Case::Lower, Case::Capitalized, Case::Mixed] {
+        let config = Config {
+            number: words.len().clamp(1, 8),
+            case,
+            ..Config::default()
+        };
+        if let Ok(password) = xkpass::generate_password_from(&words, config) {
+            assert_eq!(password.words().len(), words.len().clamp(1, 8));
+        }
+    }
+});
@@ -0,0 +1,105 @@
+#ifndef XKPASS_H
+#define XKPASS_H
+
+/* Generated by cbindgen from src/ffi.rs, do not edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// The password was generated.
+#define XKPASS_OK 0
+
+// A required pointer argument was null.
+#define XKPASS_ERROR_NULL_ARGUMENT 1
+
+// The separator is not valid UTF-8.
+#define XKPASS_ERROR_INVALID_UTF8 2
+
+// The list is not one of the `XKPASS_LIST_*` values.
+#define XKPASS_ERROR_UNKNOWN_LIST 3
+
+// The case is not one of the `XKPASS_CASE_*` values.
+#define XKPASS_ERROR_UNKNOWN_CASE 4
+
+// The number of words is zero.
+#define XKPASS_ERROR_NO_WORDS 5
+
+// The number of words is larger than the list.
+#define XKPASS_ERROR_NOT_ENOUGH_WORDS 6
+
+// The separator contains control characters.
+#define XKPASS_ERROR_INVALID_SEPARATOR 7
+
+// Anything else, including a bug in xkpass.
+#define XKPASS_ERROR_INTERNAL 255
+
+// EFF's long word list.
+#define XKPASS_LIST_LONG 0
+
+// EFF's first short word list.
+#define XKPASS_LIST_SHORT1 1
+
+// EFF's second short word list.
+#define XKPASS_LIST_SHORT2 2
+
+// Convert every word to uppercase.
+#define XKPASS_CASE_UPPER 0
+
+// Convert every word to lowercase.
+#define XKPASS_CASE_LOWER 1
+
+// Capitalize every word.
+#define XKPASS_CASE_CAPITALIZED 2
+
+// Randomly convert each word to uppercase or lowercase.
+#define XKPASS_CASE_MIXED 3
+
+// Settings for generating a password.
+typedef struct XkpassConfig {
+  // Number of words to include in the password.
+  size_t number;
+  // NUL-terminated UTF-8 separator to use between words, a null pointer means a space.
+  const char *separator;
+  // One of the `XKPASS_LIST_*` values.
+  uint32_t list;
+  // One of the `XKPASS_CASE_*` values.
+  uint32_t case_;
+} XkpassConfig;
+
+
+
+// Fill `config` with the same defaults as the command line.
+//
+// # Safety
+//
+// `config` must be null or point to writable memory for an `XkpassConfig`.
+void xkpass_config_default(struct XkpassConfig *config);
+
+// Generate a password.
+//
+// Returns a NUL-terminated string to be released with `xkpass_free`, or null
+// on failure. When `error` is not null, it receives `XKPASS_OK` or one of the
+// `XKPASS_ERROR_*` codes.
+//
+// # Safety
+//
+// `config` must be null or point to a valid `XkpassConfig` whose separator is
+// null or a NUL-terminated string. `error` must be null or point to writable
+// memory for an `int32_t`.
+char *xkpass_generate(const struct XkpassConfig *config, int32_t *error);
+
+// Zero and release a password returned by `xkpass_generate`. Null is ignored.
+//
+// # Safety
+//
+// `password` must be null or a pointer returned by `xkpass_generate` that
+// hasn't been freed yet.
+void xkpass_free(char *password);
+
+// Describe an error code, as a static NUL-terminated string.
+const char *xkpass_error_message(int32_t code);
+
+#endif  /* XKPASS_H */
@@ -0,0 +1,107 @@
+//! Writing arbitrary bytes as words of the long list, and reading them back.
+//!
+//! Most words stand for 12 bits, taken from the first 4096 words of the list.
+//! When the bytes don't fill a whole number of such words, the last word
+//! stands for the 4 or 8 bits left, taken from the 16 and the 256 words that
+//! follow, so that the number of bytes never has to be written down.
+
+use alloc::{string::String, vec::Vec};
+use thiserror::Error;
+
+use crate::List;
+
+const WORD_BITS: u32 = 12;
+const WORDS_4_BITS: usize = 1 << WORD_BITS;
+const WORDS_8_BITS: usize = WORDS_4_BITS + 16;
+const WORDS_END: usize = WORDS_8_BITS + 256;
+
+/// Reasons words can't be read back as bytes.
+#[derive(Debug, Error, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DecodeError {
+    /// The word isn't one of those bytes are written with.
+    #[error("{0:?} isn't a word of the encoding")]
+    UnknownWord(String),
+
+    /// The words don't end on a whole byte, some are missing.
+    #[error("words are missing at the end")]
+    Truncated,
+}
+
+/// Write `bytes` as words of the long list, about two words for every three bytes.
+///
+/// ```
+/// let words = xkpass::encode_bytes(&[0xde, 0xad, 0xbe, 0xef]);
+/// assert_eq!(xkpass::decode_words(words), Ok(vec![0xde, 0xad, 0xbe, 0xef]));
+/// ```
+pub fn encode_bytes(bytes: &[u8]) -> Vec<&'static str> {
+    let list = List::Long.as_slice();
+    let mut words = Vec::with_capacity((bytes.len() * 8).div_ceil(WORD_BITS as usize));
+    let (mut bits, mut count) = (0u32, 0);
+    for &byte in bytes {
+        bits = bits << 8 | u32::from(byte);
+        count += 8;
+        if count >= WORD_BITS {
+            count -= WORD_BITS;
+            words.push(list[(bits >> count) as usize]);
+            bits &= (1 << count) - 1;
+        }
+    }
+    match count {
+        4 => words.push(list[WORDS_4_BITS + bits as usize]),
+        8 => words.push(list[WORDS_8_BITS + bits as usize]),
+        _ => {}
+    }
+    words
+}
+
+/// Read back the bytes written as `words` by [`encode_bytes`], in any case.
+pub fn decode_words<'a>(words: impl IntoIterator<Item = &'a str>) -> Result<Vec<u8>, DecodeError> {
+    let mut bytes = Vec::new();
+    let (mut bits, mut count) = (0u32, 0);
+    for word in words {
+        let (value, len) = match List::Long.position(&word.to_lowercase()) {
+            Some(i) if i < WORDS_4_BITS => (i, WORD_BITS),
+            Some(i) if i < WORDS_8_BITS => (i - WORDS_4_BITS, 4),
+            Some(i) if i < WORDS_END => (i - WORDS_8_BITS, 8),
+            _ => return Err(DecodeError::UnknownWord(String::from(word))),
+        };
+        bits = bits << len | value as u32;
+        count += len;
+        while count >= 8 {
+            count -= 8;
+            bytes.push((bits >> count) as u8);
+            bits &= (1 << count) - 1;
+        }
+    }
+    match count {
+        0 => Ok(bytes),
+        _ => Err(DecodeError::Truncated),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_back_bytes_of_any_length() {
+        for len in 0..8u8 {
+            let bytes: Vec<u8> = (0..len)
+                .map(|i| i.wrapping_mul(37).wrapping_add(200))
+                .collect();
+            let words = encode_bytes(&bytes);
+            assert_eq!(words.len(), (usize::from(len) * 8).div_ceil(12));
+            assert_eq!(decode_words(words), Ok(bytes));
+        }
+    }
+
+    #[test]
+    fn rejects_other_words() {
+        assert_eq!(
+            decode_words(["Abacus", "zoom"]),
+            Err(DecodeError::UnknownWord(String::from("zoom")))
+        );
+        assert_eq!(decode_words(["abacus"]), Err(DecodeError::Truncated));
+    }
+}
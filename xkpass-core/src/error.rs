@@ -0,0 +1,40 @@
+use alloc::string::String;
+use thiserror::Error;
+
+/// Reasons a password can't be generated.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum Error {
+    /// The word list has no words in it.
+    #[error("the word list is empty")]
+    EmptyWordList,
+
+    /// Passwords need at least one word.
+    #[error("passwords need at least one word")]
+    NoWords,
+
+    /// More words were requested than the list contains.
+    #[error("{requested} words were requested but the list only has {available}")]
+    NotEnoughWords { requested: usize, available: usize },
+
+    /// The separator contains control characters, such as a newline.
+    #[error("the separator {0:?} contains control characters")]
+    InvalidSeparator(String),
+
+    /// The name doesn't match any word list.
+    #[error("unknown list {0:?}, expected one of long, short1, short2")]
+    UnknownList(String),
+
+    /// The name doesn't match any case.
+    #[error("unknown case {0:?}, expected one of upper, lower, capitalized, mixed")]
+    UnknownCase(String),
+
+    /// The name doesn't match any order.
+    #[error("unknown order {0:?}, expected one of shuffle, alphabetical, length, sampled")]
+    UnknownOrder(String),
+
+    /// Reading a word list failed.
+    #[cfg(feature = "std")]
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
@@ -0,0 +1,248 @@
+//! C bindings, so C, C++ or Swift applications can link the generator directly.
+//!
+//! The matching header is `include/xkpass.h`, generated with
+//! [cbindgen](https://github.com/mozilla/cbindgen): `cbindgen --output include/xkpass.h`.
+
+use crate::{generate_password, Case, Config, Error, List};
+use std::{
+    ffi::{c_char, CStr, CString},
+    panic, ptr,
+};
+use zeroize::Zeroize;
+
+/// The password was generated.
+pub const XKPASS_OK: i32 = 0;
+/// A required pointer argument was null.
+pub const XKPASS_ERROR_NULL_ARGUMENT: i32 = 1;
+/// The separator is not valid UTF-8.
+pub const XKPASS_ERROR_INVALID_UTF8: i32 = 2;
+/// The list is not one of the `XKPASS_LIST_*` values.
+pub const XKPASS_ERROR_UNKNOWN_LIST: i32 = 3;
+/// The case is not one of the `XKPASS_CASE_*` values.
+pub const XKPASS_ERROR_UNKNOWN_CASE: i32 = 4;
+/// The number of words is zero.
+pub const XKPASS_ERROR_NO_WORDS: i32 = 5;
+/// The number of words is larger than the list.
+pub const XKPASS_ERROR_NOT_ENOUGH_WORDS: i32 = 6;
+/// The separator contains control characters.
+pub const XKPASS_ERROR_INVALID_SEPARATOR: i32 = 7;
+/// Anything else, including a bug in xkpass.
+pub const XKPASS_ERROR_INTERNAL: i32 = 255;
+
+/// EFF's long word list.
+pub const XKPASS_LIST_LONG: u32 = 0;
+/// EFF's first short word list.
+pub const XKPASS_LIST_SHORT1: u32 = 1;
+/// EFF's second short word list.
+pub const XKPASS_LIST_SHORT2: u32 = 2;
+
+/// Convert every word to uppercase.
+pub const XKPASS_CASE_UPPER: u32 = 0;
+/// Convert every word to lowercase.
+pub const XKPASS_CASE_LOWER: u32 = 1;
+/// Capitalize every word.
+pub const XKPASS_CASE_CAPITALIZED: u32 = 2;
+/// Randomly convert each word to uppercase or lowercase.
+pub const XKPASS_CASE_MIXED: u32 = 3;
+
+/// Settings for generating a password.
+#[repr(C)]
+pub struct XkpassConfig {
+    /// Number of words to include in the password.
+    pub number: usize,
+    /// NUL-terminated UTF-8 separator to use between words, a null pointer means a space.
+    pub separator: *const c_char,
+    /// One of the `XKPASS_LIST_*` values.
+    pub list: u32,
+    /// One of the `XKPASS_CASE_*` values.
+    pub case_: u32,
+}
+
+/// Fill `config` with the same defaults as the command line.
+///
+/// # Safety
+///
+/// `config` must be null or point to writable memory for an `XkpassConfig`.
+#[no_mangle]
+pub unsafe extern "C" fn xkpass_config_default(config: *mut XkpassConfig) {
+    if let Some(config) = config.as_mut() {
+        *config = XkpassConfig {
+            number: 6,
+            separator: ptr::null(),
+            list: XKPASS_LIST_LONG,
+            case_: XKPASS_CASE_LOWER,
+        };
+    }
+}
+
+/// Generate a password.
+///
+/// Returns a NUL-terminated string to be released with `xkpass_free`, or null
+/// on failure. When `error` is not null, it receives `XKPASS_OK` or one of the
+/// `XKPASS_ERROR_*` codes.
+///
+/// # Safety
+///
+/// `config` must be null or point to a valid `XkpassConfig` whose separator is
+/// null or a NUL-terminated string. `error` must be null or point to writable
+/// memory for an `int32_t`.
+#[no_mangle]
+pub unsafe extern "C" fn xkpass_generate(
+    config: *const XkpassConfig,
+    error: *mut i32,
+) -> *mut c_char {
+    let result = panic::catch_unwind(|| generate(config)).unwrap_or(Err(XKPASS_ERROR_INTERNAL));
+
+    let (password, code) = match result {
+        Ok(password) => (password.into_raw(), XKPASS_OK),
+        Err(code) => (ptr::null_mut(), code),
+    };
+    if let Some(error) = error.as_mut() {
+        *error = code;
+    }
+
+    password
+}
+
+/// Zero and release a password returned by `xkpass_generate`. Null is ignored.
+///
+/// # Safety
+///
+/// `password` must be null or a pointer returned by `xkpass_generate` that
+/// hasn't been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn xkpass_free(password: *mut c_char) {
+    if !password.is_null() {
+        let mut password = CString::from_raw(password).into_bytes();
+        password.zeroize();
+    }
+}
+
+/// Describe an error code, as a static NUL-terminated string.
+#[no_mangle]
+pub extern "C" fn xkpass_error_message(code: i32) -> *const c_char {
+    let message: &'static CStr = match code {
+        XKPASS_OK => c"no error",
+        XKPASS_ERROR_NULL_ARGUMENT => c"a required argument is null",
+        XKPASS_ERROR_INVALID_UTF8 => c"the separator is not valid UTF-8",
+        XKPASS_ERROR_UNKNOWN_LIST => c"unknown word list",
+        XKPASS_ERROR_UNKNOWN_CASE => c"unknown case",
+        XKPASS_ERROR_NO_WORDS => c"passwords need at least one word",
+        XKPASS_ERROR_NOT_ENOUGH_WORDS => c"more words were requested than the list has",
+        XKPASS_ERROR_INVALID_SEPARATOR => c"the separator contains control characters",
+        _ => c"internal error",
+    };
+
+    message.as_ptr()
+}
+
+unsafe fn generate(config: *const XkpassConfig) -> Result<CString, i32> {
+    let config = config.as_ref().ok_or(XKPASS_ERROR_NULL_ARGUMENT)?;
+
+    let separator = if config.separator.is_null() {
+        String::from(" ")
+    } else {
+        CStr::from_ptr(config.separator)
+            .to_str()
+            .map_err(|_| XKPASS_ERROR_INVALID_UTF8)?
+            .to_string()
+    };
+    let list = match config.list {
+        XKPASS_LIST_LONG => List::Long,
+        XKPASS_LIST_SHORT1 => List::Short1,
+        XKPASS_LIST_SHORT2 => List::Short2,
+        _ => return Err(XKPASS_ERROR_UNKNOWN_LIST),
+    };
+    let case = match config.case_ {
+        XKPASS_CASE_UPPER => Case::Upper,
+        XKPASS_CASE_LOWER => Case::Lower,
+        XKPASS_CASE_CAPITALIZED => Case::Capitalized,
+        XKPASS_CASE_MIXED => Case::Mixed,
+        _ => return Err(XKPASS_ERROR_UNKNOWN_CASE),
+    };
+
+    let config = Config {
+        number: config.number,
+        separator,
+        list,
+        case,
+    };
+    let password = generate_password(config).map_err(|err| match err {
+        Error::NoWords => XKPASS_ERROR_NO_WORDS,
+        Error::NotEnoughWords { .. } => XKPASS_ERROR_NOT_ENOUGH_WORDS,
+        Error::InvalidSeparator(_) => XKPASS_ERROR_INVALID_SEPARATOR,
+        _ => XKPASS_ERROR_INTERNAL,
+    })?;
+
+    // separators are free of control characters, so there is no NUL inside
+    CString::new(password.into_string()).map_err(|_| XKPASS_ERROR_INTERNAL)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::mem::MaybeUninit;
+
+    fn default_config() -> XkpassConfig {
+        let mut config = MaybeUninit::uninit();
+        unsafe {
+            xkpass_config_default(config.as_mut_ptr());
+            config.assume_init()
+        }
+    }
+
+    #[test]
+    fn generates_and_frees() {
+        let separator = CString::new("+").unwrap();
+        let config = XkpassConfig {
+            number: 3,
+            separator: separator.as_ptr(),
+            ..default_config()
+        };
+        let mut error = -1;
+
+        unsafe {
+            let password = xkpass_generate(&config, &mut error);
+            assert_eq!(error, XKPASS_OK);
+            assert_eq!(
+                CStr::from_ptr(password)
+                    .to_str()
+                    .unwrap()
+                    .split('+')
+                    .count(),
+                3
+            );
+            xkpass_free(password);
+        }
+    }
+
+    #[test]
+    fn reports_error_codes() {
+        let mut error = -1;
+        let generate =
+            |config: &XkpassConfig, error: &mut i32| unsafe { xkpass_generate(config, error) };
+
+        let no_words = XkpassConfig {
+            number: 0,
+            ..default_config()
+        };
+        assert!(generate(&no_words, &mut error).is_null());
+        assert_eq!(error, XKPASS_ERROR_NO_WORDS);
+
+        let unknown_list = XkpassConfig {
+            list: 9,
+            ..default_config()
+        };
+        assert!(generate(&unknown_list, &mut error).is_null());
+        assert_eq!(error, XKPASS_ERROR_UNKNOWN_LIST);
+
+        assert!(unsafe { xkpass_generate(ptr::null(), &mut error) }.is_null());
+        assert_eq!(error, XKPASS_ERROR_NULL_ARGUMENT);
+
+        let message = unsafe { CStr::from_ptr(xkpass_error_message(XKPASS_ERROR_NO_WORDS)) };
+        assert_eq!(
+            message.to_str().unwrap(),
+            "passwords need at least one word"
+        );
+    }
+}
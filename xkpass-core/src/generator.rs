@@ -0,0 +1,390 @@
+//! Builder-style API for generating passwords from library code.
+
+use crate::{
+    build_password, estimate_entropy, transform::MapWords, validate, write_password, Case, Config,
+    Error, List, Password, WordTransform,
+};
+use alloc::{string::String, sync::Arc, vec, vec::Vec};
+#[cfg(feature = "std")]
+use rand::rngs::ThreadRng;
+use rand::{Rng, RngCore};
+
+/// A reusable password generator.
+///
+/// The settings are checked once when the generator is built, so generating many
+/// passwords with the same settings is cheap.
+///
+/// ```
+/// use xkpass::{Case, List, PasswordGenerator};
+///
+/// let generator = PasswordGenerator::builder()
+///     .words(4)
+///     .list(List::Short1)
+///     .case(Case::Capitalized)
+///     .separator(".")
+///     .build()
+///     .unwrap();
+///
+/// # #[cfg(feature = "std")] {
+/// let password = generator.generate();
+/// assert_eq!(password.words().len(), 4);
+/// assert_eq!(password.expose().split('.').count(), 4);
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct PasswordGenerator {
+    words: &'static [&'static str],
+    number: usize,
+    separator: String,
+    case: Case,
+    transforms: Vec<Arc<dyn WordTransform>>,
+}
+
+impl PasswordGenerator {
+    /// Start configuring a generator with the same defaults as the command line.
+    pub fn builder() -> PasswordGeneratorBuilder {
+        PasswordGeneratorBuilder::default()
+    }
+
+    /// Generate a random password in xkcd style.
+    ///
+    /// Uses a cryptographically secure PRNG provided by the
+    /// [`rand`](https://docs.rs/rand/latest/rand/) crate.
+    #[cfg(feature = "std")]
+    pub fn generate(&self) -> Password {
+        self.generate_with(&mut rand::thread_rng())
+    }
+
+    /// Generate `n` independent passwords.
+    #[cfg(feature = "std")]
+    pub fn generate_n(&self, n: usize) -> Vec<Password> {
+        let mut rng = rand::thread_rng();
+        (0..n).map(|_| self.generate_with(&mut rng)).collect()
+    }
+
+    /// Turn the generator into an endless iterator over passwords.
+    #[cfg(feature = "std")]
+    pub fn into_passwords(self) -> Passwords {
+        Passwords {
+            generator: self,
+            rng: rand::thread_rng(),
+        }
+    }
+
+    /// Generate a password using `rng` for every random choice, which must be
+    /// a cryptographically secure generator.
+    pub fn generate_with<T: Rng>(&self, rng: &mut T) -> Password {
+        build_password(
+            self.words,
+            self.number,
+            &self.pipeline(),
+            &self.separator,
+            rng,
+        )
+    }
+
+    /// Append a password to `out`, for callers generating so many that a
+    /// [`Password`] per password, and a string per word, would add up.
+    ///
+    /// `out` isn't cleared first, and isn't zeroed on drop like a `Password`.
+    ///
+    /// ```
+    /// # #[cfg(feature = "std")] {
+    /// let generator = xkpass::PasswordGenerator::builder().words(4).build().unwrap();
+    /// let mut out = String::new();
+    /// for _ in 0..1000 {
+    ///     out.clear();
+    ///     generator.generate_into(&mut out);
+    /// }
+    /// # }
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn generate_into(&self, out: &mut String) {
+        self.generate_into_with(out, &mut rand::thread_rng());
+    }
+
+    /// Append a password to `out` using `rng` for every random choice, see
+    /// [`generate_into`](Self::generate_into).
+    pub fn generate_into_with<T: Rng>(&self, out: &mut String, rng: &mut T) {
+        if self.transforms.is_empty() {
+            let pipeline: [&dyn WordTransform; 1] = [&self.case];
+            write_password(
+                self.words,
+                self.number,
+                &pipeline,
+                &self.separator,
+                out,
+                rng,
+            );
+        } else {
+            let pipeline = self.pipeline();
+            write_password(
+                self.words,
+                self.number,
+                &pipeline,
+                &self.separator,
+                out,
+                rng,
+            );
+        }
+    }
+
+    /// Estimated entropy in bits of every password the generator makes.
+    pub fn entropy(&self) -> f64 {
+        estimate_entropy(self.words.len(), self.number, &self.pipeline())
+    }
+
+    /// The case followed by the other transforms, in order.
+    fn pipeline(&self) -> Vec<&dyn WordTransform> {
+        let mut pipeline: Vec<&dyn WordTransform> = vec![&self.case];
+        pipeline.extend(self.transforms.iter().map(|transform| &**transform));
+        pipeline
+    }
+}
+
+/// An endless iterator over passwords generated with the same settings.
+///
+/// Created by [`PasswordGenerator::into_passwords`] or [`iter_passwords`](crate::iter_passwords).
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct Passwords {
+    generator: PasswordGenerator,
+    rng: ThreadRng,
+}
+
+#[cfg(feature = "std")]
+impl Iterator for Passwords {
+    type Item = Password;
+
+    fn next(&mut self) -> Option<Password> {
+        Some(self.generator.generate_with(&mut self.rng))
+    }
+}
+
+/// Builder for [`PasswordGenerator`], created by [`PasswordGenerator::builder`].
+#[derive(Clone, Debug, Default)]
+pub struct PasswordGeneratorBuilder {
+    config: Config,
+    transforms: Vec<Arc<dyn WordTransform>>,
+}
+
+impl PasswordGeneratorBuilder {
+    /// Replace every setting with the ones in `config`.
+    pub fn config(mut self, config: Config) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Number of words to include in the password.
+    pub fn words(mut self, number: usize) -> Self {
+        self.config.number = number;
+        self
+    }
+
+    /// Separator to use between words.
+    pub fn separator(mut self, separator: impl Into<String>) -> Self {
+        self.config.separator = separator.into();
+        self
+    }
+
+    /// List of words to pick from.
+    pub fn list(mut self, list: List) -> Self {
+        self.config.list = list;
+        self
+    }
+
+    /// Case to use on the words.
+    pub fn case(mut self, case: Case) -> Self {
+        self.config.case = case;
+        self
+    }
+
+    /// Add a step to the pipeline every word goes through, after the case has been applied.
+    ///
+    /// Transforms run in the order they were added.
+    pub fn transform(mut self, transform: impl WordTransform + 'static) -> Self {
+        self.transforms.push(Arc::new(transform));
+        self
+    }
+
+    /// Add a closure to the pipeline every word goes through, for modifications
+    /// that don't deserve their own [`WordTransform`].
+    ///
+    /// The closure must be deterministic, use [`map_words_with_entropy`](Self::map_words_with_entropy)
+    /// if it makes random choices.
+    pub fn map_words<F>(self, f: F) -> Self
+    where
+        F: Fn(String, &mut dyn RngCore) -> String + Send + Sync + 'static,
+    {
+        self.map_words_with_entropy(0.0, f)
+    }
+
+    /// Add a closure making random choices with the given `rng` to the pipeline,
+    /// declaring the bits of entropy it adds to every word.
+    pub fn map_words_with_entropy<F>(self, entropy: f64, f: F) -> Self
+    where
+        F: Fn(String, &mut dyn RngCore) -> String + Send + Sync + 'static,
+    {
+        self.transform(MapWords::new(f, entropy))
+    }
+
+    /// Check the settings.
+    pub fn build(self) -> Result<PasswordGenerator, Error> {
+        let config = self.config;
+        let words = config.list.as_slice();
+        validate(words, &config)?;
+
+        Ok(PasswordGenerator {
+            words,
+            number: config.number,
+            separator: config.separator,
+            case: config.case,
+            transforms: self.transforms,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Leet;
+    use alloc::format;
+
+    #[test]
+    fn builds_with_settings() {
+        let generator = PasswordGenerator::builder()
+            .words(3)
+            .list(List::Short2)
+            .case(Case::Upper)
+            .separator(".")
+            .build()
+            .unwrap();
+
+        let password = generator.generate_with(&mut rand::thread_rng());
+        let words = List::Short2.as_slice();
+
+        assert_eq!(password.expose(), password.expose().to_uppercase());
+        assert_eq!(password.expose().split('.').count(), 3);
+        assert_eq!(password.words().len(), 3);
+        assert_eq!(password.separators(), [".", "."]);
+        assert!(password
+            .words()
+            .iter()
+            .all(|word| words.contains(&word.to_lowercase().as_str())));
+    }
+
+    #[test]
+    fn runs_transforms_after_case() {
+        let generator = PasswordGenerator::builder()
+            .words(4)
+            .case(Case::Mixed)
+            .transform(Leet)
+            .build()
+            .unwrap();
+
+        let password = generator.generate_with(&mut rand::thread_rng());
+        let plain = PasswordGenerator::builder()
+            .words(4)
+            .case(Case::Mixed)
+            .build()
+            .unwrap()
+            .generate_with(&mut rand::thread_rng());
+
+        assert!(!password.expose().contains(|c| "aeiostAEIOST".contains(c)));
+        assert_eq!(password.entropy(), plain.entropy());
+    }
+
+    #[test]
+    fn maps_words_with_closures() {
+        let generator = PasswordGenerator::builder()
+            .words(3)
+            .map_words(|word, _| format!("<{}>", word))
+            .map_words_with_entropy(1.0, |word, rng| {
+                if rng.gen_bool(0.5) {
+                    word + "!"
+                } else {
+                    word + "?"
+                }
+            })
+            .build()
+            .unwrap();
+        let plain = PasswordGenerator::builder().words(3).build().unwrap();
+
+        let mut rng = rand::thread_rng();
+        let password = generator.generate_with(&mut rng);
+
+        assert!(password
+            .words()
+            .iter()
+            .all(|word| word.starts_with('<') && (word.ends_with(">!") || word.ends_with(">?"))));
+        assert_eq!(
+            password.entropy(),
+            plain.generate_with(&mut rng).entropy() + 3.0
+        );
+    }
+
+    #[test]
+    fn generates_into_buffer() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let generator = PasswordGenerator::builder()
+            .words(5)
+            .case(Case::Mixed)
+            .separator("+")
+            .build()
+            .unwrap();
+        let password = generator.generate_with(&mut StdRng::from_seed([3; 32]));
+        let mut out = String::from("> ");
+        generator.generate_into_with(&mut out, &mut StdRng::from_seed([3; 32]));
+
+        // the same random choices produce the same password
+        assert_eq!(out, format!("> {}", password.expose()));
+        assert_eq!(generator.entropy(), password.entropy());
+
+        let leet = PasswordGenerator::builder()
+            .transform(Leet)
+            .build()
+            .unwrap();
+        let mut out = String::new();
+        leet.generate_into_with(&mut out, &mut StdRng::from_seed([4; 32]));
+        assert!(!out.contains(['a', 'e', 'i', 'o', 's', 't']));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn generates_many() {
+        let generator = PasswordGenerator::builder().build().unwrap();
+        let passwords = generator.generate_n(5);
+
+        assert_eq!(passwords.len(), 5);
+        assert!(passwords.iter().all(|p| p.words().len() == 6));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn iterates_over_passwords() {
+        let generator = PasswordGenerator::builder().words(2).build().unwrap();
+        let passwords: Vec<Password> = generator.into_passwords().take(3).collect();
+
+        assert_eq!(passwords.len(), 3);
+        assert!(passwords.iter().all(|p| p.words().len() == 2));
+    }
+
+    #[test]
+    fn rejects_impossible_settings() {
+        let no_words = PasswordGenerator::builder().words(0).build();
+        let too_many = PasswordGenerator::builder()
+            .list(List::Short1)
+            .words(2000)
+            .build();
+
+        assert!(matches!(no_words, Err(Error::NoWords)));
+        assert!(matches!(
+            too_many,
+            Err(Error::NotEnoughWords {
+                requested: 2000,
+                available: 1296
+            })
+        ));
+    }
+}
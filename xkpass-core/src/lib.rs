@@ -0,0 +1,762 @@
+//! Generate passwords that are easy to remember, in the style of [xkcd](https://xkcd.com/936/).
+//!
+//! Without the default `std` feature the crate is `no_std` and only needs `alloc`:
+//! the word lists can be provided by the caller and passwords are generated with
+//! [`generate_password_with`] from a random number generator the caller supplies.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use alloc::{string::String, vec::Vec};
+use rand::{seq::SliceRandom, Rng};
+use trace::span;
+use unicode_segmentation::UnicodeSegmentation;
+use zeroize::Zeroizing;
+
+mod codec;
+mod error;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod generator;
+mod names;
+mod password;
+#[cfg(feature = "python")]
+mod python;
+#[cfg(feature = "secure-mem")]
+mod secure_mem;
+mod stream;
+mod trace;
+mod transform;
+#[cfg(feature = "wasm")]
+mod wasm;
+mod word_list;
+
+pub use codec::{decode_words, encode_bytes, DecodeError};
+pub use error::Error;
+#[cfg(feature = "std")]
+pub use generator::Passwords;
+pub use generator::{PasswordGenerator, PasswordGeneratorBuilder};
+pub use names::VariantNames;
+pub use password::{Insertion, Password};
+#[cfg(feature = "std")]
+pub use stream::generate_password_from_stream;
+pub use stream::generate_password_from_stream_with;
+pub use transform::{Leet, WordTransform};
+#[cfg(feature = "std")]
+pub use word_list::parse_words;
+pub use word_list::{parse_line, parse_word_list};
+
+/// Settings for generating a password.
+///
+/// With the `serde` feature, missing fields are filled in from [`Config::default`]
+/// when deserializing.
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct Config {
+    /// Number of words to include in the password
+    pub number: usize,
+
+    /// A separator to use between words
+    pub separator: String,
+
+    /// List of words to use for random password generation
+    pub list: List,
+
+    /// Case to use on the words
+    pub case: Case,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            number: 6,
+            separator: String::from(" "),
+            list: List::Long,
+            case: Case::Lower,
+        }
+    }
+}
+
+/// List of words to use for password generation.
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum List {
+    /// [EFF's long word list](https://www.eff.org/files/2016/07/18/eff_large_wordlist.txt)
+    Long,
+    /// [EFF's first short word list](https://www.eff.org/files/2016/09/08/eff_short_wordlist_1.txt)
+    Short1,
+    /// [EFF's second short word list](https://www.eff.org/files/2016/09/08/eff_short_wordlist_2_0.txt)
+    Short2,
+}
+
+/// Case to use on the words.
+///
+/// Words are converted with the default Unicode case mappings, which never
+/// depend on the locale: `i` becomes `I` even for Turkish users, and a list
+/// gives the same passwords on every machine.
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum Case {
+    Upper,
+    Lower,
+    Capitalized,
+    /// Randomly choose between converting the word to uppercase or lowercase
+    Mixed,
+}
+
+/// Order to put the words of a password in, see [`Password::reorder`].
+///
+/// Sorting the words makes a password easier to remember, but an attacker who
+/// knows the order only has one arrangement of the words to try instead of
+/// all of them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum Order {
+    /// Shuffle the words once they are picked
+    #[default]
+    Shuffle,
+    /// Sort the words alphabetically, ignoring case
+    Alphabetical,
+    /// Sort the words from the shortest to the longest, then alphabetically
+    Length,
+    /// Keep the words in the order they were picked, which for word lists is random already
+    Sampled,
+}
+
+impl Order {
+    /// Bits of entropy lost by putting `number` words in this order rather
+    /// than in random order.
+    pub fn lost_entropy(&self, number: usize) -> f64 {
+        match self {
+            Order::Shuffle | Order::Sampled => 0.0,
+            // only one of the number! arrangements of the words is left
+            Order::Alphabetical | Order::Length => (2..=number).map(|i| libm::log2(i as f64)).sum(),
+        }
+    }
+}
+
+/// Generate a random password in xkcd style.
+///
+/// Uses a cryptographically secure PRNG provided by the
+/// [`rand`](https://docs.rs/rand/latest/rand/) crate.
+#[cfg(feature = "std")]
+pub fn generate_password(config: Config) -> Result<Password, Error> {
+    generate_password_from(config.list.as_slice(), config)
+}
+
+/// Generate `n` passwords with the same settings, checking them only once.
+#[cfg(feature = "std")]
+pub fn generate_passwords(config: &Config, n: usize) -> Result<Vec<Password>, Error> {
+    Ok(iter_passwords(config)?.take(n).collect())
+}
+
+/// Return an endless iterator over passwords with the same settings.
+///
+/// The settings are checked once, up front.
+#[cfg(feature = "std")]
+pub fn iter_passwords(config: &Config) -> Result<Passwords, Error> {
+    let generator = PasswordGenerator::builder()
+        .config(config.clone())
+        .build()?;
+    Ok(generator.into_passwords())
+}
+
+/// Generate a random password in xkcd style out of already parsed words.
+///
+/// The list chosen in `config` is ignored, which lets long-running callers parse
+/// the word lists once (see [`parse_word_list`]) and reuse them for every password.
+#[cfg(feature = "std")]
+pub fn generate_password_from(words: &[&str], config: Config) -> Result<Password, Error> {
+    generate_password_with(words, &config, &mut rand::thread_rng())
+}
+
+/// Generate a random password in xkcd style out of `words`, using `rng` for every random choice.
+///
+/// The list chosen in `config` is ignored. This is the entry point available
+/// without the `std` feature, where there is no operating system to ask for
+/// randomness: `rng` must be a cryptographically secure generator, such as
+/// [`ChaCha20Rng`](https://docs.rs/rand_chacha/latest/rand_chacha/struct.ChaCha20Rng.html)
+/// seeded from a hardware random number generator.
+pub fn generate_password_with<T: Rng>(
+    words: &[&str],
+    config: &Config,
+    rng: &mut T,
+) -> Result<Password, Error> {
+    span!(
+        tracing::Level::DEBUG,
+        "generate_password",
+        number = config.number,
+        pool = words.len()
+    );
+    validate(words, config)?;
+
+    Ok(build_password(
+        words,
+        config.number,
+        &[&config.case],
+        &config.separator,
+        rng,
+    ))
+}
+
+/// Generate a random password in xkcd style out of `pool` words, looking up
+/// the word at each index with `word`, using `rng` for every random choice.
+///
+/// Only the words picked are looked up, so a list too large to gather into a
+/// slice, such as one indexed inside a memory map, can be used as it is. With
+/// the same `rng`, the words are those [`generate_password_with`] would pick.
+pub fn generate_password_indexed<'a, T, F>(
+    pool: usize,
+    word: F,
+    config: &Config,
+    rng: &mut T,
+) -> Result<Password, Error>
+where
+    T: Rng,
+    F: Fn(usize) -> &'a str,
+{
+    span!(
+        tracing::Level::DEBUG,
+        "generate_password_indexed",
+        number = config.number,
+        pool
+    );
+    validate_pool(pool, config)?;
+
+    let pipeline: [&dyn WordTransform; 1] = [&config.case];
+    let entropy = estimate_entropy(pool, config.number, &pipeline);
+    let words = rand::seq::index::sample(rng, pool, config.number)
+        .into_iter()
+        .map(|i| transform::apply_all(&pipeline, word(i), rng))
+        .collect();
+    Ok(Password::new(words, &config.separator, entropy))
+}
+
+/// Check that a password can be generated out of `words` with the settings in `config`.
+fn validate(words: &[&str], config: &Config) -> Result<(), Error> {
+    validate_pool(words.len(), config)
+}
+
+/// Check that a password can be generated out of `pool` words with the settings in `config`.
+fn validate_pool(pool: usize, config: &Config) -> Result<(), Error> {
+    validate_number(pool, config.number)?;
+    if config.separator.chars().any(char::is_control) {
+        return Err(Error::InvalidSeparator(config.separator.clone()));
+    }
+
+    Ok(())
+}
+
+/// Check that `number` distinct words can be picked out of `pool`.
+fn validate_number(pool: usize, number: usize) -> Result<(), Error> {
+    if pool == 0 {
+        return Err(Error::EmptyWordList);
+    }
+    if number == 0 {
+        return Err(Error::NoWords);
+    }
+    if number > pool {
+        return Err(Error::NotEnoughWords {
+            requested: number,
+            available: pool,
+        });
+    }
+
+    Ok(())
+}
+
+/// Pick `number` of the words in random order, run them through `pipeline`
+/// and join them with `separator`.
+fn build_password<T: Rng>(
+    words: &[&str],
+    number: usize,
+    pipeline: &[&dyn WordTransform],
+    separator: &str,
+    rng: &mut T,
+) -> Password {
+    let entropy = estimate_entropy(words.len(), number, pipeline);
+
+    let picked = {
+        span!(
+            tracing::Level::TRACE,
+            "sample_words",
+            number,
+            pool = words.len()
+        );
+        get_random_words(words, rng, number)
+    };
+    let random_words: Vec<String> = {
+        span!(
+            tracing::Level::TRACE,
+            "transform",
+            transforms = pipeline.len()
+        );
+        picked
+            .map(|word| transform::apply_all(pipeline, word, rng))
+            .collect()
+    };
+
+    Password::new(random_words, separator, entropy)
+}
+
+/// Append a password of `number` of the words, run through `pipeline` and
+/// joined with `separator`, to `out`.
+///
+/// Words are written straight into `out`, so unlike [`build_password`] nothing
+/// but the picked indices is allocated when `out` has room enough.
+fn write_password<T: Rng>(
+    words: &[&str],
+    number: usize,
+    pipeline: &[&dyn WordTransform],
+    separator: &str,
+    out: &mut String,
+    rng: &mut T,
+) {
+    span!(
+        tracing::Level::TRACE,
+        "write_password",
+        number,
+        pool = words.len()
+    );
+    for (i, word) in get_random_words(words, rng, number).enumerate() {
+        if i > 0 {
+            out.push_str(separator);
+        }
+        transform::apply_all_into(pipeline, word, out, rng);
+    }
+}
+
+/// Pick `number` distinct words out of `words` at random, as they are: without
+/// changing their case or joining them into a password.
+#[cfg(feature = "std")]
+pub fn sample_words<'a>(words: &[&'a str], number: usize) -> Result<Vec<&'a str>, Error> {
+    sample_words_with(words, number, &mut rand::thread_rng())
+}
+
+/// Pick `number` distinct words out of `words`, using `rng` for every random
+/// choice, see [`sample_words`].
+pub fn sample_words_with<'a, T: Rng>(
+    words: &[&'a str],
+    number: usize,
+    rng: &mut T,
+) -> Result<Vec<&'a str>, Error> {
+    span!(
+        tracing::Level::DEBUG,
+        "sample_words",
+        number,
+        pool = words.len()
+    );
+    validate_number(words.len(), number)?;
+
+    Ok(get_random_words(words, rng, number).collect())
+}
+
+/// Bits of entropy of `number` distinct words picked at random out of `pool`,
+/// in random order, which is how every password is made.
+///
+/// `number` must not be larger than `pool`.
+pub fn word_entropy(pool: usize, number: usize) -> f64 {
+    // every ordered choice of distinct words is equally likely: pool! / (pool - number)!
+    (0..number).map(|i| libm::log2((pool - i) as f64)).sum()
+}
+
+/// Estimate the entropy in bits of a password made of `number` distinct words
+/// out of `pool`, in random order, each run through `pipeline`.
+fn estimate_entropy(pool: usize, number: usize, pipeline: &[&dyn WordTransform]) -> f64 {
+    let transforms: f64 = pipeline.iter().map(|transform| transform.entropy()).sum();
+
+    word_entropy(pool, number) + transforms * number as f64
+}
+
+/// Return an iterator over `num` distinct words chosen at random, in random order.
+///
+/// Only `num` indices are drawn, the rest of the list is never looked at.
+fn get_random_words<'a, 'w, T>(
+    words: &'w [&'a str],
+    rng: &mut T,
+    num: usize,
+) -> impl Iterator<Item = &'a str> + 'w
+where
+    T: Rng + ?Sized,
+{
+    words.choose_multiple(rng, num).copied()
+}
+
+/// An extension trait to change letter casing, appending the result to an
+/// existing buffer so that passwords are built without a string per word.
+///
+/// Only the default, locale-independent Unicode mappings are used, including
+/// those that map a letter to several characters, such as `ß` to `SS`. The
+/// copies made of words outside of ASCII are zeroed once appended.
+trait ExtraCases {
+    fn push_uppercase(&self, out: &mut String);
+
+    fn push_lowercase(&self, out: &mut String);
+
+    fn push_capitalized(&self, out: &mut String);
+
+    fn push_random_case<T: Rng + ?Sized>(&self, out: &mut String, rng: &mut T);
+
+    /// Return a new string with the first letter capitalized and the rest in lowercase.
+    #[cfg(test)]
+    fn capitalize(&self) -> String {
+        let mut out = String::new();
+        self.push_capitalized(&mut out);
+        out
+    }
+}
+
+impl ExtraCases for str {
+    fn push_uppercase(&self, out: &mut String) {
+        if self.is_ascii() {
+            out.extend(self.chars().map(|c| c.to_ascii_uppercase()));
+        } else {
+            out.push_str(&Zeroizing::new(self.to_uppercase()));
+        }
+    }
+
+    fn push_lowercase(&self, out: &mut String) {
+        // the standard library takes care of context-dependent letters such as a final sigma
+        if self.is_ascii() {
+            out.extend(self.chars().map(|c| c.to_ascii_lowercase()));
+        } else {
+            out.push_str(&Zeroizing::new(self.to_lowercase()));
+        }
+    }
+
+    /// Append the word with its first grapheme cluster in title case and the
+    /// rest in lowercase.
+    ///
+    /// Only the first character of the cluster changes, so combining marks stay
+    /// on their letter, and the word is lowercased as a whole so that
+    /// context-dependent letters such as a final sigma come out right.
+    fn push_capitalized(&self, out: &mut String) {
+        if self.is_ascii() {
+            let mut chars = self.chars();
+            if let Some(first) = chars.next() {
+                out.push(first.to_ascii_uppercase());
+                chars.as_str().push_lowercase(out);
+            }
+            return;
+        }
+
+        let first = match self.graphemes(true).next() {
+            Some(first) => first,
+            None => return,
+        };
+        let mut chars = first.chars();
+        if let Some(c) = chars.next() {
+            push_titlecase(c, out);
+        }
+        out.push_str(chars.as_str());
+
+        // lowercasing maps every character on its own, except for the sigma whose
+        // forms have the same length, so the cluster is as long in both
+        let lower = Zeroizing::new(self.to_lowercase());
+        out.push_str(&lower[first.to_lowercase().len()..]);
+    }
+
+    /// Append the word in either upper or lower case, chosen at random.
+    fn push_random_case<T: Rng + ?Sized>(&self, out: &mut String, rng: &mut T) {
+        if rng.gen_range(0..=1) == 0 {
+            self.push_lowercase(out);
+        } else {
+            self.push_uppercase(out);
+        }
+    }
+}
+
+/// Append the title case of `c`: the digraphs such as `ǆ` have their own, and
+/// of letters whose uppercase is several characters long, such as `ß`, only the
+/// first character stays in uppercase.
+fn push_titlecase(c: char, out: &mut String) {
+    let digraph = match c {
+        'Ǆ' | 'ǅ' | 'ǆ' => 'ǅ',
+        'Ǉ' | 'ǈ' | 'ǉ' => 'ǈ',
+        'Ǌ' | 'ǋ' | 'ǌ' => 'ǋ',
+        'Ǳ' | 'ǲ' | 'ǳ' => 'ǲ',
+        _ => {
+            let mut upper = c.to_uppercase();
+            out.extend(upper.next());
+            out.extend(upper.flat_map(char::to_lowercase));
+            return;
+        }
+    };
+
+    out.push(digraph);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn capitalize_lower_case() {
+        let all_lowercase = "foobar";
+        assert_eq!(all_lowercase.capitalize(), "Foobar");
+    }
+
+    #[test]
+    fn capitalize_upper_case() {
+        let all_uppercase = "FOOBAR";
+        assert_eq!(all_uppercase.capitalize(), "Foobar");
+    }
+
+    #[test]
+    fn capitalize_mixed_case() {
+        let mixed_case = "foOBaR";
+        assert_eq!(mixed_case.capitalize(), "Foobar");
+    }
+
+    #[test]
+    fn capitalize_sharp_s() {
+        assert_eq!("straße".capitalize(), "Straße");
+        assert_eq!("ßig".capitalize(), "Ssig");
+        assert_eq!("STRASSE".capitalize(), "Strasse");
+    }
+
+    #[test]
+    fn capitalize_accented_initials() {
+        assert_eq!("élan".capitalize(), "Élan");
+        assert_eq!("ÅNGSTRÖM".capitalize(), "Ångström");
+        assert_eq!("ǆungla".capitalize(), "ǅungla");
+        assert_eq!("ﬁnale".capitalize(), "Finale");
+    }
+
+    #[test]
+    fn capitalize_combining_marks() {
+        // "école" with the accent as a combining mark, which must stay on the "E"
+        assert_eq!("e\u{301}cole".capitalize(), "E\u{301}cole");
+        assert_eq!("a\u{308}\u{304}rger".capitalize(), "A\u{308}\u{304}rger");
+    }
+
+    #[test]
+    fn capitalize_final_sigma() {
+        assert_eq!("ΟΔΟΣ".capitalize(), "Οδος");
+        assert_eq!("ΟΣ".capitalize(), "Ος");
+    }
+
+    fn upper(word: &str) -> String {
+        let mut out = String::new();
+        word.push_uppercase(&mut out);
+        out
+    }
+
+    fn lower(word: &str) -> String {
+        let mut out = String::new();
+        word.push_lowercase(&mut out);
+        out
+    }
+
+    #[test]
+    fn ignores_turkish_casing() {
+        // Turkish would give "İSTANBUL", "ılık" and "Istanbul"
+        assert_eq!(upper("istanbul"), "ISTANBUL");
+        assert_eq!(lower("ILIK"), "ilik");
+        assert_eq!("İstanbul".capitalize(), "İstanbul");
+        // the dotted and dotless letters keep their own mappings
+        assert_eq!(upper("ılık"), "ILIK");
+        assert_eq!(lower("İSTANBUL"), "i\u{307}stanbul");
+        assert_eq!(lower("DIŞ"), "diş");
+    }
+
+    #[test]
+    fn maps_to_several_characters() {
+        assert_eq!(upper("straße"), "STRASSE");
+        assert_eq!(upper("ﬁnale"), "FINALE");
+        assert_eq!(lower("ΣΟΦΟΣ"), "σοφος");
+    }
+
+    #[test]
+    fn capitalize_nothing() {
+        let nothing = "";
+        assert_eq!(nothing.capitalize(), "");
+    }
+
+    #[test]
+    fn samples_words_as_they_are() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let words = ["Tokyo", "Lima", "Oslo"];
+        let mut rng = StdRng::from_seed([7; 32]);
+        let mut sample = sample_words_with(&words, 3, &mut rng).unwrap();
+        sample.sort_unstable();
+        assert_eq!(sample, ["Lima", "Oslo", "Tokyo"]);
+
+        assert!(sample_words_with(&words, 4, &mut rng).is_err());
+        assert!(sample_words_with(&words, 0, &mut rng).is_err());
+    }
+
+    #[test]
+    fn looks_up_the_words_generate_password_with_picks() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let words = List::Long.as_slice();
+        let config = Config {
+            number: 6,
+            case: Case::Mixed,
+            ..Config::default()
+        };
+        let with = generate_password_with(words, &config, &mut StdRng::from_seed([3; 32])).unwrap();
+        let indexed = generate_password_indexed(
+            words.len(),
+            |i| words[i],
+            &config,
+            &mut StdRng::from_seed([3; 32]),
+        )
+        .unwrap();
+        assert_eq!(indexed.expose(), with.expose());
+        assert_eq!(indexed.entropy(), with.entropy());
+
+        assert!(matches!(
+            generate_password_indexed(0, |i| words[i], &config, &mut StdRng::from_seed([3; 32])),
+            Err(Error::EmptyWordList)
+        ));
+    }
+
+    #[test]
+    fn gets_random_words() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        // the function being tested uses randomness, rng is created from seed
+        let mut rng = StdRng::from_seed([42; 32]);
+
+        let actual: Vec<&str> = get_random_words(List::Long.as_slice(), &mut rng, 6).collect();
+        let expected = vec!["sprain", "disdain", "reuse", "tartly", "navy", "earlobe"];
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn rejects_invalid_config() {
+        let words = ["foo", "bar", "buzz"];
+        let config = |number: usize, separator: &str| Config {
+            number,
+            separator: String::from(separator),
+            ..Config::default()
+        };
+
+        assert!(matches!(
+            validate(&[], &config(1, " ")),
+            Err(Error::EmptyWordList)
+        ));
+        assert!(matches!(
+            validate(&words, &config(0, " ")),
+            Err(Error::NoWords)
+        ));
+        assert!(matches!(
+            validate(&words, &config(4, " ")),
+            Err(Error::NotEnoughWords {
+                requested: 4,
+                available: 3
+            })
+        ));
+        assert!(matches!(
+            validate(&words, &config(2, "\n")),
+            Err(Error::InvalidSeparator(_))
+        ));
+        assert!(validate(&words, &config(3, "-")).is_ok());
+    }
+
+    #[test]
+    fn generates_from_caller_words_and_rng() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let words = ["correct", "horse", "battery", "staple"];
+        let config = Config {
+            number: 4,
+            separator: String::from("-"),
+            ..Config::default()
+        };
+
+        // the same seed must always produce the same password
+        let first = generate_password_with(&words, &config, &mut StdRng::from_seed([7; 32]));
+        let second = generate_password_with(&words, &config, &mut StdRng::from_seed([7; 32]));
+        let (first, second) = (first.unwrap(), second.unwrap());
+
+        assert_eq!(first.expose(), second.expose());
+        let mut picked = first.words().to_vec();
+        picked.sort();
+        assert_eq!(picked, ["battery", "correct", "horse", "staple"]);
+    }
+
+    #[test]
+    fn estimates_entropy() {
+        // 7776 words: log2(7776) = 12.92...
+        let one_word = estimate_entropy(7776, 1, &[&Case::Lower]);
+        assert!((one_word - 12.925).abs() < 0.001);
+
+        let two_words = estimate_entropy(2, 2, &[&Case::Lower]);
+        assert_eq!(two_words, 1.0);
+
+        let mixed = estimate_entropy(2, 2, &[&Case::Mixed]);
+        assert_eq!(mixed, 3.0);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn config_round_trips_through_serde() {
+        let config: Config =
+            serde_json::from_str(r#"{"number": 4, "list": "short2", "case": "mixed"}"#).unwrap();
+
+        assert_eq!(config.number, 4);
+        assert_eq!(config.separator, " ");
+        assert!(matches!(config.list, List::Short2));
+        assert!(matches!(config.case, Case::Mixed));
+
+        let json = serde_json::to_string(&config).unwrap();
+        assert_eq!(
+            json,
+            r#"{"number":4,"separator":" ","list":"short2","case":"mixed"}"#
+        );
+    }
+
+    fn default_change_case(case: Case) -> Vec<String> {
+        ["foo", "bar", "buzz"]
+            .iter()
+            .map(|word| transform::apply_all(&[&case], word, &mut rand::thread_rng()))
+            .collect()
+    }
+
+    #[test]
+    fn change_to_uppercase() {
+        let actual = default_change_case(Case::Upper);
+        let expected = vec!["FOO", "BAR", "BUZZ"];
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn change_to_lowercase() {
+        let actual = default_change_case(Case::Lower);
+        let expected = vec!["foo", "bar", "buzz"];
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn change_to_capitalized() {
+        let actual = default_change_case(Case::Capitalized);
+        let expected = vec!["Foo", "Bar", "Buzz"];
+
+        assert_eq!(actual, expected);
+    }
+}
@@ -0,0 +1,153 @@
+//! Conversions between [`List`], [`Case`] and [`Order`] and the names users
+//! know them by, the same ones the command line accepts.
+
+use crate::{Case, Error, List, Order};
+use alloc::string::ToString;
+use core::{fmt, str::FromStr};
+
+/// Names of every variant of an enum, in declaration order.
+pub trait VariantNames {
+    const VARIANTS: &'static [&'static str];
+}
+
+impl List {
+    /// Name of the list, as accepted by [`List::from_str`].
+    pub fn name(&self) -> &'static str {
+        match self {
+            List::Long => "long",
+            List::Short1 => "short1",
+            List::Short2 => "short2",
+        }
+    }
+}
+
+impl VariantNames for List {
+    const VARIANTS: &'static [&'static str] = &["long", "short1", "short2"];
+}
+
+impl fmt::Display for List {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for List {
+    type Err = Error;
+
+    /// Parse a list name, ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "long" => Ok(List::Long),
+            "short1" => Ok(List::Short1),
+            "short2" => Ok(List::Short2),
+            _ => Err(Error::UnknownList(s.to_string())),
+        }
+    }
+}
+
+impl Case {
+    /// Name of the case, as accepted by [`Case::from_str`].
+    pub fn name(&self) -> &'static str {
+        match self {
+            Case::Upper => "upper",
+            Case::Lower => "lower",
+            Case::Capitalized => "capitalized",
+            Case::Mixed => "mixed",
+        }
+    }
+}
+
+impl VariantNames for Case {
+    const VARIANTS: &'static [&'static str] = &["upper", "lower", "capitalized", "mixed"];
+}
+
+impl fmt::Display for Case {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Case {
+    type Err = Error;
+
+    /// Parse a case name, ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "upper" => Ok(Case::Upper),
+            "lower" => Ok(Case::Lower),
+            "capitalized" => Ok(Case::Capitalized),
+            "mixed" => Ok(Case::Mixed),
+            _ => Err(Error::UnknownCase(s.to_string())),
+        }
+    }
+}
+
+impl Order {
+    /// Name of the order, as accepted by [`Order::from_str`].
+    pub fn name(&self) -> &'static str {
+        match self {
+            Order::Shuffle => "shuffle",
+            Order::Alphabetical => "alphabetical",
+            Order::Length => "length",
+            Order::Sampled => "sampled",
+        }
+    }
+}
+
+impl VariantNames for Order {
+    const VARIANTS: &'static [&'static str] = &["shuffle", "alphabetical", "length", "sampled"];
+}
+
+impl fmt::Display for Order {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Order {
+    type Err = Error;
+
+    /// Parse an order name, ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "shuffle" => Ok(Order::Shuffle),
+            "alphabetical" => Ok(Order::Alphabetical),
+            "length" => Ok(Order::Length),
+            "sampled" => Ok(Order::Sampled),
+            _ => Err(Error::UnknownOrder(s.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_round_trip() {
+        for name in List::VARIANTS {
+            assert_eq!(name.parse::<List>().unwrap().to_string(), *name);
+        }
+        for name in Case::VARIANTS {
+            assert_eq!(name.parse::<Case>().unwrap().to_string(), *name);
+        }
+        for name in Order::VARIANTS {
+            assert_eq!(name.parse::<Order>().unwrap().to_string(), *name);
+        }
+    }
+
+    #[test]
+    fn parses_ignoring_case() {
+        assert!(matches!("Short1".parse::<List>(), Ok(List::Short1)));
+        assert!(matches!("UPPER".parse::<Case>(), Ok(Case::Upper)));
+    }
+
+    #[test]
+    fn rejects_unknown_names() {
+        assert!(matches!("huge".parse::<List>(), Err(Error::UnknownList(_))));
+        assert!(matches!(
+            "sideways".parse::<Case>(),
+            Err(Error::UnknownCase(_))
+        ));
+    }
+}
@@ -0,0 +1,497 @@
+use alloc::{
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::{cmp::Ordering, fmt, mem};
+use rand::{seq::SliceRandom, Rng};
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
+use crate::Order;
+
+/// A generated password, along with how it was put together.
+///
+/// To keep the password out of logs by accident, neither `Debug` nor `Display`
+/// show it; use [`Password::expose`] to get at it. Every buffer holding a part
+/// of the password is zeroed when it is dropped.
+///
+/// With the `secure-mem` feature, the buffers are also locked in RAM so that
+/// they are never written to swap.
+#[derive(Zeroize, ZeroizeOnDrop)]
+pub struct Password {
+    value: String,
+    words: Vec<String>,
+    separators: Vec<String>,
+    insertions: Vec<Insertion>,
+    entropy: f64,
+    /// Declared last, so that the pages are only unlocked once zeroed.
+    #[cfg(feature = "secure-mem")]
+    #[zeroize(skip)]
+    locked: crate::secure_mem::LockedPages,
+}
+
+/// Characters inserted into a password on top of its words, such as digits or symbols.
+#[derive(Clone, Debug, PartialEq, Eq, Zeroize)]
+pub struct Insertion {
+    /// Byte offset of the inserted text in the password.
+    pub position: usize,
+    /// The inserted text.
+    pub text: String,
+}
+
+impl Password {
+    /// Join the words with the separator.
+    pub(crate) fn new(words: Vec<String>, separator: &str, entropy: f64) -> Self {
+        Self::from_parts(
+            words.join(separator),
+            vec![separator.to_string(); words.len().saturating_sub(1)],
+            words,
+            Vec::new(),
+            entropy,
+        )
+    }
+
+    fn from_parts(
+        value: String,
+        separators: Vec<String>,
+        words: Vec<String>,
+        insertions: Vec<Insertion>,
+        entropy: f64,
+    ) -> Self {
+        #[cfg(feature = "secure-mem")]
+        let locked = {
+            let mut locked = crate::secure_mem::LockedPages::default();
+            let texts = insertions.iter().map(|insertion| &insertion.text);
+            for text in [&value]
+                .into_iter()
+                .chain(&words)
+                .chain(&separators)
+                .chain(texts)
+            {
+                locked.lock(text.as_ptr(), text.capacity());
+            }
+            locked
+        };
+
+        Self {
+            value,
+            words,
+            separators,
+            insertions,
+            entropy,
+            #[cfg(feature = "secure-mem")]
+            locked,
+        }
+    }
+
+    /// The password itself.
+    pub fn expose(&self) -> &str {
+        &self.value
+    }
+
+    /// The words of the password, in order and with their case already changed.
+    pub fn words(&self) -> &[String] {
+        &self.words
+    }
+
+    /// The separators between the words, one fewer than there are words.
+    pub fn separators(&self) -> &[String] {
+        &self.separators
+    }
+
+    /// Digits or symbols inserted on top of the words, in order of position.
+    pub fn insertions(&self) -> &[Insertion] {
+        &self.insertions
+    }
+
+    /// Estimated entropy of the password in bits, assuming the attacker knows
+    /// the word list and every setting used to generate it.
+    pub fn entropy(&self) -> f64 {
+        self.entropy
+    }
+
+    /// Put the words in `order`, keeping the separators where they are, and
+    /// lower the entropy estimate by the bits the order takes away.
+    ///
+    /// Words are compared without allocating lowercase copies of them, which
+    /// would be left behind in memory. The positions of insertions aren't
+    /// moved along with the words, so reorder before inserting anything.
+    pub fn reorder<T: Rng + ?Sized>(mut self, order: Order, rng: &mut T) -> Self {
+        let mut words = mem::take(&mut self.words);
+        let alphabetical = |a: &String, b: &String| {
+            let a = a.chars().flat_map(char::to_lowercase);
+            a.cmp(b.chars().flat_map(char::to_lowercase))
+        };
+        match order {
+            Order::Shuffle => words.shuffle(rng),
+            Order::Sampled => {}
+            Order::Alphabetical => words.sort_by(alphabetical),
+            Order::Length => {
+                words.sort_by(|a, b| match a.chars().count().cmp(&b.chars().count()) {
+                    Ordering::Equal => alphabetical(a, b),
+                    unequal => unequal,
+                })
+            }
+        }
+
+        let entropy = self.entropy - order.lost_entropy(words.len());
+        let separators = mem::take(&mut self.separators);
+
+        Self::from_parts(
+            join(&words, &separators),
+            separators,
+            words,
+            mem::take(&mut self.insertions),
+            entropy,
+        )
+    }
+
+    /// Replace every separator with a fresh one drawn by `separator`, such as a
+    /// random digit, and raise the entropy estimate by the `entropy` bits each
+    /// of them adds.
+    ///
+    /// Like [`Password::reorder`], this doesn't move the positions of insertions.
+    pub fn separate_with<T, F>(mut self, entropy: f64, rng: &mut T, mut separator: F) -> Self
+    where
+        T: Rng + ?Sized,
+        F: FnMut(&mut T) -> String,
+    {
+        let words = mem::take(&mut self.words);
+        let separators: Vec<String> = (1..words.len()).map(|_| separator(rng)).collect();
+        let entropy = self.entropy + entropy * separators.len() as f64;
+
+        Self::from_parts(
+            join(&words, &separators),
+            separators,
+            words,
+            mem::take(&mut self.insertions),
+            entropy,
+        )
+    }
+
+    /// Make one small change to one of the words: swap two letters next to
+    /// each other, double a letter or reverse the word, and raise the entropy
+    /// estimate by the bits of the choice.
+    ///
+    /// The change is picked out of every distinct word the changes make, so
+    /// that swapping two identical letters or reversing a palindrome, which
+    /// change nothing, aren't counted. Each change is counted on its own even
+    /// though a later one may undo it, which few do. Like [`Password::reorder`],
+    /// mutate before inserting anything.
+    pub fn mutate<T: Rng + ?Sized>(mut self, rng: &mut T) -> Self {
+        let variants: Vec<usize> = self
+            .words
+            .iter()
+            .map(|word| Mutation::count(word))
+            .collect();
+        let total: usize = variants.iter().sum();
+        if total == 0 {
+            return self;
+        }
+        let entropy = self.entropy + libm::log2(total as f64);
+
+        let mut pick = rng.gen_range(0..total);
+        let mut words = mem::take(&mut self.words);
+        for (word, variants) in words.iter_mut().zip(variants) {
+            if pick < variants {
+                let mut old = mem::replace(word, Mutation::apply(word, pick));
+                old.zeroize();
+                break;
+            }
+            pick -= variants;
+        }
+        let separators = mem::take(&mut self.separators);
+
+        Self::from_parts(
+            join(&words, &separators),
+            separators,
+            words,
+            mem::take(&mut self.insertions),
+            entropy,
+        )
+    }
+
+    /// Insert one of `symbols` inside one of the words, never before its first
+    /// character or after its last, and raise the entropy estimate by the bits
+    /// of the choice.
+    ///
+    /// Every place between two characters of a word is as likely as any other,
+    /// so the choice is worth the bits of their number times that of `symbols`.
+    /// Without such a place, as with words of one character, or without
+    /// symbols, the password is left as it is.
+    pub fn embed_symbol<T: Rng + ?Sized>(mut self, symbols: &[char], rng: &mut T) -> Self {
+        let mut places = Vec::new();
+        let mut start = 0;
+        for (i, word) in self.words.iter().enumerate() {
+            if i > 0 {
+                start += self.separators[i - 1].len();
+            }
+            places.extend(
+                word.char_indices()
+                    .skip(1)
+                    .map(|(offset, _)| start + offset),
+            );
+            start += word.len();
+        }
+        let (Some(&place), Some(&symbol)) = (places.choose(rng), symbols.choose(rng)) else {
+            return self;
+        };
+        let entropy = self.entropy + libm::log2((places.len() * symbols.len()) as f64);
+
+        // past the characters inserted before the place
+        let mut position = place;
+        for insertion in &self.insertions {
+            if insertion.position <= position {
+                position += insertion.text.len();
+            }
+        }
+        let mut value = String::with_capacity(self.value.len() + symbol.len_utf8());
+        value.push_str(&self.value[..position]);
+        value.push(symbol);
+        value.push_str(&self.value[position..]);
+
+        let mut insertions = mem::take(&mut self.insertions);
+        for insertion in &mut insertions {
+            if insertion.position >= position {
+                insertion.position += symbol.len_utf8();
+            }
+        }
+        let text = symbol.to_string();
+        insertions.push(Insertion { position, text });
+        insertions.sort_by_key(|insertion| insertion.position);
+
+        Self::from_parts(
+            value,
+            mem::take(&mut self.separators),
+            mem::take(&mut self.words),
+            insertions,
+            entropy,
+        )
+    }
+
+    /// Take the password out as a plain string.
+    ///
+    /// The returned string is no longer zeroed on drop, nor locked in RAM.
+    pub fn into_string(mut self) -> String {
+        core::mem::take(&mut self.value)
+    }
+}
+
+/// The small changes [`Password::mutate`] makes to a word, each making a
+/// word different from the others and from the word itself.
+enum Mutation {
+    /// Swap the letter at this index with the one before it.
+    Swap(usize),
+    /// Double the letter at this index, the first of a run of the same letter.
+    Double(usize),
+    /// Reverse the word.
+    Reverse,
+}
+
+impl Mutation {
+    fn all(chars: &[char]) -> impl Iterator<Item = Self> + '_ {
+        let swaps = (1..chars.len())
+            .filter(|&i| chars[i - 1] != chars[i])
+            .map(Mutation::Swap);
+        let doubles = (0..chars.len())
+            .filter(|&i| i == 0 || chars[i - 1] != chars[i])
+            .map(Mutation::Double);
+        // a reversal that only swaps the two letters in the middle is a swap already
+        let changed: Vec<usize> = (0..chars.len())
+            .filter(|&i| chars[i] != chars[chars.len() - 1 - i])
+            .collect();
+        let reverse = match changed[..] {
+            [] => None,
+            [a, b] if b == a + 1 => None,
+            _ => Some(Mutation::Reverse),
+        };
+        swaps.chain(doubles).chain(reverse)
+    }
+
+    /// Number of distinct words the changes make out of `word`.
+    fn count(word: &str) -> usize {
+        let chars = Zeroizing::new(word.chars().collect::<Vec<char>>());
+        Self::all(&chars).count()
+    }
+
+    /// `word` with the change at `index` of [`Mutation::all`] made.
+    fn apply(word: &str, index: usize) -> String {
+        let mut chars = Zeroizing::new(word.chars().collect::<Vec<char>>());
+        let mutation = Self::all(&chars).nth(index).expect("a change of the word");
+        match mutation {
+            Mutation::Swap(i) => chars.swap(i - 1, i),
+            Mutation::Double(i) => {
+                let letter = chars[i];
+                chars.insert(i, letter);
+            }
+            Mutation::Reverse => chars.reverse(),
+        }
+        chars.iter().collect()
+    }
+}
+
+/// `words` with `separators` between them.
+fn join(words: &[String], separators: &[String]) -> String {
+    let len = words.iter().chain(separators).map(String::len).sum();
+    let mut value = String::with_capacity(len);
+    for (i, word) in words.iter().enumerate() {
+        if i > 0 {
+            value.push_str(&separators[i - 1]);
+        }
+        value.push_str(word);
+    }
+    value
+}
+
+impl Clone for Password {
+    fn clone(&self) -> Self {
+        Self::from_parts(
+            self.value.clone(),
+            self.separators.clone(),
+            self.words.clone(),
+            self.insertions.clone(),
+            self.entropy,
+        )
+    }
+}
+
+impl fmt::Debug for Password {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Password(****)")
+    }
+}
+
+impl fmt::Display for Password {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("****")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::format;
+
+    fn password() -> Password {
+        let words = vec![String::from("foo"), String::from("bar")];
+        Password::new(words, "-", 2.0)
+    }
+
+    #[test]
+    fn redacts_formatting() {
+        let password = password();
+
+        assert_eq!(format!("{:?}", password), "Password(****)");
+        assert_eq!(password.to_string(), "****");
+        assert_eq!(password.expose(), "foo-bar");
+    }
+
+    #[test]
+    fn reorders_words() {
+        let words = ["Pear", "fig", "apple"].map(String::from).to_vec();
+        let password = Password::from_parts(
+            String::from("Pear-fig+apple"),
+            vec![String::from("-"), String::from("+")],
+            words,
+            Vec::new(),
+            10.0,
+        );
+        let rng = &mut rand::rngs::mock::StepRng::new(0, 1);
+
+        let sorted = password.clone().reorder(Order::Alphabetical, rng);
+        assert_eq!(sorted.expose(), "apple-fig+Pear");
+        assert_eq!(sorted.words(), ["apple", "fig", "Pear"]);
+        assert!((sorted.entropy() - (10.0 - libm::log2(6.0))).abs() < 1e-9);
+
+        let by_length = password.clone().reorder(Order::Length, rng);
+        assert_eq!(by_length.expose(), "fig-Pear+apple");
+
+        let sampled = password.reorder(Order::Sampled, rng);
+        assert_eq!(sampled.expose(), "Pear-fig+apple");
+        assert_eq!(sampled.entropy(), 10.0);
+    }
+
+    #[test]
+    fn replaces_separators() {
+        let words = ["foo", "bar", "baz"].map(String::from).to_vec();
+        let password = Password::new(words, " ", 10.0);
+        let mut next = 0;
+        let separated = password.separate_with(2.0, &mut rand::thread_rng(), |_| {
+            next += 1;
+            next.to_string()
+        });
+
+        assert_eq!(separated.expose(), "foo1bar2baz");
+        assert_eq!(separated.separators(), ["1", "2"]);
+        assert_eq!(separated.entropy(), 14.0);
+    }
+
+    #[test]
+    fn mutates_words() {
+        let variants = |word: &str| {
+            let mut words: Vec<String> = (0..Mutation::count(word))
+                .map(|i| Mutation::apply(word, i))
+                .collect();
+            words.sort();
+            words
+        };
+        assert_eq!(
+            variants("abc"),
+            ["aabc", "abbc", "abcc", "acb", "bac", "cba"]
+        );
+        // doubling either "l" makes the same word, "ab" reversed is swapped
+        assert_eq!(variants("all"), ["aall", "alll", "lal", "lla"]);
+        assert_eq!(variants("ab"), ["aab", "abb", "ba"]);
+        assert_eq!(
+            variants("xabx"),
+            ["axbx", "xaabx", "xabbx", "xabxx", "xaxb", "xbax", "xxabx"]
+        );
+        assert_eq!(Mutation::count("a"), 1);
+
+        let words = ["ab", "c"].map(String::from).to_vec();
+        let password = Password::new(words, " ", 10.0);
+        let mutated = password.mutate(&mut rand::rngs::mock::StepRng::new(0, 1));
+        assert_eq!(mutated.expose(), "ba c");
+        assert_eq!(mutated.words(), ["ba", "c"]);
+        assert_eq!(mutated.entropy(), 12.0);
+    }
+
+    #[test]
+    fn embeds_symbols_inside_words() {
+        let words = ["ab", "c", "de"].map(String::from).to_vec();
+        let password = Password::new(words, "-", 10.0);
+        let rng = &mut rand::rngs::mock::StepRng::new(0, 1);
+
+        // "a_b" and "d_e" are the only places, StepRng picks the first of each
+        let embedded = password.embed_symbol(&['!', '?'], rng);
+        assert_eq!(embedded.expose(), "a!b-c-de");
+        assert_eq!(embedded.words(), ["ab", "c", "de"]);
+        assert_eq!(embedded.entropy(), 12.0);
+        let insertion = Insertion {
+            position: 1,
+            text: String::from("!"),
+        };
+        assert_eq!(embedded.insertions(), [insertion]);
+
+        let again = embedded.clone().embed_symbol(&['#'], rng);
+        assert_eq!(again.expose().matches(['!', '#']).count(), 2);
+        assert_eq!(again.insertions().len(), 2);
+        for insertion in again.insertions() {
+            assert_eq!(&again.expose()[insertion.position..][..1], insertion.text);
+        }
+
+        let single = Password::new(vec![String::from("a")], " ", 1.0);
+        assert_eq!(single.embed_symbol(&['!'], rng).expose(), "a");
+    }
+
+    #[test]
+    fn zeroizes_buffers() {
+        let mut password = password();
+        password.zeroize();
+
+        assert_eq!(password.expose(), "");
+        assert!(password.words().is_empty());
+        assert!(password.separators().is_empty());
+        assert_eq!(password.entropy(), 0.0);
+    }
+}
@@ -0,0 +1,79 @@
+//! Python bindings, built with [PyO3](https://pyo3.rs) and packaged with
+//! [maturin](https://www.maturin.rs).
+//!
+//! ```python
+//! import xkpass
+//!
+//! password = xkpass.generate(number=4, case="capitalized")
+//! bits = xkpass.entropy(number=4, case="capitalized")
+//! ```
+
+use crate::{estimate_entropy, generate_password, validate, Case, Config, List};
+use pyo3::{exceptions::PyValueError, prelude::*};
+
+/// Generate a password, the arguments have the same defaults as the command line.
+#[pyfunction]
+#[pyo3(signature = (number = 6, separator = " ", list = "long", case = "lower"))]
+fn generate(number: usize, separator: &str, list: &str, case: &str) -> PyResult<String> {
+    let config = config(number, separator, list, case)?;
+    let password = generate_password(config).map_err(value_error)?;
+
+    Ok(password.into_string())
+}
+
+/// Number of bits of entropy of passwords generated with these settings.
+#[pyfunction]
+#[pyo3(signature = (number = 6, list = "long", case = "lower"))]
+fn entropy(number: usize, list: &str, case: &str) -> PyResult<f64> {
+    let config = config(number, " ", list, case)?;
+    let words = config.list.as_slice();
+    validate(words, &config).map_err(value_error)?;
+
+    Ok(estimate_entropy(
+        words.len(),
+        config.number,
+        &[&config.case],
+    ))
+}
+
+fn config(number: usize, separator: &str, list: &str, case: &str) -> PyResult<Config> {
+    Ok(Config {
+        number,
+        separator: separator.to_string(),
+        list: list.parse::<List>().map_err(value_error)?,
+        case: case.parse::<Case>().map_err(value_error)?,
+    })
+}
+
+fn value_error(err: crate::Error) -> PyErr {
+    PyValueError::new_err(err.to_string())
+}
+
+#[pymodule]
+fn xkpass(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_function(wrap_pyfunction!(generate, module)?)?;
+    module.add_function(wrap_pyfunction!(entropy, module)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generates_with_keywords() {
+        let password = generate(3, "+", "short1", "upper").unwrap();
+
+        assert_eq!(password.split('+').count(), 3);
+        assert_eq!(password, password.to_uppercase());
+    }
+
+    #[test]
+    fn computes_entropy() {
+        let bits = entropy(1, "long", "mixed").unwrap();
+
+        assert!((bits - (7776f64.log2() + 1.0)).abs() < 1e-9);
+        assert!(entropy(0, "long", "lower").is_err());
+        assert!(config(6, " ", "huge", "lower").is_err());
+    }
+}
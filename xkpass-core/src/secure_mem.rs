@@ -0,0 +1,193 @@
+//! Keeping the buffers of passwords out of swap, behind the `secure-mem` feature.
+//!
+//! The pages holding a password are locked in RAM with `mlock`, or
+//! `VirtualLock` on Windows, as soon as its buffers are allocated. Locks don't
+//! nest, and several buffers can share a page, so the locks on each page are
+//! counted and a page is only unlocked once none of its buffers need it.
+//!
+//! Locking is best effort: when the limit on locked memory is reached, the
+//! password is still generated, only without the guarantee.
+//!
+//! Exactly the buffers a [`Password`](crate::Password) keeps are locked: the
+//! password itself, its words, its separators and the text of its insertions.
+//! The words are locked once they are all picked and moved into it, and the
+//! copies made on the way, by case changes outside of ASCII and by transforms
+//! before the last one, are only zeroed when dropped, having never been locked.
+//! Neither is anything the caller owns: the word list, the separator of the
+//! [`Config`](crate::Config), or the copies made of the password with
+//! [`Password::expose`](crate::Password::expose) or `into_string`.
+
+use std::{
+    collections::BTreeMap,
+    sync::{Mutex, OnceLock, PoisonError},
+};
+
+/// Number of locks on each locked page, by address.
+static LOCKED: Mutex<BTreeMap<usize, usize>> = Mutex::new(BTreeMap::new());
+
+/// Pages locked for the buffers of a password, unlocked when dropped.
+#[derive(Debug, Default)]
+pub(crate) struct LockedPages(Vec<usize>);
+
+impl LockedPages {
+    /// Lock the pages holding the `capacity` bytes at `ptr`.
+    pub(crate) fn lock(&mut self, ptr: *const u8, capacity: usize) {
+        if capacity == 0 {
+            return;
+        }
+
+        let size = page_size();
+        let start = ptr as usize / size * size;
+        let end = (ptr as usize + capacity).div_ceil(size) * size;
+        let mut locked = LOCKED.lock().unwrap_or_else(PoisonError::into_inner);
+        for page in (start..end).step_by(size) {
+            let count = locked.entry(page).or_insert(0);
+            if *count == 0 && !sys::lock(page, size) {
+                locked.remove(&page);
+                continue;
+            }
+            *count += 1;
+            self.0.push(page);
+        }
+    }
+}
+
+impl Drop for LockedPages {
+    fn drop(&mut self) {
+        let size = page_size();
+        let mut locked = LOCKED.lock().unwrap_or_else(PoisonError::into_inner);
+        for page in self.0.drain(..) {
+            if let Some(count) = locked.get_mut(&page) {
+                *count -= 1;
+                if *count == 0 {
+                    locked.remove(&page);
+                    sys::unlock(page, size);
+                }
+            }
+        }
+    }
+}
+
+fn page_size() -> usize {
+    static PAGE_SIZE: OnceLock<usize> = OnceLock::new();
+    *PAGE_SIZE.get_or_init(sys::page_size)
+}
+
+#[cfg(unix)]
+mod sys {
+    pub(super) fn page_size() -> usize {
+        // SAFETY: sysconf has no preconditions
+        match unsafe { libc::sysconf(libc::_SC_PAGESIZE) } {
+            size if size > 0 => size as usize,
+            _ => 4096,
+        }
+    }
+
+    pub(super) fn lock(page: usize, size: usize) -> bool {
+        // SAFETY: the page belongs to a live allocation, locking doesn't change its contents
+        unsafe { libc::mlock(page as *const libc::c_void, size) == 0 }
+    }
+
+    pub(super) fn unlock(page: usize, size: usize) {
+        // SAFETY: the page was locked by `lock`
+        unsafe { libc::munlock(page as *const libc::c_void, size) };
+    }
+}
+
+#[cfg(windows)]
+mod sys {
+    use windows_sys::Win32::System::{
+        Memory::{VirtualLock, VirtualUnlock},
+        SystemInformation::{GetSystemInfo, SYSTEM_INFO},
+    };
+
+    pub(super) fn page_size() -> usize {
+        let mut info = SYSTEM_INFO::default();
+        // SAFETY: `info` is valid for writes for the duration of the call
+        unsafe { GetSystemInfo(&mut info) };
+        info.dwPageSize as usize
+    }
+
+    pub(super) fn lock(page: usize, size: usize) -> bool {
+        // SAFETY: the page belongs to a live allocation, locking doesn't change its contents
+        unsafe { VirtualLock(page as *const core::ffi::c_void, size) != 0 }
+    }
+
+    pub(super) fn unlock(page: usize, size: usize) {
+        // SAFETY: the page was locked by `lock`
+        unsafe { VirtualUnlock(page as *const core::ffi::c_void, size) };
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+mod sys {
+    pub(super) fn page_size() -> usize {
+        4096
+    }
+
+    pub(super) fn lock(_page: usize, _size: usize) -> bool {
+        false
+    }
+
+    pub(super) fn unlock(_page: usize, _size: usize) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn count(page: usize) -> Option<usize> {
+        LOCKED.lock().unwrap().get(&page).copied()
+    }
+
+    #[test]
+    fn counts_locks_on_shared_pages() {
+        // large enough to get pages of its own from the allocator
+        let buffer = vec![0u8; 1 << 18];
+        let page = buffer.as_ptr() as usize / page_size() * page_size() + page_size();
+
+        let mut first = LockedPages::default();
+        first.lock(buffer.as_ptr(), buffer.len());
+        if count(page).is_none() {
+            // the limit on locked memory is too low to tell anything
+            return;
+        }
+        let mut second = LockedPages::default();
+        second.lock(buffer.as_ptr(), buffer.len());
+        assert_eq!(count(page), Some(2));
+
+        drop(first);
+        assert_eq!(count(page), Some(1));
+        drop(second);
+        assert_eq!(count(page), None);
+    }
+
+    #[test]
+    fn locks_every_buffer_of_a_password() {
+        let config = crate::Config {
+            number: 4,
+            separator: String::from("→"),
+            list: crate::List::Short1,
+            case: crate::Case::Capitalized,
+        };
+        let password = crate::generate_password(config).unwrap();
+        let page = |text: &str| text.as_ptr() as usize / page_size() * page_size();
+
+        let probe = String::from("probe");
+        let mut locked = LockedPages::default();
+        locked.lock(probe.as_ptr(), probe.capacity());
+        if count(page(&probe)).is_none() {
+            // the limit on locked memory is too low to tell anything
+            return;
+        }
+        drop(locked);
+
+        let buffers = [password.expose()]
+            .into_iter()
+            .chain(password.words().iter().map(String::as_str))
+            .chain(password.separators().iter().map(String::as_str));
+        for buffer in buffers {
+            assert!(count(page(buffer)).is_some(), "{:?} isn't locked", buffer);
+        }
+    }
+}
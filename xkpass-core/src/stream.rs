@@ -0,0 +1,156 @@
+//! Passwords out of a stream of words of unknown length, such as a pipe.
+
+use crate::{
+    estimate_entropy,
+    trace::{event, span},
+    transform, validate_pool, Config, Error, Password, WordTransform,
+};
+use alloc::{string::String, vec::Vec};
+use rand::{seq::SliceRandom, Rng};
+
+/// Generate a password out of the words of `stream` and return it along with
+/// the number of words in the stream, see [`generate_password_from_stream_with`].
+#[cfg(feature = "std")]
+pub fn generate_password_from_stream<I, S>(
+    stream: I,
+    config: &Config,
+) -> Result<(Password, usize), Error>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    generate_password_from_stream_with(stream, config, &mut rand::thread_rng())
+}
+
+/// Generate a password out of the words of `stream`, using `rng` for every
+/// random choice, and return it along with the number of words in the stream.
+///
+/// The stream is read once and only `config.number` words are kept at any
+/// time, picked with reservoir sampling ([Algorithm L]), so it can be far
+/// larger than memory. The list chosen in `config` is ignored, and the
+/// entropy estimate is based on the number of words observed: unlike with
+/// word lists, duplicates in the stream are counted and weaken the password.
+///
+/// [Algorithm L]: https://en.wikipedia.org/wiki/Reservoir_sampling#Optimal:_Algorithm_L
+pub fn generate_password_from_stream_with<I, S, T>(
+    stream: I,
+    config: &Config,
+    rng: &mut T,
+) -> Result<(Password, usize), Error>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+    T: Rng,
+{
+    span!(
+        tracing::Level::DEBUG,
+        "generate_password_from_stream",
+        number = config.number
+    );
+    let (reservoir, pool) = sample(stream, config.number, rng);
+    event!(tracing::Level::DEBUG, pool);
+    validate_pool(pool, config)?;
+
+    let pipeline: [&dyn WordTransform; 1] = [&config.case];
+    // the reservoir isn't in random order, unlike the words picked from a list
+    let mut words: Vec<String> = reservoir
+        .iter()
+        .map(|word| transform::apply_all(&pipeline, word.as_ref(), rng))
+        .collect();
+    words.shuffle(rng);
+
+    let entropy = estimate_entropy(pool, config.number, &pipeline);
+    Ok((Password::new(words, &config.separator, entropy), pool))
+}
+
+/// Pick `k` items of `stream` uniformly at random, in a single pass, and
+/// count the items.
+fn sample<I, T>(stream: I, k: usize, rng: &mut T) -> (Vec<I::Item>, usize)
+where
+    I: IntoIterator,
+    T: Rng,
+{
+    let mut stream = stream.into_iter();
+    let mut reservoir: Vec<I::Item> = stream.by_ref().take(k).collect();
+    let mut count = reservoir.len();
+    if count < k || k == 0 {
+        return (reservoir, count + stream.count());
+    }
+
+    let mut w = libm::exp(libm::log(random(rng)) / k as f64);
+    loop {
+        // number of items to skip before the next one enters the reservoir
+        let skip = libm::floor(libm::log(random(rng)) / libm::log(1.0 - w));
+        let skipped = if skip >= usize::MAX as f64 {
+            stream.by_ref().count()
+        } else {
+            stream.by_ref().take(skip as usize).count()
+        };
+        count += skipped;
+
+        let item = match stream.next() {
+            Some(item) => item,
+            None => return (reservoir, count),
+        };
+        count += 1;
+        reservoir[rng.gen_range(0..k)] = item;
+        w *= libm::exp(libm::log(random(rng)) / k as f64);
+    }
+}
+
+/// A number uniform in (0, 1], so that its logarithm is finite.
+fn random<T: Rng>(rng: &mut T) -> f64 {
+    1.0 - rng.gen::<f64>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn counts_the_stream() {
+        let mut rng = StdRng::from_seed([5; 32]);
+        let config = Config {
+            number: 3,
+            separator: String::from("+"),
+            ..Config::default()
+        };
+        let words = (0..10_000).map(|i| alloc::format!("w{}", i));
+
+        let (password, pool) =
+            generate_password_from_stream_with(words, &config, &mut rng).unwrap();
+        assert_eq!(pool, 10_000);
+        assert_eq!(password.words().len(), 3);
+        assert!((password.entropy() - crate::word_entropy(10_000, 3)).abs() < 1e-9);
+
+        let short = generate_password_from_stream_with(["a", "b"], &config, &mut rng);
+        assert!(matches!(
+            short,
+            Err(Error::NotEnoughWords {
+                requested: 3,
+                available: 2
+            })
+        ));
+    }
+
+    #[test]
+    fn samples_uniformly() {
+        let mut rng = StdRng::from_seed([6; 32]);
+        let mut hits = [0usize; 20];
+        for _ in 0..20_000 {
+            let (picked, count) = sample(0..20, 2, &mut rng);
+            assert_eq!(count, 20);
+            for item in picked {
+                hits[item] += 1;
+            }
+        }
+
+        // every item is expected 2000 times
+        assert!(
+            hits.iter().all(|&n| (1700..2300).contains(&n)),
+            "{:?}",
+            hits
+        );
+    }
+}
@@ -0,0 +1,102 @@
+//! Instrumentation for the `tracing` feature, compiled away without it.
+//!
+//! Spans and events only ever record counts and settings, such as the number
+//! of words picked and the size of the list, never a word or a password.
+
+/// Enter a span, given as to [`tracing::span!`], until the end of the block.
+macro_rules! span {
+    ($($span:tt)*) => {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::span!($($span)*).entered();
+    };
+}
+
+/// Record an event, given as to [`tracing::event!`].
+macro_rules! event {
+    ($($event:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::event!($($event)*);
+    };
+}
+
+pub(crate) use {event, span};
+
+#[cfg(all(test, feature = "tracing", feature = "std"))]
+mod tests {
+    use crate::{generate_password_from, parse_words, Config};
+    use std::{
+        fmt,
+        sync::{Arc, Mutex},
+    };
+    use tracing::{
+        field::{Field, Visit},
+        span, Event, Metadata, Subscriber,
+    };
+
+    /// Records the names of spans and the values of every field.
+    #[derive(Clone, Default)]
+    struct Recorder(Arc<Mutex<Vec<String>>>);
+
+    impl Visit for Recorder {
+        fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+            let record = format!("{}={:?}", field.name(), value);
+            self.0.lock().unwrap().push(record);
+        }
+    }
+
+    impl Subscriber for Recorder {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &span::Attributes<'_>) -> span::Id {
+            self.0
+                .lock()
+                .unwrap()
+                .push(span.metadata().name().to_string());
+            span.record(&mut self.clone());
+            span::Id::from_u64(1)
+        }
+
+        fn record(&self, _: &span::Id, values: &span::Record<'_>) {
+            values.record(&mut self.clone());
+        }
+
+        fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            event.record(&mut self.clone());
+        }
+
+        fn enter(&self, _: &span::Id) {}
+
+        fn exit(&self, _: &span::Id) {}
+    }
+
+    #[test]
+    fn records_spans_without_words() {
+        let recorder = Recorder::default();
+        let password = tracing::subscriber::with_default(recorder.clone(), || {
+            let words = parse_words("apple\nbanana\ncherry\ndate\n");
+            let config = Config {
+                number: 3,
+                ..Config::default()
+            };
+            generate_password_from(&words, config).unwrap()
+        });
+
+        let records = recorder.0.lock().unwrap();
+        for name in [
+            "parse_words",
+            "generate_password",
+            "sample_words",
+            "transform",
+        ] {
+            assert!(records.iter().any(|record| record == name), "{:?}", records);
+        }
+        assert!(records.iter().any(|record| record == "pool=4"));
+        for word in password.words() {
+            assert!(!records.iter().any(|record| record.contains(word.as_str())));
+        }
+    }
+}
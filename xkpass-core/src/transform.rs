@@ -0,0 +1,186 @@
+//! Transformations applied to every word of a password after it has been picked.
+
+use crate::{Case, ExtraCases};
+use alloc::string::String;
+use core::fmt::{self, Debug};
+use rand::{Rng, RngCore};
+use zeroize::Zeroizing;
+
+/// A step of the pipeline every picked word goes through.
+///
+/// [`Case`] is always the first step, more can be added with
+/// [`PasswordGeneratorBuilder::transform`](crate::PasswordGeneratorBuilder::transform).
+///
+/// ```
+/// use xkpass::{PasswordGenerator, WordTransform};
+///
+/// /// Reverse every word.
+/// #[derive(Debug)]
+/// struct Reverse;
+///
+/// impl WordTransform for Reverse {
+///     fn apply(&self, word: String, _rng: &mut dyn rand::RngCore) -> String {
+///         word.chars().rev().collect()
+///     }
+/// }
+///
+/// let generator = PasswordGenerator::builder().transform(Reverse).build().unwrap();
+/// ```
+pub trait WordTransform: Debug + Send + Sync {
+    /// Transform a single word, `rng` must be used for any random choice.
+    fn apply(&self, word: String, rng: &mut dyn RngCore) -> String;
+
+    /// Append the transformed `word` to `out`.
+    ///
+    /// Goes through [`apply`](Self::apply) by default, zeroing the transformed
+    /// copy once appended, override it to write into `out` without allocating
+    /// for every word.
+    fn apply_into(&self, word: &str, out: &mut String, rng: &mut dyn RngCore) {
+        out.push_str(&Zeroizing::new(self.apply(String::from(word), rng)));
+    }
+
+    /// Bits of entropy the transform adds to every word, for the password's
+    /// [`entropy`](crate::Password::entropy) estimate.
+    ///
+    /// Only random choices made with `rng` count, deterministic transforms add nothing.
+    fn entropy(&self) -> f64 {
+        0.0
+    }
+}
+
+impl WordTransform for Case {
+    fn apply(&self, word: String, rng: &mut dyn RngCore) -> String {
+        let word = Zeroizing::new(word);
+        let mut out = String::with_capacity(word.len());
+        self.apply_into(&word, &mut out, rng);
+        out
+    }
+
+    fn apply_into(&self, word: &str, out: &mut String, rng: &mut dyn RngCore) {
+        match self {
+            Case::Upper => word.push_uppercase(out),
+            Case::Lower => word.push_lowercase(out),
+            Case::Capitalized => word.push_capitalized(out),
+            Case::Mixed => word.push_random_case(out, rng),
+        }
+    }
+
+    fn entropy(&self) -> f64 {
+        match self {
+            // one coin flip per word
+            Case::Mixed => 1.0,
+            Case::Upper | Case::Lower | Case::Capitalized => 0.0,
+        }
+    }
+}
+
+/// Replace letters with look-alike digits, as in "l33t".
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Leet;
+
+impl WordTransform for Leet {
+    fn apply(&self, word: String, _rng: &mut dyn RngCore) -> String {
+        let word = Zeroizing::new(word);
+        word.chars()
+            .map(|c| match c {
+                'a' | 'A' => '4',
+                'e' | 'E' => '3',
+                'i' | 'I' => '1',
+                'o' | 'O' => '0',
+                's' | 'S' => '5',
+                't' | 'T' => '7',
+                c => c,
+            })
+            .collect()
+    }
+}
+
+/// A transform made of a closure, see
+/// [`PasswordGeneratorBuilder::map_words`](crate::PasswordGeneratorBuilder::map_words).
+pub(crate) struct MapWords<F> {
+    f: F,
+    entropy: f64,
+}
+
+impl<F> MapWords<F> {
+    pub(crate) fn new(f: F, entropy: f64) -> Self {
+        Self { f, entropy }
+    }
+}
+
+impl<F> Debug for MapWords<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MapWords")
+            .field("entropy", &self.entropy)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<F> WordTransform for MapWords<F>
+where
+    F: Fn(String, &mut dyn RngCore) -> String + Send + Sync,
+{
+    fn apply(&self, word: String, rng: &mut dyn RngCore) -> String {
+        (self.f)(word, rng)
+    }
+
+    fn entropy(&self) -> f64 {
+        self.entropy
+    }
+}
+
+/// Run `word` through every transform of `pipeline`, in order.
+///
+/// The words in between are dropped by the transforms, [`Case`] and [`Leet`]
+/// zero them, transforms of the caller only do if they take care to.
+pub(crate) fn apply_all<T: Rng>(
+    pipeline: &[&dyn WordTransform],
+    word: &str,
+    rng: &mut T,
+) -> String {
+    pipeline.iter().fold(String::from(word), |word, transform| {
+        transform.apply(word, rng)
+    })
+}
+
+/// Run `word` through every transform of `pipeline` and append the result to
+/// `out`, only allocating when there is more than one transform.
+pub(crate) fn apply_all_into<T: Rng>(
+    pipeline: &[&dyn WordTransform],
+    word: &str,
+    out: &mut String,
+    rng: &mut T,
+) {
+    match pipeline.split_last() {
+        None => out.push_str(word),
+        Some((last, [])) => last.apply_into(word, out, rng),
+        Some((last, first)) => {
+            let word = Zeroizing::new(apply_all(first, word, rng));
+            last.apply_into(&word, out, rng);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn applies_in_order() {
+        let pipeline: [&dyn WordTransform; 2] = [&Case::Capitalized, &Leet];
+        let word = apply_all(&pipeline, "toaster", &mut rand::thread_rng());
+
+        assert_eq!(word, "704573r");
+
+        let mut out = String::from("x-");
+        apply_all_into(&pipeline, "toaster", &mut out, &mut rand::thread_rng());
+        assert_eq!(out, "x-704573r");
+    }
+
+    #[test]
+    fn adds_entropy_for_random_choices() {
+        assert_eq!(Case::Mixed.entropy(), 1.0);
+        assert_eq!(Case::Capitalized.entropy(), 0.0);
+        assert_eq!(Leet.entropy(), 0.0);
+    }
+}
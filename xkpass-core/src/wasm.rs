@@ -0,0 +1,45 @@
+//! JavaScript bindings, built with [wasm-bindgen](https://rustwasm.github.io/docs/wasm-bindgen/).
+//!
+//! ```js
+//! import init, { generate } from "xkpass";
+//!
+//! await init();
+//! const password = generate(JSON.stringify({ number: 4, case: "capitalized" }));
+//! ```
+
+use crate::{generate_password, Config};
+use alloc::string::{String, ToString};
+use wasm_bindgen::prelude::*;
+
+/// Generate a password from settings given as JSON, with the same fields as [`Config`].
+///
+/// Missing fields fall back to their defaults, so `"{}"` is a valid configuration.
+/// Randomness comes from the browser's `crypto.getRandomValues`.
+#[wasm_bindgen]
+pub fn generate(config_json: &str) -> Result<String, JsError> {
+    generate_from_json(config_json).map_err(|err| JsError::new(&err))
+}
+
+fn generate_from_json(config_json: &str) -> Result<String, String> {
+    let config: Config = serde_json::from_str(config_json).map_err(|err| err.to_string())?;
+    let password = generate_password(config).map_err(|err| err.to_string())?;
+
+    Ok(password.into_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generates_from_json() {
+        let password = generate_from_json(r#"{"number": 3, "separator": "+"}"#).unwrap();
+        assert_eq!(password.split('+').count(), 3);
+    }
+
+    #[test]
+    fn reports_invalid_json() {
+        assert!(generate_from_json(r#"{"list": "huge"}"#).is_err());
+        assert!(generate_from_json(r#"{"number": 0}"#).is_err());
+    }
+}
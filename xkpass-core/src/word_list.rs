@@ -0,0 +1,164 @@
+//! Access to the embedded word lists, and parsing of word list files.
+//!
+//! The lists are split into arrays of words by the build script, which also
+//! sorts an index of each of them for [`List::position`].
+
+use crate::{trace::span, List};
+use alloc::vec::Vec;
+
+include!(concat!(env!("OUT_DIR"), "/word_lists.rs"));
+
+impl List {
+    /// Every embedded word list.
+    pub const ALL: [List; 3] = [List::Long, List::Short1, List::Short2];
+
+    /// Iterate over the words of the list, in the order EFF published them.
+    pub fn words(&self) -> impl Iterator<Item = &'static str> {
+        self.as_slice().iter().copied()
+    }
+
+    /// The words of the list, in the order EFF published them.
+    pub const fn as_slice(&self) -> &'static [&'static str] {
+        match self {
+            List::Long => &WORDS_LONG,
+            List::Short1 => &WORDS_SHORT1,
+            List::Short2 => &WORDS_SHORT2,
+        }
+    }
+
+    /// Position of `word` in the list, found by binary search of an index
+    /// sorted at build time. The lists are lowercase, so is `word` expected to be.
+    pub fn position(&self, word: &str) -> Option<usize> {
+        let words = self.as_slice();
+        let index: &[u16] = match self {
+            List::Long => &WORDS_LONG_INDEX,
+            List::Short1 => &WORDS_SHORT1_INDEX,
+            List::Short2 => &WORDS_SHORT2_INDEX,
+        };
+        index
+            .binary_search_by(|&i| words[i as usize].cmp(word))
+            .ok()
+            .map(|i| index[i] as usize)
+    }
+
+    /// Whether `word` is in the list, see [`List::position`].
+    pub fn contains(&self, word: &str) -> bool {
+        self.position(word).is_some()
+    }
+
+    /// Number of words in the list, known at compile time.
+    pub const fn len(&self) -> usize {
+        self.as_slice().len()
+    }
+
+    /// Whether the list has no words, which is never the case for the embedded lists.
+    pub const fn is_empty(&self) -> bool {
+        self.as_slice().is_empty()
+    }
+
+    /// Bits of entropy contributed by each word picked uniformly at random from the list.
+    pub fn entropy_per_word(&self) -> f64 {
+        libm::log2(self.len() as f64)
+    }
+}
+
+/// Split the contents of a word list file into its words, keeping the first
+/// occurrence of duplicates so that they can't inflate the entropy estimate.
+///
+/// See [`parse_line`] for the format of each line.
+#[cfg(feature = "std")]
+pub fn parse_words(contents: &str) -> Vec<&str> {
+    span!(tracing::Level::DEBUG, "parse_words", bytes = contents.len());
+    let mut seen = std::collections::HashSet::new();
+    let words: Vec<&str> = contents
+        .lines()
+        .filter_map(parse_line)
+        .filter(|word| seen.insert(*word))
+        .collect();
+    crate::trace::event!(tracing::Level::DEBUG, words = words.len());
+    words
+}
+
+/// The word on a line of a word list file, nothing for blank lines and lines
+/// starting with `#`. For lists in diceware format, such as EFF's, the word is
+/// the last column.
+pub fn parse_line(line: &str) -> Option<&str> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+
+    line.split_whitespace().last()
+}
+
+/// Collect the words of the list.
+///
+/// The words are already split at build time, prefer [`List::as_slice`] to
+/// avoid the copy.
+pub fn parse_word_list(list: &List) -> Vec<&'static str> {
+    span!(tracing::Level::DEBUG, "parse_word_list", list = list.name());
+    list.as_slice().to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_words() {
+        assert_eq!(List::Long.len(), 7776);
+        assert_eq!(List::Short1.len(), 1296);
+        assert_eq!(List::Short2.len(), 1296);
+        assert!(List::ALL.iter().all(|list| !list.is_empty()));
+    }
+
+    #[test]
+    fn counts_words_at_compile_time() {
+        const LONG: usize = List::Long.len();
+        assert_eq!(LONG, 7776);
+        assert_eq!(List::Short2.as_slice()[0], "aardvark");
+    }
+
+    #[test]
+    fn computes_entropy_per_word() {
+        assert!((List::Long.entropy_per_word() - 12.925).abs() < 0.001);
+        assert!((List::Short1.entropy_per_word() - 10.340).abs() < 0.001);
+    }
+
+    #[test]
+    fn looks_up_words() {
+        for list in List::ALL {
+            for (i, word) in list.words().enumerate() {
+                assert_eq!(list.position(word), Some(i));
+            }
+        }
+        assert!(List::Short1.contains("acid"));
+        assert!(!List::Short1.contains("abacus"));
+        assert!(!List::Long.contains("Abacus"));
+        assert!(!List::Long.contains(""));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn parses_word_lists() {
+        let words = parse_words("# my words\napple\n\n11111\tbanana\ncherry\napple\n");
+        assert_eq!(words, ["apple", "banana", "cherry"]);
+    }
+
+    #[test]
+    fn parses_lines() {
+        assert_eq!(parse_line("  11111\tabacus \r"), Some("abacus"));
+        assert_eq!(parse_line("# 11111 abacus"), None);
+        assert_eq!(parse_line(" \t"), None);
+    }
+
+    #[test]
+    fn words_are_unique() {
+        for list in List::ALL {
+            let mut words = parse_word_list(&list);
+            words.sort_unstable();
+            words.dedup();
+            assert_eq!(words.len(), list.len());
+        }
+    }
+}
@@ -0,0 +1,179 @@
+//! Invariants of generated passwords, checked over random settings with proptest.
+
+#![cfg(feature = "std")]
+
+use proptest::prelude::*;
+use std::collections::HashSet;
+use xkpass::{Case, Config, List};
+
+fn any_list() -> impl Strategy<Value = List> {
+    prop::sample::select(List::ALL.to_vec())
+}
+
+fn any_case() -> impl Strategy<Value = Case> {
+    prop_oneof![
+        Just(Case::Upper),
+        Just(Case::Lower),
+        Just(Case::Capitalized),
+        Just(Case::Mixed),
+    ]
+}
+
+/// Separators that can't be found inside the words of the embedded lists.
+fn clean_separator() -> impl Strategy<Value = String> {
+    "[ .,_+=:;|/~0-9]{1,3}"
+}
+
+/// Every ordered choice of `number` distinct words out of `words`.
+fn arrangements<'a>(words: &[&'a str], number: usize) -> Vec<Vec<&'a str>> {
+    if number == 0 {
+        return vec![Vec::new()];
+    }
+
+    let mut all = Vec::new();
+    for (i, word) in words.iter().enumerate() {
+        let rest: Vec<&str> = words
+            .iter()
+            .enumerate()
+            .filter(|&(j, _)| j != i)
+            .map(|(_, word)| *word)
+            .collect();
+        for mut tail in arrangements(&rest, number - 1) {
+            tail.insert(0, word);
+            all.push(tail);
+        }
+    }
+
+    all
+}
+
+proptest! {
+    #[test]
+    fn splits_into_distinct_list_words(
+        number in 1..=12usize,
+        separator in clean_separator(),
+        list in any_list(),
+        case in any_case(),
+    ) {
+        let config = Config {
+            number,
+            separator: separator.clone(),
+            list: list.clone(),
+            case,
+        };
+        let password = xkpass::generate_password(config).unwrap();
+
+        let words: Vec<String> = password
+            .expose()
+            .split(&separator)
+            .map(str::to_lowercase)
+            .collect();
+        prop_assert_eq!(words.len(), number);
+        prop_assert!(words.iter().all(|word| list.contains(word)));
+        prop_assert_eq!(words.iter().collect::<HashSet<_>>().len(), number);
+    }
+
+    #[test]
+    fn applies_the_case(number in 1..=12usize, list in any_list(), case in any_case()) {
+        let config = Config { number, list, case: case.clone(), ..Config::default() };
+        let password = xkpass::generate_password(config).unwrap();
+
+        for word in password.words() {
+            let lower = word.to_lowercase();
+            let upper = word.to_uppercase();
+            match case {
+                Case::Upper => prop_assert_eq!(word, &upper),
+                Case::Lower => prop_assert_eq!(word, &lower),
+                Case::Capitalized => {
+                    prop_assert_eq!(&word[..1], &upper[..1]);
+                    prop_assert_eq!(&word[1..], &lower[1..]);
+                }
+                Case::Mixed => prop_assert!(*word == lower || *word == upper),
+            }
+        }
+    }
+
+    #[test]
+    fn keeps_separators_out_of_words(
+        number in 1..=12usize,
+        separator in clean_separator(),
+        list in any_list(),
+        case in any_case(),
+    ) {
+        let config = Config {
+            number,
+            separator: separator.clone(),
+            list,
+            case,
+        };
+        let password = xkpass::generate_password(config).unwrap();
+
+        prop_assert!(password.words().iter().all(|word| !word.contains(&separator)));
+        prop_assert_eq!(password.separators().len(), number - 1);
+        prop_assert!(password.separators().iter().all(|between| *between == separator));
+    }
+
+    #[test]
+    fn estimates_entropy_by_counting(
+        (pool, number) in (1..=6usize).prop_flat_map(|pool| (Just(pool), 1..=pool)),
+        case in any_case(),
+    ) {
+        let names = ["ant", "bee", "cat", "dog", "eel", "fox"];
+        let words = &names[..pool];
+        let config = Config { number, case: case.clone(), ..Config::default() };
+        let password = xkpass::generate_password_from(words, config).unwrap();
+
+        // every password that could have come out, with each case a word can take
+        let mut passwords = HashSet::new();
+        for arrangement in arrangements(words, number) {
+            let mut cased = vec![String::new()];
+            for word in arrangement {
+                let forms = match case {
+                    Case::Upper => vec![word.to_uppercase()],
+                    Case::Lower => vec![word.to_string()],
+                    Case::Capitalized => vec![word[..1].to_uppercase() + &word[1..]],
+                    Case::Mixed => vec![word.to_string(), word.to_uppercase()],
+                };
+                cased = cased
+                    .iter()
+                    .flat_map(|prefix| {
+                        forms.iter().map(move |form| format!("{} {}", prefix, form))
+                    })
+                    .collect();
+            }
+            passwords.extend(cased);
+        }
+
+        let counted = (passwords.len() as f64).log2();
+        prop_assert!((password.entropy() - counted).abs() < 1e-9);
+        let arranged = (arrangements(words, number).len() as f64).log2();
+        prop_assert!((xkpass::word_entropy(pool, number) - arranged).abs() < 1e-9);
+    }
+
+    #[test]
+    fn never_panics(
+        number in 0..10_000usize,
+        separator in ".{0,8}",
+        list in any_list(),
+        case in any_case(),
+    ) {
+        let valid = (1..=list.len()).contains(&number) && !separator.chars().any(char::is_control);
+        let config = Config { number, separator, list, case };
+        let password = xkpass::generate_password(config);
+        prop_assert_eq!(password.is_ok(), valid);
+    }
+
+    #[test]
+    fn never_panics_on_custom_words(
+        words in prop::collection::vec(".{0,12}", 0..20),
+        number in 0..25usize,
+        separator in ".{0,4}",
+        case in any_case(),
+    ) {
+        let words: Vec<&str> = words.iter().map(String::as_str).collect();
+        let config = Config { number, separator, case, ..Config::default() };
+        if let Ok(password) = xkpass::generate_password_from(&words, config) {
+            prop_assert_eq!(password.words().len(), number);
+        }
+    }
+}